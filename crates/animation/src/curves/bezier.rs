@@ -0,0 +1,93 @@
+use super::ParametricCurve;
+
+/// CSS-style cubic Bézier easing going from `(0, 0)` to `(1, 1)` with control
+/// points `(x1, y1)` and `(x2, y2)`.
+///
+/// Unlike [`Cubic`], `t` is found with Newton iteration, falling back to
+/// bisection where the slope is too flat for it to converge.
+///
+/// [`Cubic`]: super::Cubic
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub struct CubicBezier {
+    pub(super) x1: f32,
+    pub(super) y1: f32,
+    pub(super) x2: f32,
+    pub(super) y2: f32,
+}
+
+impl CubicBezier {
+    const BISECTION_ITERATIONS: u8 = 32;
+    const EPSILON: f32 = 1e-6;
+    const NEWTON_ITERATIONS: u8 = 8;
+
+    pub const fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self { x1, y1, x2, y2 }
+    }
+
+    /// Returns coefficients of the polynomial `a * t^3 + b * t^2 + c * t` for a
+    /// single axis with the given control point coordinates.
+    fn coefficients(p1: f32, p2: f32) -> (f32, f32, f32) {
+        let c = 3.0 * p1;
+        let b = 3.0f32.mul_add(p2 - p1, -c);
+
+        (1.0 - c - b, b, c)
+    }
+
+    fn evaluate((a, b, c): (f32, f32, f32), t: f32) -> f32 {
+        a.mul_add(t, b).mul_add(t, c) * t
+    }
+
+    fn derivative((a, b, c): (f32, f32, f32), t: f32) -> f32 {
+        (3.0 * a).mul_add(t, 2.0 * b).mul_add(t, c)
+    }
+
+    /// Finds `t` at which the x-polynomial equals `x`.
+    fn solve(&self, x: f32) -> f32 {
+        let coefficients = Self::coefficients(self.x1, self.x2);
+        let mut t = x;
+
+        for _ in 0..Self::NEWTON_ITERATIONS {
+            let error = Self::evaluate(coefficients, t) - x;
+
+            if error.abs() < Self::EPSILON {
+                return t;
+            }
+
+            let slope = Self::derivative(coefficients, t);
+
+            if slope.abs() < Self::EPSILON {
+                break;
+            }
+
+            t -= error / slope;
+        }
+
+        let (mut low, mut high) = (0.0f32, 1.0f32);
+
+        t = x;
+
+        for _ in 0..Self::BISECTION_ITERATIONS {
+            let estimate = Self::evaluate(coefficients, t);
+
+            if (estimate - x).abs() < Self::EPSILON {
+                break;
+            }
+
+            if estimate < x {
+                low = t;
+            } else {
+                high = t;
+            }
+
+            t = f32::midpoint(low, high);
+        }
+
+        t
+    }
+}
+
+impl ParametricCurve<f32> for CubicBezier {
+    fn transform_internal(&self, t: f32) -> f32 {
+        Self::evaluate(Self::coefficients(self.y1, self.y2), self.solve(t))
+    }
+}
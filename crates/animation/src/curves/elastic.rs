@@ -20,7 +20,7 @@ impl ParametricCurve<f32> for ElasticInCurve {
 
         t -= 1.0;
 
-        -(10.0 * t).exp2() * ((t - s) * (PI * 2.0) / self.period).cos()
+        -(10.0 * t).exp2() * ((t - s) * (PI * 2.0) / self.period).sin()
     }
 }
 
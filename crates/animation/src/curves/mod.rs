@@ -1,3 +1,4 @@
+mod bezier;
 mod bounce;
 mod cubic;
 mod decelerate;
@@ -13,6 +14,7 @@ mod threshold;
 use three_point_cubic::ThreePointCubic;
 
 pub use self::{
+    bezier::CubicBezier,
     bounce::{BounceInCurve, BounceInOutCurve, BounceOutCurve},
     cubic::Cubic,
     decelerate::DecelerateCurve,
@@ -27,8 +29,58 @@ trait ParametricCurve<T> {
     fn transform_internal(&self, t: f32) -> T;
 }
 
+/// Amount of samples used to check whether a curve is monotonic.
+const MONOTONIC_SAMPLES: u8 = 64;
+/// Amount of bisection iterations used to find the inverse of a curve.
+const INVERSE_ITERATIONS: u8 = 32;
+
 pub trait ICurve {
     fn transform(&self, t: f32) -> f32;
+
+    /// Returns `true` if the curve never decreases across `[0, 1]`.
+    ///
+    /// This is checked by sampling the curve, so very short dips between
+    /// samples may go unnoticed.
+    fn is_monotonic(&self) -> bool {
+        let mut previous = self.transform(0.0);
+
+        for step in 1..=MONOTONIC_SAMPLES {
+            let value = self.transform(f32::from(step) / f32::from(MONOTONIC_SAMPLES));
+
+            if value < previous - 1e-6 {
+                return false;
+            }
+
+            previous = value;
+        }
+
+        true
+    }
+
+    /// Returns `t` at which the curve produces the given eased value, found
+    /// using bisection.
+    ///
+    /// Returns `None` if the curve is not monotonic (e.g. bounce or elastic),
+    /// since there may be several such `t`, or if the value is never reached.
+    fn inverse(&self, eased: f32) -> Option<f32> {
+        if !self.is_monotonic() || !(self.transform(0.0)..=self.transform(1.0)).contains(&eased) {
+            return None;
+        }
+
+        let (mut low, mut high) = (0.0f32, 1.0f32);
+
+        for _ in 0..INVERSE_ITERATIONS {
+            let middle = f32::midpoint(low, high);
+
+            if self.transform(middle) < eased {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        Some(f32::midpoint(low, high))
+    }
 }
 
 impl<T: ParametricCurve<f32>> ICurve for T {
@@ -48,6 +100,7 @@ pub enum Curve {
     None,
     Linear(Linear),
     Cubic(Cubic),
+    CubicBezier(CubicBezier),
     ThreePointCubic(ThreePointCubic),
     Threshold(Threshold),
     SawTooth(SawTooth),
@@ -135,6 +188,11 @@ impl Curve {
         Self::Cubic(Cubic::new(a, b, c, d))
     }
 
+    #[must_use]
+    pub const fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self::CubicBezier(CubicBezier::new(x1, y1, x2, y2))
+    }
+
     #[must_use]
     pub const fn threshold(threshold: f32) -> Self {
         Self::Threshold(Threshold { threshold })
@@ -157,6 +215,22 @@ impl Curve {
             step_count,
         })
     }
+
+    /// Samples the curve at `steps + 1` evenly spaced points across `[0, 1]`,
+    /// returning `(t, eased)` pairs. Useful for plotting a curve.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn sample(&self, steps: usize) -> Vec<(f32, f32)> {
+        let steps = steps.max(1);
+
+        (0..=steps)
+            .map(|step| {
+                let t = step as f32 / steps as f32;
+
+                (t, self.transform(t))
+            })
+            .collect()
+    }
 }
 
 impl ParametricCurve<f32> for Curve {
@@ -165,6 +239,7 @@ impl ParametricCurve<f32> for Curve {
             Self::None => 1.0,
             Self::Linear(curve) => curve.transform_internal(t),
             Self::Cubic(curve) => curve.transform_internal(t),
+            Self::CubicBezier(curve) => curve.transform_internal(t),
             Self::ThreePointCubic(curve) => curve.transform_internal(t),
             Self::Threshold(curve) => curve.transform_internal(t),
             Self::SawTooth(curve) => curve.transform_internal(t),
@@ -179,3 +254,99 @@ impl ParametricCurve<f32> for Curve {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Curve, ICurve, ParametricCurve};
+
+    #[test]
+    fn test_sample_linear() {
+        let samples = Curve::LINEAR.sample(10);
+
+        assert_eq!(samples.len(), 11);
+
+        for (t, eased) in samples {
+            assert!((t - eased).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_sample_endpoints() {
+        for curve in [
+            Curve::LINEAR,
+            Curve::EASE,
+            Curve::EASE_IN_OUT,
+            Curve::cubic_bezier(0.25, 0.1, 0.25, 1.0),
+            Curve::FAST_OUT_SLOW_IN,
+            Curve::FAST_EASE_IN_TO_SLOW_EASE_OUT,
+            Curve::BOUNCE_IN,
+            Curve::BOUNCE_OUT,
+            Curve::BOUNCE_IN_OUT,
+            Curve::ELASTIC_IN,
+            Curve::ELASTIC_OUT,
+            Curve::ELASTIC_IN_OUT,
+            Curve::DECELERATE,
+            Curve::stepped(4, false, false),
+        ] {
+            let samples = curve.sample(16);
+
+            assert_eq!(samples.first(), Some(&(0.0, 0.0)), "{curve:?}");
+            assert_eq!(samples.last(), Some(&(1.0, 1.0)), "{curve:?}");
+        }
+    }
+
+    #[test]
+    fn test_inverse_round_trip() {
+        for curve in [
+            Curve::LINEAR,
+            Curve::EASE,
+            Curve::EASE_IN_OUT,
+            Curve::DECELERATE,
+        ] {
+            for t in [0.1, 0.3, 0.5, 0.8] {
+                let inverse = curve.inverse(curve.transform(t)).unwrap();
+
+                // cubic curves are only accurate up to `Cubic::CUBIC_ERROR_BOUND`
+                assert!((inverse - t).abs() < 5e-3, "{curve:?} at {t}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_cubic_bezier_ease() {
+        let ease = Curve::cubic_bezier(0.25, 0.1, 0.25, 1.0);
+
+        for (t, expected) in [(0.25, 0.408_510_6), (0.5, 0.802_403_4), (0.75, 0.960_459)] {
+            assert!((ease.transform(t) - expected).abs() < 1e-4, "ease at {t}");
+        }
+
+        // control points outside of `[0, 1]` on the y axis overshoot
+        assert!(Curve::cubic_bezier(0.68, -0.55, 0.265, 1.55).transform(0.1) < 0.0);
+    }
+
+    #[test]
+    fn test_elastic_endpoints() {
+        for curve in [Curve::ELASTIC_IN, Curve::ELASTIC_OUT, Curve::ELASTIC_IN_OUT] {
+            assert!(curve.transform(0.0).abs() < f32::EPSILON, "{curve:?}");
+            assert!(
+                (curve.transform(1.0) - 1.0).abs() < f32::EPSILON,
+                "{curve:?}"
+            );
+
+            // `transform` returns endpoints as is, so the formulas have to
+            // reach them on their own to not jump at the start or the end
+            assert!(curve.transform_internal(0.0).abs() < 2e-3, "{curve:?}");
+            assert!(
+                (curve.transform_internal(1.0) - 1.0).abs() < 2e-3,
+                "{curve:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_inverse_non_monotonic() {
+        assert!(!Curve::BOUNCE_OUT.is_monotonic());
+        assert_eq!(Curve::BOUNCE_OUT.inverse(0.9), None);
+        assert_eq!(Curve::LINEAR.inverse(1.5), None);
+    }
+}
@@ -1,5 +1,6 @@
 mod curves;
 mod player;
+mod spring;
 mod value;
 
 use std::time::Duration;
@@ -9,6 +10,7 @@ use meralus_shared::Lerp;
 pub use self::{
     curves::{Curve, ICurve},
     player::AnimationPlayer,
+    spring::SpringAnimation,
     value::TweenValue,
 };
 
@@ -127,6 +129,10 @@ impl Animation {
         self.restart_behaviour.is_end_value()
     }
 
+    pub const fn get_curve(&self) -> Curve {
+        self.curve
+    }
+
     pub const fn get_duration(&self) -> f32 {
         self.delay + self.duration
     }
@@ -155,6 +161,61 @@ impl Animation {
         self.value = self.origin;
     }
 
+    /// Swaps origin and destination, moving the elapsed time to the point
+    /// where the curve produces the current value, so that the animation
+    /// continues from it back towards where it started.
+    ///
+    /// Curves that can't be inverted (like [`Curve::BOUNCE_OUT`]) have the
+    /// elapsed time mirrored instead, which keeps the value continuous only if
+    /// the curve is symmetric.
+    pub fn reverse(&mut self) {
+        let progress = if self.duration > 0.0 {
+            (self.elapsed - self.delay).clamp(0.0, self.duration) / self.duration
+        } else {
+            1.0
+        };
+
+        (self.origin, self.destination) = (self.destination, self.origin);
+
+        let progress = self
+            .curve
+            .inverse(1.0 - self.curve.transform(progress))
+            .unwrap_or(1.0 - progress);
+
+        self.elapsed = self.duration.mul_add(progress, self.delay);
+    }
+
+    /// Jumps to the given point in time (in seconds, delay included) and
+    /// recomputes the current value.
+    ///
+    /// Time is clamped to the total duration of all repeats, while infinite
+    /// animations wrap around to the matching point of their cycle.
+    pub fn seek(&mut self, seconds: f32) {
+        let seconds = seconds.max(0.0);
+
+        self.elapsed = match self.repeat {
+            RepeatMode::Once => seconds.min(self.get_duration()),
+            RepeatMode::Times(n) => seconds.min(self.get_duration() * f32::from(n)),
+            RepeatMode::Infinite => {
+                let cycle = self.delay
+                    + if self.is_backwards() {
+                        self.duration * 2.0
+                    } else {
+                        self.duration
+                    };
+
+                if seconds > cycle {
+                    seconds % cycle
+                } else {
+                    seconds
+                }
+            }
+        };
+        self.value = self.origin;
+
+        self.advance(0.0);
+    }
+
     pub const fn set_delay(&mut self, delay: u64) {
         self.delay = Duration::from_millis(delay).as_secs_f32();
     }
@@ -207,3 +268,112 @@ impl Animation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use crate::{Animation, Curve, RepeatMode};
+
+    #[test]
+    fn test_reverse_keeps_value_continuous() {
+        let mut animation = Animation::new(0.0, 10.0, 1000, Curve::LINEAR, RepeatMode::Once);
+
+        animation.advance(0.3);
+
+        let before = animation.get::<f32>();
+
+        animation.reverse();
+        animation.advance(0.0);
+
+        assert!((animation.get::<f32>() - before).abs() < 1e-4);
+
+        animation.advance(0.1);
+
+        assert!((animation.get::<f32>() - 2.0).abs() < 1e-4);
+
+        animation.advance(1.0);
+
+        assert!(animation.is_finished());
+        assert!(animation.get::<f32>().abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_reverse_asymmetric_curve() {
+        let mut animation = Animation::new(0.0, 10.0, 1000, Curve::EASE_IN, RepeatMode::Once);
+
+        animation.advance(0.6);
+
+        let before = animation.get::<f32>();
+
+        animation.reverse();
+        animation.advance(0.0);
+
+        // mirroring the elapsed time would jump from 4.3 to 7.9
+        assert!((animation.get::<f32>() - before).abs() < 0.05);
+
+        // the value keeps moving back towards the start
+        animation.advance(0.1);
+
+        assert!(animation.get::<f32>() < before);
+
+        animation.advance(1.0);
+
+        assert!(animation.is_finished());
+        assert!(animation.get::<f32>().abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_seek_matches_advance() {
+        for repeat in [RepeatMode::Once, RepeatMode::Times(3), RepeatMode::Infinite] {
+            for seconds in [0.5, 1.0] {
+                let mut seeked = Animation::new(0.0, 10.0, 1000, Curve::EASE_IN_OUT, repeat);
+                let mut advanced = seeked;
+
+                seeked.seek(seconds);
+                advanced.advance(seconds);
+
+                assert!((seeked.get::<f32>() - advanced.get::<f32>()).abs() < 1e-4);
+                assert!((seeked.get_elapsed() - advanced.get_elapsed()).abs() < 1e-4);
+            }
+        }
+
+        let mut animation = Animation::new(0.0, 10.0, 1000, Curve::LINEAR, RepeatMode::Once);
+
+        animation.seek(5.0);
+
+        assert!((animation.get_elapsed() - 1.0).abs() < 1e-4);
+        assert!((animation.get::<f32>() - 10.0).abs() < 1e-4);
+
+        let mut animation = Animation::new(0.0, 10.0, 1000, Curve::LINEAR, RepeatMode::Infinite);
+
+        animation.seek(2.25);
+
+        assert!((animation.get::<f32>() - 2.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut animation =
+            Animation::new_with_delay(0.0, 10.0, 1000, 500, Curve::LINEAR, RepeatMode::Once);
+
+        animation.seek(1.0);
+
+        assert!((animation.get::<f32>() - 5.0).abs() < 1e-4);
+
+        animation.seek(0.25);
+
+        assert!(animation.get::<f32>().abs() < 1e-4);
+        assert!(!animation.is_finished());
+    }
+
+    #[test]
+    fn test_vec3_animation() {
+        let mut animation =
+            Animation::new(Vec3::ZERO, Vec3::ONE, 500, Curve::LINEAR, RepeatMode::Once);
+
+        animation.advance(0.25);
+
+        assert!(animation.get::<Vec3>().abs_diff_eq(Vec3::splat(0.5), 1e-4));
+    }
+}
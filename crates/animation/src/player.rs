@@ -1,16 +1,74 @@
-use std::collections::HashSet;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt,
+    rc::Rc,
+};
 
 use indexmap::IndexMap;
 
 use crate::{Animation, TweenValue};
 
-#[derive(Debug, Clone, Default, PartialEq)]
+/// Setter invoked with the current value of a bound animation every time it
+/// advances. It's shared, so that clones of the player keep their bindings.
+type Setter = Rc<RefCell<dyn FnMut(TweenValue)>>;
+/// Callback invoked once an animation finishes.
+type Callback = Rc<RefCell<dyn FnMut()>>;
+
+#[derive(Clone)]
 pub struct AnimationPlayer {
     animations: IndexMap<String, Animation>,
     running: HashSet<String>,
+    groups: HashMap<String, Vec<String>>,
+    bindings: HashMap<String, Setter>,
+    callbacks: HashMap<String, Callback>,
+    /// Multiplier of the time passed to [`advance`].
+    ///
+    /// [`advance`]: AnimationPlayer::advance
+    speed: f32,
     enabled: bool,
 }
 
+impl Default for AnimationPlayer {
+    fn default() -> Self {
+        Self {
+            animations: IndexMap::new(),
+            running: HashSet::new(),
+            groups: HashMap::new(),
+            bindings: HashMap::new(),
+            callbacks: HashMap::new(),
+            speed: 1.0,
+            enabled: false,
+        }
+    }
+}
+
+/// Players are compared by their animations and state, ignoring setters and
+/// callbacks, as closures can't be compared.
+impl PartialEq for AnimationPlayer {
+    fn eq(&self, other: &Self) -> bool {
+        self.animations == other.animations
+            && self.running == other.running
+            && self.groups == other.groups
+            && self.speed.total_cmp(&other.speed).is_eq()
+            && self.enabled == other.enabled
+    }
+}
+
+impl fmt::Debug for AnimationPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnimationPlayer")
+            .field("animations", &self.animations)
+            .field("running", &self.running)
+            .field("groups", &self.groups)
+            .field("bindings", &self.bindings.keys().collect::<Vec<_>>())
+            .field("callbacks", &self.callbacks.keys().collect::<Vec<_>>())
+            .field("speed", &self.speed)
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
 impl AnimationPlayer {
     pub fn reset(&mut self) {
         for animation in self.animations.values_mut() {
@@ -30,14 +88,35 @@ impl AnimationPlayer {
         self.enabled = false;
     }
 
+    pub const fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Sets the multiplier of time animations are advanced by, e.g. `0.5` for
+    /// slow motion. Negative multipliers are clamped to zero, as animations
+    /// can't be played in reverse.
+    pub const fn set_speed(&mut self, multiplier: f32) {
+        self.speed = multiplier.max(0.0);
+    }
+
     pub fn advance(&mut self, delta: f32) {
+        let delta = delta * self.speed;
+
         if self.enabled {
             for (name, animation) in &mut self.animations {
                 if self.running.contains(name) {
                     animation.advance(delta);
 
+                    if let Some(setter) = self.bindings.get_mut(name) {
+                        (setter.borrow_mut())(animation.value);
+                    }
+
                     if animation.is_finished() {
                         self.running.remove(name);
+
+                        if let Some(callback) = self.callbacks.get_mut(name) {
+                            (callback.borrow_mut())();
+                        }
                     }
                 }
             }
@@ -48,6 +127,66 @@ impl AnimationPlayer {
         self.animations.insert(name.into(), animation);
     }
 
+    /// Binds a setter to the animation with the given name, so that every
+    /// [`advance`] writes the interpolated value through it instead of
+    /// requiring it to be pulled out with [`get_value`].
+    ///
+    /// Since the setter must be `'static`, it should operate on an owned
+    /// target (e.g. `Rc<Cell<f32>>`) or send values through a channel.
+    ///
+    /// [`advance`]: AnimationPlayer::advance
+    /// [`get_value`]: AnimationPlayer::get_value
+    pub fn bind<T: Into<String>, V: From<TweenValue>, F: FnMut(V) + 'static>(
+        &mut self,
+        name: T,
+        mut setter: F,
+    ) {
+        self.bindings.insert(
+            name.into(),
+            Rc::new(RefCell::new(move |value| setter(V::from(value)))),
+        );
+    }
+
+    pub fn unbind<T: AsRef<str>>(&mut self, name: T) {
+        self.bindings.remove(name.as_ref());
+    }
+
+    /// Sets a callback invoked by [`advance`] every time the animation with
+    /// the given name finishes and stops running, replacing the previous one.
+    ///
+    /// Animations with [`RepeatMode::Infinite`] never finish, so the callback
+    /// is never invoked for them.
+    ///
+    /// [`advance`]: AnimationPlayer::advance
+    /// [`RepeatMode::Infinite`]: crate::RepeatMode::Infinite
+    pub fn on_finished<T: Into<String>, F: FnMut() + 'static>(&mut self, name: T, callback: F) {
+        self.callbacks
+            .insert(name.into(), Rc::new(RefCell::new(callback)));
+    }
+
+    pub fn remove<T: AsRef<str>>(&mut self, name: T) -> Option<Animation> {
+        let name = name.as_ref();
+
+        self.running.remove(name);
+        self.bindings.remove(name);
+        self.callbacks.remove(name);
+
+        self.animations.shift_remove(name)
+    }
+
+    /// Removes all animations along with their bindings and callbacks. Groups
+    /// are kept, so they can be reused with newly added animations.
+    pub fn clear(&mut self) {
+        self.animations.clear();
+        self.running.clear();
+        self.bindings.clear();
+        self.callbacks.clear();
+    }
+
+    pub fn contains<T: AsRef<str>>(&self, name: T) -> bool {
+        self.animations.contains_key(name.as_ref())
+    }
+
     pub fn get_at(&self, index: usize) -> Option<(&str, &Animation)> {
         self.animations
             .get_index(index)
@@ -72,6 +211,68 @@ impl AnimationPlayer {
         }
     }
 
+    /// Stops advancing the animation while keeping its current value, so
+    /// [`resume`] continues it from the same point.
+    ///
+    /// [`resume`]: AnimationPlayer::resume
+    pub fn pause<T: AsRef<str>>(&mut self, name: T) {
+        self.running.remove(name.as_ref());
+    }
+
+    /// Continues paused animation without resetting it, unlike [`play`].
+    ///
+    /// [`play`]: AnimationPlayer::play
+    pub fn resume<T: Into<String>>(&mut self, name: T) {
+        let name = name.into();
+
+        if self.animations.contains_key(&name) {
+            self.running.insert(name);
+        }
+    }
+
+    pub fn is_running<T: AsRef<str>>(&self, name: T) -> bool {
+        self.running.contains(name.as_ref())
+    }
+
+    pub fn add_group<T: Into<String>, I: IntoIterator<Item = N>, N: Into<String>>(
+        &mut self,
+        name: T,
+        animations: I,
+    ) {
+        self.groups.insert(
+            name.into(),
+            animations.into_iter().map(Into::into).collect(),
+        );
+    }
+
+    pub fn play_group<T: AsRef<str>>(&mut self, name: T) {
+        if let Some(animations) = self.groups.get(name.as_ref()) {
+            for name in animations {
+                if let Some(animation) = self.animations.get_mut(name) {
+                    animation.reset();
+
+                    self.running.insert(name.clone());
+                }
+            }
+        }
+    }
+
+    pub fn reset_group<T: AsRef<str>>(&mut self, name: T) {
+        if let Some(animations) = self.groups.get(name.as_ref()) {
+            for name in animations {
+                if let Some(animation) = self.animations.get_mut(name) {
+                    animation.reset();
+                }
+            }
+        }
+    }
+
+    pub fn is_group_finished<T: AsRef<str>>(&self, name: T) -> bool {
+        self.groups
+            .get(name.as_ref())
+            .is_some_and(|animations| animations.iter().all(|name| self.is_finished(name)))
+    }
+
     pub fn get_elapsed<T: AsRef<str>>(&self, name: T) -> Option<f32> {
         self.animations
             .get(name.as_ref())
@@ -110,3 +311,285 @@ impl AnimationPlayer {
                 .is_some_and(Animation::is_finished)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+
+    use glam::Vec2;
+
+    use super::AnimationPlayer;
+    use crate::{Animation, Curve, RepeatMode};
+
+    #[test]
+    fn test_remove_running_animation() {
+        let mut player = AnimationPlayer::default();
+
+        player.enable();
+        player.add(
+            "fade",
+            Animation::new(0.0, 1.0, 1000, Curve::LINEAR, RepeatMode::Once),
+        );
+        player.add(
+            "slide",
+            Animation::new(0.0, 1.0, 1000, Curve::LINEAR, RepeatMode::Once),
+        );
+
+        player.play("fade");
+        player.advance(0.25);
+
+        assert!(player.contains("fade"));
+
+        let removed = player.remove("fade").unwrap();
+
+        assert_eq!(player.len(), 1);
+        assert!(!player.contains("fade"));
+        assert!(!player.running.contains("fade"));
+
+        player.advance(0.25);
+
+        assert!((removed.get::<f32>() - 0.25).abs() < f32::EPSILON);
+        assert!(player.remove("fade").is_none());
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let mut player = AnimationPlayer::default();
+
+        player.enable();
+        player.add(
+            "fade",
+            Animation::new(0.0, 1.0, 1000, Curve::LINEAR, RepeatMode::Once),
+        );
+
+        player.play("fade");
+        player.advance(0.25);
+        player.pause("fade");
+
+        assert!(!player.is_running("fade"));
+
+        player.advance(0.5);
+
+        assert!((player.get_value::<_, f32>("fade").unwrap() - 0.25).abs() < 1e-4);
+
+        player.resume("fade");
+        player.resume("missing");
+        player.advance(0.25);
+
+        assert!(player.is_running("fade"));
+        assert!(!player.is_running("missing"));
+        assert!((player.get_value::<_, f32>("fade").unwrap() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut player = AnimationPlayer::default();
+
+        player.enable();
+        player.add(
+            "fade",
+            Animation::new(0.0, 1.0, 1000, Curve::LINEAR, RepeatMode::Once),
+        );
+        player.bind("fade", |_: f32| {});
+        player.play("fade");
+        player.clear();
+
+        assert!(player.is_empty());
+        assert!(player.running.is_empty() && player.bindings.is_empty());
+
+        player.advance(0.25);
+
+        assert_eq!(player.get_value::<_, f32>("fade"), None);
+    }
+
+    #[test]
+    fn test_play_group() {
+        let mut player = AnimationPlayer::default();
+
+        player.enable();
+        player.add(
+            "scale",
+            Animation::new(0.0, 1.0, 400, Curve::LINEAR, RepeatMode::Once),
+        );
+        player.add(
+            "opacity",
+            Animation::new_with_delay(0.0, 1.0, 400, 400, Curve::LINEAR, RepeatMode::Once),
+        );
+        player.add_group("inventory", ["scale", "opacity"]);
+
+        player.play("scale");
+        player.advance(0.3);
+        player.play_group("inventory");
+
+        assert_eq!(player.get_value::<_, f32>("scale"), Some(0.0));
+        assert_eq!(player.running.len(), 2);
+
+        player.advance(0.5);
+
+        assert!(player.is_finished("scale"));
+        assert!(!player.is_group_finished("inventory"));
+
+        player.advance(0.5);
+
+        assert!(player.is_group_finished("inventory"));
+        assert!(!player.is_group_finished("missing"));
+    }
+
+    #[test]
+    fn test_vec2_animation() {
+        let mut player = AnimationPlayer::default();
+
+        player.enable();
+        player.add(
+            "offset",
+            Animation::new(
+                Vec2::ZERO,
+                Vec2::new(200.0, -100.0),
+                400,
+                Curve::LINEAR,
+                RepeatMode::Once,
+            ),
+        );
+
+        player.play("offset");
+        player.advance(0.1);
+
+        let offset = player.get_value::<_, Vec2>("offset").unwrap();
+
+        assert!(offset.abs_diff_eq(Vec2::new(50.0, -25.0), 1e-3));
+    }
+
+    #[test]
+    fn test_bound_animation() {
+        let mut player = AnimationPlayer::default();
+        let values = Rc::new(RefCell::new(Vec::new()));
+
+        player.enable();
+        player.add(
+            "fov",
+            Animation::new(0.0, 1.0, 1000, Curve::LINEAR, RepeatMode::Once),
+        );
+        player.bind("fov", {
+            let values = values.clone();
+
+            move |value: f32| values.borrow_mut().push(value)
+        });
+
+        player.advance(0.5);
+
+        assert!(values.borrow().is_empty());
+
+        player.play("fov");
+        player.advance(0.25);
+        player.advance(0.25);
+
+        assert_eq!(*values.borrow(), [0.25, 0.5]);
+
+        player.unbind("fov");
+        player.advance(0.25);
+
+        assert_eq!(values.borrow().len(), 2);
+    }
+
+    #[test]
+    fn test_finished_callback() {
+        let mut player = AnimationPlayer::default();
+        let finished = Rc::new(Cell::new(0));
+
+        player.enable();
+        player.add(
+            "loading",
+            Animation::new(1.0, 0.0, 500, Curve::LINEAR, RepeatMode::Once),
+        );
+        player.add(
+            "spinner",
+            Animation::new(0.0, 1.0, 500, Curve::LINEAR, RepeatMode::Infinite),
+        );
+
+        for name in ["loading", "spinner"] {
+            player.on_finished(name, {
+                let finished = finished.clone();
+
+                move || finished.set(finished.get() + 1)
+            });
+
+            player.play(name);
+        }
+
+        player.advance(0.25);
+
+        assert_eq!(finished.get(), 0);
+
+        player.advance(0.25);
+
+        assert_eq!(finished.get(), 1);
+        assert!(!player.is_running("loading"));
+
+        for _ in 0..10 {
+            player.advance(0.25);
+        }
+
+        assert_eq!(finished.get(), 1);
+        assert!(player.is_running("spinner"));
+    }
+
+    #[test]
+    fn test_playback_speed() {
+        let mut player = AnimationPlayer::default();
+
+        player.enable();
+        player.add(
+            "fade",
+            Animation::new(0.0, 1.0, 1000, Curve::LINEAR, RepeatMode::Once),
+        );
+
+        player.set_speed(2.0);
+        player.play("fade");
+        player.advance(0.25);
+
+        assert!((player.get_value::<_, f32>("fade").unwrap() - 0.5).abs() < 1e-4);
+
+        player.advance(0.25);
+
+        assert!(player.is_finished("fade"));
+
+        player.set_speed(-1.0);
+
+        assert!(player.speed().abs() < f32::EPSILON);
+
+        player.play("fade");
+        player.advance(0.5);
+
+        assert_eq!(player.get_value::<_, f32>("fade"), Some(0.0));
+    }
+
+    #[test]
+    fn test_clone_keeps_bindings() {
+        let mut player = AnimationPlayer::default();
+        let value = Rc::new(Cell::new(0.0));
+
+        player.enable();
+        player.add(
+            "fade",
+            Animation::new(0.0, 1.0, 1000, Curve::LINEAR, RepeatMode::Once),
+        );
+        player.bind("fade", {
+            let value = value.clone();
+
+            move |fade: f32| value.set(fade)
+        });
+
+        let mut clone = player.clone();
+
+        assert_eq!(clone, player);
+
+        clone.play("fade");
+        clone.advance(0.5);
+
+        assert_ne!(clone, player);
+        assert!((value.get() - 0.5).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,118 @@
+use glam::Vec4;
+
+use crate::TweenValue;
+
+/// Animation moving towards its target like a damped spring, instead of
+/// following a curve over a fixed duration.
+///
+/// Velocity is kept when the target changes, so an interrupted animation
+/// carries its momentum over to the new target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpringAnimation {
+    stiffness: f32,
+    damping: f32,
+    mass: f32,
+
+    position: Vec4,
+    velocity: Vec4,
+    destination: TweenValue,
+}
+
+impl SpringAnimation {
+    /// Distance to the target and speed below which the spring is at rest.
+    pub const EPSILON: f32 = 1e-3;
+    /// Longest step the spring is advanced by at once, since larger ones make
+    /// the simulation unstable.
+    const MAX_STEP: f32 = 1.0 / 120.0;
+
+    #[must_use]
+    pub fn new<T: Into<TweenValue>>(
+        start: T,
+        end: T,
+        stiffness: f32,
+        damping: f32,
+        mass: f32,
+    ) -> Self {
+        Self {
+            stiffness,
+            damping,
+            mass,
+            position: start.into().to_components(),
+            velocity: Vec4::ZERO,
+            destination: end.into(),
+        }
+    }
+
+    /// Changes the target, keeping the current velocity.
+    pub fn to<T: Into<TweenValue>>(&mut self, value: T) {
+        self.destination = value.into();
+    }
+
+    pub fn get<T: From<TweenValue>>(&self) -> T {
+        self.destination.with_components(self.position).into()
+    }
+
+    pub fn advance(&mut self, mut delta: f32) {
+        let target = self.destination.to_components();
+
+        while delta > 0.0 && !self.is_finished() {
+            let step = delta.min(Self::MAX_STEP);
+            let force = (target - self.position) * self.stiffness - self.velocity * self.damping;
+
+            // Semi-implicit Euler: position is moved with the updated velocity.
+            self.velocity += force / self.mass * step;
+            self.position += self.velocity * step;
+
+            delta -= step;
+        }
+
+        if self.is_finished() {
+            self.position = target;
+            self.velocity = Vec4::ZERO;
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.velocity.length() < Self::EPSILON
+            && self.position.distance(self.destination.to_components()) < Self::EPSILON
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+
+    use super::SpringAnimation;
+
+    #[test]
+    fn test_spring_settles() {
+        let mut spring = SpringAnimation::new(Vec2::ZERO, Vec2::new(4.0, 2.0), 170.0, 26.0, 1.0);
+
+        assert!(!spring.is_finished());
+
+        for _ in 0..300 {
+            spring.advance(1.0 / 60.0);
+        }
+
+        assert!(spring.is_finished());
+        assert_eq!(spring.get::<Vec2>(), Vec2::new(4.0, 2.0));
+    }
+
+    #[test]
+    fn test_retarget_keeps_velocity() {
+        let mut spring = SpringAnimation::new(0.0, 10.0, 170.0, 26.0, 1.0);
+
+        spring.advance(0.1);
+
+        let value = spring.get::<f32>();
+        let mut resting = SpringAnimation::new(value, 0.0, 170.0, 26.0, 1.0);
+
+        spring.to(0.0);
+        spring.advance(0.01);
+        resting.advance(0.01);
+
+        // moving spring keeps going up for a while, unlike the one at rest
+        assert!(spring.get::<f32>() > value);
+        assert!(resting.get::<f32>() < value);
+    }
+}
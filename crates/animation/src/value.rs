@@ -1,9 +1,65 @@
-use meralus_shared::{Color, Lerp};
+use std::cmp::Ordering;
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+use glam::{Vec2, Vec3, Vec4};
+use meralus_shared::{AsValue, Color, Lerp};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TweenValue {
     Color(Color),
     Float(f32),
+    Vec2(Vec2),
+    Vec3(Vec3),
+}
+
+impl TweenValue {
+    const fn variant_index(&self) -> u8 {
+        match self {
+            Self::Color(_) => 0,
+            Self::Float(_) => 1,
+            Self::Vec2(_) => 2,
+            Self::Vec3(_) => 3,
+        }
+    }
+
+    /// Returns components of the value, with unused ones set to zero.
+    pub fn to_components(self) -> Vec4 {
+        match self {
+            Self::Color(color) => Vec4::from_array(color.as_value()),
+            Self::Float(value) => Vec4::new(value, 0.0, 0.0, 0.0),
+            Self::Vec2(value) => value.extend(0.0).extend(0.0),
+            Self::Vec3(value) => value.extend(0.0),
+        }
+    }
+
+    /// Creates value of the same kind as this one from the given components.
+    #[must_use]
+    pub fn with_components(self, components: Vec4) -> Self {
+        match self {
+            Self::Color(_) => Self::Color(Color::new_f32(
+                components.x,
+                components.y,
+                components.z,
+                components.w,
+            )),
+            Self::Float(_) => Self::Float(components.x),
+            Self::Vec2(_) => Self::Vec2(components.truncate().truncate()),
+            Self::Vec3(_) => Self::Vec3(components.truncate()),
+        }
+    }
+}
+
+// Vectors don't implement `PartialOrd`, so their components are compared
+// lexicographically instead.
+impl PartialOrd for TweenValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Color(a), Self::Color(b)) => a.partial_cmp(b),
+            (Self::Float(a), Self::Float(b)) => a.partial_cmp(b),
+            (Self::Vec2(a), Self::Vec2(b)) => a.to_array().partial_cmp(&b.to_array()),
+            (Self::Vec3(a), Self::Vec3(b)) => a.to_array().partial_cmp(&b.to_array()),
+            _ => self.variant_index().partial_cmp(&other.variant_index()),
+        }
+    }
 }
 
 impl Lerp for TweenValue {
@@ -11,6 +67,8 @@ impl Lerp for TweenValue {
         match (self, end) {
             (Self::Color(a), Self::Color(b)) => Self::Color(a.lerp(b, x)),
             (Self::Float(a), Self::Float(b)) => Self::Float(a.lerp(b, x)),
+            (Self::Vec2(a), Self::Vec2(b)) => Self::Vec2(a.lerp(b, x)),
+            (Self::Vec3(a), Self::Vec3(b)) => Self::Vec3(a.lerp(b, x)),
             _ => unimplemented!(),
         }
     }
@@ -28,11 +86,23 @@ impl From<f32> for TweenValue {
     }
 }
 
+impl From<Vec2> for TweenValue {
+    fn from(value: Vec2) -> Self {
+        Self::Vec2(value)
+    }
+}
+
+impl From<Vec3> for TweenValue {
+    fn from(value: Vec3) -> Self {
+        Self::Vec3(value)
+    }
+}
+
 impl From<TweenValue> for Color {
     fn from(value: TweenValue) -> Self {
         match value {
             TweenValue::Color(value) => value,
-            TweenValue::Float(_) => unreachable!(),
+            TweenValue::Float(_) | TweenValue::Vec2(_) | TweenValue::Vec3(_) => unreachable!(),
         }
     }
 }
@@ -41,7 +111,25 @@ impl From<TweenValue> for f32 {
     fn from(value: TweenValue) -> Self {
         match value {
             TweenValue::Float(value) => value,
-            TweenValue::Color(_) => unreachable!(),
+            TweenValue::Color(_) | TweenValue::Vec2(_) | TweenValue::Vec3(_) => unreachable!(),
+        }
+    }
+}
+
+impl From<TweenValue> for Vec2 {
+    fn from(value: TweenValue) -> Self {
+        match value {
+            TweenValue::Vec2(value) => value,
+            TweenValue::Color(_) | TweenValue::Float(_) | TweenValue::Vec3(_) => unreachable!(),
+        }
+    }
+}
+
+impl From<TweenValue> for Vec3 {
+    fn from(value: TweenValue) -> Self {
+        match value {
+            TweenValue::Vec3(value) => value,
+            TweenValue::Color(_) | TweenValue::Float(_) | TweenValue::Vec2(_) => unreachable!(),
         }
     }
 }
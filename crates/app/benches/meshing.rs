@@ -0,0 +1,101 @@
+use std::hint::black_box;
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use glam::{IVec2, Vec2, Vec3};
+use meralus_app::{
+    BakedBlockModelLoader,
+    loaders::{BakedBlockModel, BlockModelElement, BlockModelFace, FaceUV},
+    simulation::{Simulation, WorldGenerator},
+};
+use meralus_shared::{Color, Cube3D};
+use meralus_world::{BlockManifest, Face};
+
+const SEED: u32 = 12723;
+
+/// Returns full cube models of dirt, grass and water with every face pointing
+/// at the same tile, which is all meshing needs without loading resources.
+fn models() -> BakedBlockModelLoader {
+    let mut models = BakedBlockModelLoader::with_manifest(
+        BlockManifest::from_slice(br#"{ "1": "dirt", "2": "grass_block", "3": "water" }"#).unwrap(),
+    );
+
+    for (id, name, transparent) in [
+        (1, "dirt", false),
+        (2, "grass_block", false),
+        (3, "water", true),
+    ] {
+        let mut faces = [const { None }; 6];
+
+        for face in Face::ALL {
+            faces[face.normal_index()] = Some(BlockModelFace {
+                texture_id: 0,
+                face,
+                cull_face: Some(face),
+                tint: name == "grass_block" && face == Face::Top,
+                uv: FaceUV::new(Vec2::ZERO, Vec2::splat(0.25), None),
+                is_opaque: !transparent,
+                color: Color::WHITE,
+            });
+        }
+
+        models.insert(id, BakedBlockModel {
+            name: String::from(name),
+            bounding_box: Cube3D::ONE,
+            ambient_occlusion: true,
+            transparent,
+            cull_same_block: transparent,
+            connected: None,
+            elements: vec![BlockModelElement {
+                cube: Cube3D::ONE,
+                rotation: None,
+                faces,
+            }],
+        });
+    }
+
+    models
+}
+
+/// Returns a 3x3 chunk world around the origin, with light calculated if
+/// `lit` is set.
+fn world(lit: bool) -> Simulation {
+    let mut simulation = Simulation::new(models(), -1..2, -1..2);
+
+    simulation.generate_world(WorldGenerator::Surface { seed: SEED });
+
+    if lit {
+        simulation.generate_lights();
+    }
+
+    simulation
+}
+
+fn meshing(c: &mut Criterion) {
+    let simulation = world(true);
+    let chunk = simulation.chunk_manager().get_chunk(&IVec2::ZERO).unwrap();
+
+    c.bench_function("compute_chunk_mesh", |b| {
+        b.iter(|| simulation.compute_chunk_mesh(black_box(chunk)));
+    });
+}
+
+fn lighting(c: &mut Criterion) {
+    c.bench_function("generate_lights", |b| {
+        b.iter_batched_ref(
+            || world(false),
+            Simulation::generate_lights,
+            BatchSize::LargeInput,
+        );
+    });
+
+    c.bench_function("set_block_light", |b| {
+        b.iter_batched_ref(
+            || world(true),
+            |simulation| simulation.set_block_light(black_box(Vec3::new(8.0, 200.0, 8.0)), 15),
+            BatchSize::LargeInput,
+        );
+    });
+}
+
+criterion_group!(benches, meshing, lighting);
+criterion_main!(benches);
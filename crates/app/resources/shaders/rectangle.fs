@@ -1,9 +1,7 @@
 #version 140
 
-out vec4 f_color;
+in vec4 v_color;
 
-uniform vec4 color;
+out vec4 f_color;
 
-void main() {
-    f_color = color;
-}
+void main() { f_color = v_color; }
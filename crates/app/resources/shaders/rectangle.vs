@@ -1,13 +1,25 @@
 #version 140
 
+in vec2 position;
 in vec2 screen_position;
-in vec3 position;
-in vec2 size;
+in vec2 screen_size;
+in vec4 color;
+in mat4 transform;
+
+out vec4 v_color;
 
 uniform mat4 matrix;
 
+vec4 toLinear(vec4 sRGB) {
+  bvec3 cutoff = lessThan(sRGB.rgb, vec3(0.04045));
+  vec3 higher = pow((sRGB.rgb + vec3(0.055)) / vec3(1.055), vec3(2.4));
+  vec3 lower = sRGB.rgb / vec3(12.92);
+
+  return vec4(mix(higher, lower, cutoff), sRGB.a);
+}
+
 void main() {
-  gl_Position =
-      matrix *
-      vec4(vec3(screen_position + position.xy * size, position.z), 1.0);
+  gl_Position = matrix * transform *
+                vec4(screen_position + position * screen_size, 0.0, 1.0);
+  v_color = toLinear(color / 255.0);
 }
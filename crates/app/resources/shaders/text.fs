@@ -15,4 +15,12 @@ vec4 toLinear(vec4 sRGB) {
   return vec4(mix(higher, lower, cutoff), sRGB.a);
 }
 
-void main() { f_color = texture2D(font, v_character) * toLinear(text_color); }
+void main() {
+  float distance = texture2D(font, v_character).a;
+  float width = fwidth(distance);
+  float alpha = smoothstep(0.5 - width, 0.5 + width, distance);
+
+  vec4 color = toLinear(text_color);
+
+  f_color = vec4(color.rgb, color.a * alpha);
+}
@@ -1,6 +1,8 @@
 #version 140
 
 in vec2 v_tex_coords;
+flat in vec2 v_tile_offset;
+flat in vec2 v_tile_scale;
 in vec4 v_color;
 
 out vec4 f_color;
@@ -10,7 +12,13 @@ uniform bool with_tex;
 
 void main() {
   if (with_tex) {
-    f_color = texture2D(tex, v_tex_coords) * v_color;
+    // Merged faces repeat the texture once per block, so coordinates are
+    // wrapped into the tile, while derivatives are taken before wrapping to
+    // avoid picking the smallest mipmap along tile edges.
+    vec2 tex_coords = v_tile_offset + fract(v_tex_coords) * v_tile_scale;
+    vec2 scaled = v_tex_coords * v_tile_scale;
+
+    f_color = textureGrad(tex, tex_coords, dFdx(scaled), dFdy(scaled)) * v_color;
   } else
     f_color = v_color;
 }
@@ -1,16 +1,21 @@
 #version 140
 
 in vec3 position;
-in uint light;
+in uint sun_light;
+in uint block_light;
 in vec2 uv;
+in vec2 tile_offset;
+in vec2 tile_scale;
 in vec4 color;
 in int visible;
 
 out vec2 v_tex_coords;
+flat out vec2 v_tile_offset;
+flat out vec2 v_tile_scale;
 out vec4 v_color;
 
 uniform mat4 matrix;
-uniform vec3 sun_position;
+uniform float day_factor;
 
 vec4 toLinear(vec4 sRGB) {
     bvec3 cutoff = lessThan(sRGB.rgb, vec3(0.04045));
@@ -22,11 +27,10 @@ vec4 toLinear(vec4 sRGB) {
 
 void main() {
     if (visible == 1) {
-        float block_light = (float(light & uint(15)) + 1.0) / 16.0;
-        float sun_light = (float((light >> uint(4)) & uint(15)) + 1.0) / 16.0;
+        float block_intensity = (float(block_light) + 1.0) / 16.0;
+        float sun_intensity = (float(sun_light) + 1.0) / 16.0;
 
-        float light_intensity =
-            block_light + sun_light * max(sun_position.y * 0.96 + 0.3, 0.02);
+        float light_intensity = max(block_intensity, sun_intensity * day_factor);
 
         vec4 linear_color = toLinear(color / 255.0);
 
@@ -34,5 +38,7 @@ void main() {
 
         v_color = vec4(linear_color.rgb * light_intensity, linear_color.a);
         v_tex_coords = uv;
+        v_tile_offset = tile_offset;
+        v_tile_scale = tile_scale;
     }
 }
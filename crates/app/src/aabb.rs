@@ -36,6 +36,12 @@ impl Aabb {
         self.min.z < against.max.z && self.max.z > against.min.z
     }
 
+    pub const fn intersects(&self, against: Self) -> bool {
+        self.intersects_with_x(against)
+            && self.intersects_with_y(against)
+            && self.intersects_with_z(against)
+    }
+
     pub const fn intersects_with_yz(&self, vec: DVec3) -> bool {
         vec.y >= self.min.y && vec.y <= self.max.y && vec.z >= self.min.z && vec.z <= self.max.z
     }
@@ -0,0 +1,290 @@
+use std::{collections::HashSet, mem};
+
+use glam::{IVec2, IVec3, Vec3};
+use meralus_world::{AIR_BLOCK, Face};
+
+use crate::{BlockManager, simulation::Simulation};
+
+/// Notification about a change of the block next to the updated one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NeighbourUpdate {
+    pub position: IVec3,
+    pub neighbour: IVec3,
+}
+
+/// Moves the falling block one block down if there is air below it, returning
+/// changed positions.
+fn fall(
+    simulation: &mut Simulation,
+    position: Vec3,
+    block: u8,
+    chunks: &mut HashSet<IVec2>,
+) -> Vec<Vec3> {
+    let below = position - Vec3::Y;
+
+    if below.y < 0.0 || simulation.chunk_manager().get_block(below).is_some() {
+        return Vec::new();
+    }
+
+    chunks.extend(simulation.edit_block(position, AIR_BLOCK));
+    chunks.extend(simulation.edit_block(below, block));
+
+    vec![position, below]
+}
+
+/// Bounded queue of neighbour updates. Updates are processed on the tick after
+/// they were queued, so changes made by blocks reacting to them spread one
+/// step per tick.
+#[derive(Debug, Clone)]
+pub struct BlockUpdates {
+    queue: Vec<NeighbourUpdate>,
+    /// Positions in the queue, so that a block isn't updated twice per tick.
+    queued: HashSet<IVec3>,
+    capacity: usize,
+}
+
+impl BlockUpdates {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            queue: Vec::new(),
+            queued: HashSet::new(),
+            capacity,
+        }
+    }
+
+    /// Queues update of the block at the position, unless it's already queued
+    /// or the queue is full.
+    fn push(&mut self, position: IVec3, neighbour: IVec3) {
+        if self.queue.len() < self.capacity && self.queued.insert(position) {
+            self.queue.push(NeighbourUpdate {
+                position,
+                neighbour,
+            });
+        }
+    }
+
+    /// Queues updates of the six blocks next to the changed position. Blocks
+    /// that are already queued are skipped, as well as any updates once the
+    /// queue is full.
+    pub fn notify_neighbours(&mut self, position: Vec3) {
+        let changed = position.floor().as_ivec3();
+
+        for face in Face::ALL {
+            self.push(changed + face.as_normal(), changed);
+        }
+    }
+
+    /// Queues update of the block itself, so that a placed falling block
+    /// starts falling without waiting for its neighbours to change.
+    pub fn schedule(&mut self, position: Vec3) {
+        let position = position.floor().as_ivec3();
+
+        self.push(position, position);
+    }
+
+    /// Calls [`Block::on_neighbour_changed`] for every block queued before
+    /// this call, or moves it down if it [falls], notifying neighbours of
+    /// blocks changed by it in turn.
+    /// Returns origins of chunks that have to be remeshed.
+    ///
+    /// [`Block::on_neighbour_changed`]: crate::Block::on_neighbour_changed
+    /// [falls]: crate::Block::falls
+    pub fn process(
+        &mut self,
+        blocks: &BlockManager,
+        simulation: &mut Simulation,
+    ) -> HashSet<IVec2> {
+        let updates = mem::take(&mut self.queue);
+        let mut chunks = HashSet::new();
+        // Blocks changed during this call are updated on the next tick, even
+        // if they were queued before, so that falling blocks move one block
+        // per tick.
+        let mut changed_now = HashSet::new();
+
+        self.queued.clear();
+
+        for update in updates {
+            if changed_now.contains(&update.position) {
+                continue;
+            }
+
+            let position = update.position.as_vec3();
+            let Some((id, block)) = simulation
+                .chunk_manager()
+                .get_block(position)
+                .and_then(|id| blocks.get(usize::from(id)).map(|block| (id, block)))
+            else {
+                continue;
+            };
+
+            let changed = if block.falls() {
+                fall(simulation, position, id, &mut chunks)
+            } else {
+                block.on_neighbour_changed(simulation, position, update.neighbour.as_vec3())
+            };
+
+            for position in changed {
+                changed_now.insert(position.floor().as_ivec3());
+                chunks.extend(simulation.chunk_manager().chunks_affected_by(position));
+
+                self.notify_neighbours(position);
+            }
+        }
+
+        chunks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use glam::{IVec2, IVec3, Vec3};
+    use meralus_world::AIR_BLOCK;
+
+    use super::BlockUpdates;
+    use crate::{
+        BakedBlockModelLoader, Block, BlockManager,
+        simulation::{Simulation, tests::solid_models},
+    };
+
+    struct SolidBlock;
+
+    impl Block for SolidBlock {
+        fn id(&self) -> &'static str {
+            "solid"
+        }
+    }
+
+    struct SandBlock;
+
+    impl Block for SandBlock {
+        fn id(&self) -> &'static str {
+            "sand"
+        }
+
+        fn falls(&self) -> bool {
+            true
+        }
+    }
+
+    /// Block that crumbles into air once anything next to it changes.
+    struct FragileBlock;
+
+    impl Block for FragileBlock {
+        fn id(&self) -> &'static str {
+            "fragile"
+        }
+
+        fn on_neighbour_changed(
+            &self,
+            simulation: &mut Simulation,
+            position: Vec3,
+            _: Vec3,
+        ) -> Vec<Vec3> {
+            simulation.edit_block(position, AIR_BLOCK);
+
+            vec![position]
+        }
+    }
+
+    #[test]
+    fn test_neighbours_are_notified() {
+        let mut updates = BlockUpdates::new(64);
+        let changed = Vec3::new(4.0, 100.0, 4.0);
+
+        updates.notify_neighbours(changed);
+        // queued blocks aren't queued again
+        updates.notify_neighbours(changed);
+
+        assert_eq!(
+            updates
+                .queue
+                .iter()
+                .map(|update| update.position)
+                .collect::<HashSet<_>>(),
+            HashSet::from([
+                IVec3::new(3, 100, 4),
+                IVec3::new(5, 100, 4),
+                IVec3::new(4, 99, 4),
+                IVec3::new(4, 101, 4),
+                IVec3::new(4, 100, 3),
+                IVec3::new(4, 100, 5),
+            ])
+        );
+        assert_eq!(updates.queue.len(), 6);
+
+        let mut bounded = BlockUpdates::new(8);
+
+        bounded.notify_neighbours(Vec3::ZERO);
+        bounded.notify_neighbours(Vec3::splat(10.0));
+
+        assert_eq!(bounded.queue.len(), 8);
+    }
+
+    #[test]
+    fn test_neighbour_hook() {
+        let mut simulation = Simulation::new(BakedBlockModelLoader::default(), 0..1, 0..1);
+        let mut blocks = BlockManager::new();
+        let mut updates = BlockUpdates::new(64);
+        let fragile = blocks.register(FragileBlock) as u8;
+        let above = Vec3::new(4.0, 101.0, 4.0);
+        let beside = Vec3::new(6.0, 100.0, 4.0);
+
+        simulation.edit_block(above, fragile);
+        simulation.edit_block(beside, fragile);
+        updates.notify_neighbours(Vec3::new(4.0, 100.0, 4.0));
+
+        let chunks = updates.process(&blocks, &mut simulation);
+
+        assert_eq!(chunks, HashSet::from([IVec2::ZERO]));
+        assert_eq!(simulation.chunk_manager().get_block(above), None);
+        assert_eq!(simulation.chunk_manager().get_block(beside), Some(fragile));
+
+        // the crumbled block notifies its own neighbours on the next tick
+        assert!(
+            updates
+                .queue
+                .iter()
+                .all(|update| update.neighbour == above.as_ivec3())
+        );
+        assert_eq!(updates.queue.len(), 6);
+    }
+
+    #[test]
+    fn test_falling_block() {
+        // lighting needs models of the moved blocks
+        let mut simulation = Simulation::new(solid_models(), 0..1, 0..1);
+        let mut blocks = BlockManager::new();
+        let mut updates = BlockUpdates::new(64);
+        let solid = blocks.register(SolidBlock) as u8;
+        let sand = blocks.register(SandBlock) as u8;
+        let ground = Vec3::new(4.0, 100.0, 4.0);
+        let placed = ground + Vec3::Y * 6.0;
+
+        simulation.edit_block(ground, solid);
+        simulation.edit_block(placed, sand);
+        updates.schedule(placed);
+
+        // falls one block per tick, so it lands after five ticks
+        for _ in 0..5 {
+            assert!(!updates.process(&blocks, &mut simulation).is_empty());
+        }
+
+        assert!(updates.process(&blocks, &mut simulation).is_empty());
+        assert_eq!(
+            simulation.chunk_manager().get_block(ground + Vec3::Y),
+            Some(sand)
+        );
+        assert_eq!(simulation.chunk_manager().get_block(ground), Some(solid));
+
+        for y in 2..=6 {
+            assert_eq!(
+                simulation
+                    .chunk_manager()
+                    .get_block(ground + Vec3::Y * y as f32),
+                None
+            );
+        }
+    }
+}
@@ -1,12 +1,19 @@
 use meralus_world::Property;
 
-use crate::Block;
+use crate::{
+    Block,
+    drops::{Drops, ItemDrop},
+};
 
-pub struct AirBlock;
+pub struct DirtBlock;
 
-impl Block for AirBlock {
+impl Block for DirtBlock {
     fn id(&self) -> &'static str {
-        "air"
+        "dirt"
+    }
+
+    fn hardness(&self) -> f32 {
+        0.5
     }
 
     fn get_properties(&self) -> Vec<Property> {
@@ -14,11 +21,23 @@ impl Block for AirBlock {
     }
 }
 
-pub struct DirtBlock;
+pub struct GrassBlock;
 
-impl Block for DirtBlock {
+impl Block for GrassBlock {
     fn id(&self) -> &'static str {
-        "dirt"
+        "grass_block"
+    }
+
+    fn hardness(&self) -> f32 {
+        0.6
+    }
+
+    fn drops(&self) -> Drops {
+        Drops::Items(vec![ItemDrop {
+            item: "dirt",
+            count: 1,
+            chance: 1.0,
+        }])
     }
 
     fn get_properties(&self) -> Vec<Property> {
@@ -26,11 +45,52 @@ impl Block for DirtBlock {
     }
 }
 
-pub struct GrassBlock;
+pub struct WaterBlock;
 
-impl Block for GrassBlock {
+impl Block for WaterBlock {
     fn id(&self) -> &'static str {
-        "grass_block"
+        "water"
+    }
+
+    /// Water can't be picked up as an item.
+    fn drops(&self) -> Drops {
+        Drops::Items(Vec::new())
+    }
+
+    fn get_properties(&self) -> Vec<Property> {
+        Vec::new()
+    }
+}
+
+pub struct SandBlock;
+
+impl Block for SandBlock {
+    fn id(&self) -> &'static str {
+        "sand"
+    }
+
+    fn hardness(&self) -> f32 {
+        0.5
+    }
+
+    fn falls(&self) -> bool {
+        true
+    }
+
+    fn get_properties(&self) -> Vec<Property> {
+        Vec::new()
+    }
+}
+
+pub struct GlassBlock;
+
+impl Block for GlassBlock {
+    fn id(&self) -> &'static str {
+        "glass"
+    }
+
+    fn hardness(&self) -> f32 {
+        0.3
     }
 
     fn get_properties(&self) -> Vec<Property> {
@@ -35,4 +35,9 @@ impl Camera {
     pub fn matrix(&self) -> Mat4 {
         self.projection() * self.view()
     }
+
+    /// Returns size of one screen pixel at a unit distance from the camera.
+    pub fn pixel_size(&self, viewport_height: f32) -> f32 {
+        2.0 * (self.fov / 2.0).tan() / viewport_height
+    }
 }
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{f32::consts::TAU, time::Duration};
 
 const REAL_DAY_DURATION: Duration = Duration::from_secs(24 * 60 * 60);
 
@@ -15,6 +15,9 @@ pub struct Clock {
 }
 
 impl Clock {
+    /// Sky light multiplier at midnight.
+    pub const MIN_DAY_FACTOR: f32 = 0.05;
+
     pub const fn default() -> Self {
         Self::new(
             REAL_DAY_DURATION
@@ -35,16 +38,12 @@ impl Clock {
         self.time.div_duration_f32(REAL_DAY_DURATION)
     }
 
-    pub const fn get_visual_progress(&self) -> (bool, f32) {
-        let progress = self.get_progress();
-
-        let visual_progres = if progress > 0.5 {
-            progress - 0.5
-        } else {
-            progress
-        };
+    /// Returns how much of the sky light reaches the world, going from
+    /// [`Clock::MIN_DAY_FACTOR`] at midnight to `1.0` at noon.
+    pub fn get_day_factor(&self) -> f32 {
+        let daylight = (1.0 - (self.get_progress() * TAU).cos()) / 2.0;
 
-        (progress > 0.5, visual_progres * 2.0)
+        (1.0 - Self::MIN_DAY_FACTOR).mul_add(daylight, Self::MIN_DAY_FACTOR)
     }
 
     pub const fn tick(&mut self) {
@@ -0,0 +1,90 @@
+use meralus_shared::Random;
+use meralus_world::{BlockManifest, Inventory};
+
+use crate::Block;
+
+/// Item dropped by a broken block.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ItemDrop {
+    /// Id of the block dropped as an item (see [`Block::id`]).
+    pub item: &'static str,
+    pub count: u8,
+    /// Probability of the item being dropped, in the range `0.0..=1.0`.
+    pub chance: f32,
+}
+
+/// Items a block drops when broken.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub enum Drops {
+    /// Block drops a single item of itself.
+    #[default]
+    Itself,
+    /// Block drops each of the items with its chance, so nothing is dropped
+    /// if there are none (e.g. for leaves broken without shears).
+    Items(Vec<ItemDrop>),
+}
+
+impl Drops {
+    /// Returns ids of blocks dropped as items, along with their counts.
+    pub fn roll(&self, block: &dyn Block, random: &mut Random) -> Vec<(&'static str, u8)> {
+        match self {
+            Self::Itself => vec![(block.id(), 1)],
+            Self::Items(items) => items
+                .iter()
+                .filter(|drop| random.chance(drop.chance))
+                .map(|drop| (drop.item, drop.count))
+                .collect(),
+        }
+    }
+}
+
+/// Rolls drops of the broken block, adding them to the inventory. Items
+/// missing from the manifest are skipped, as they have no id.
+pub fn collect_drops(
+    block: &dyn Block,
+    manifest: &BlockManifest,
+    inventory: &mut Inventory,
+    random: &mut Random,
+) {
+    for (item, count) in block.drops().roll(block, random) {
+        if let Some(id) = manifest.id(item) {
+            inventory.add(id, count);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use meralus_shared::Random;
+    use meralus_world::{BlockManifest, Inventory, ItemStack};
+
+    use super::{Drops, collect_drops};
+    use crate::{Block, blocks::DirtBlock};
+
+    struct LeavesBlock;
+
+    impl Block for LeavesBlock {
+        fn id(&self) -> &'static str {
+            "leaves"
+        }
+
+        fn drops(&self) -> Drops {
+            Drops::Items(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_block_drops_itself() {
+        let manifest = BlockManifest::from_slice(br#"{ "1": "dirt", "2": "leaves" }"#).unwrap();
+        let mut inventory = Inventory::new(4);
+        let mut random = Random::new(0);
+
+        collect_drops(&DirtBlock, &manifest, &mut inventory, &mut random);
+
+        assert_eq!(inventory.slots()[0], Some(ItemStack { item: 1, count: 1 }));
+
+        collect_drops(&LeavesBlock, &manifest, &mut inventory, &mut random);
+
+        assert_eq!(inventory.slots().iter().flatten().count(), 1);
+    }
+}
@@ -0,0 +1,151 @@
+use std::f32::consts::TAU;
+
+use glam::{Vec2, Vec3};
+use meralus_shared::Random;
+
+use crate::{Aabb, PlayerController, simulation::Simulation};
+
+/// Passive creature wandering around the world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    /// Position of the bottom center of the bounding box.
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Size of the bounding box.
+    pub size: Vec3,
+    pub is_on_ground: bool,
+    /// Seconds left until a new direction to wander in is picked.
+    wander_timer: f32,
+}
+
+impl Entity {
+    pub const GRAVITY: f32 = PlayerController::GRAVITY;
+    /// Chance of standing still instead of walking somewhere.
+    const IDLE_CHANCE: f32 = 0.25;
+    pub const WALK_SPEED: f32 = 1.5;
+    /// Average time an entity keeps walking in the same direction, in
+    /// seconds.
+    const WANDER_INTERVAL: f32 = 3.0;
+
+    pub const fn new(position: Vec3, size: Vec3) -> Self {
+        Self {
+            position,
+            velocity: Vec3::ZERO,
+            size,
+            is_on_ground: false,
+            wander_timer: 0.0,
+        }
+    }
+
+    pub fn aabb(&self) -> Aabb {
+        self.aabb_at(self.position)
+    }
+
+    fn aabb_at(&self, position: Vec3) -> Aabb {
+        let half = Vec3::new(self.size.x / 2.0, 0.0, self.size.z / 2.0);
+
+        Aabb::new(
+            (position - half).as_dvec3(),
+            (position + half + Vec3::Y * self.size.y).as_dvec3(),
+        )
+    }
+
+    /// Picks a random direction to walk in every few seconds, which is only
+    /// possible while standing on the ground.
+    pub fn wander(&mut self, random: &mut Random, delta: f32) {
+        self.wander_timer -= delta;
+
+        if self.wander_timer <= 0.0 && self.is_on_ground {
+            self.wander_timer = Self::WANDER_INTERVAL * (0.5 + random.next_f32());
+
+            let direction = if random.chance(Self::IDLE_CHANCE) {
+                Vec2::ZERO
+            } else {
+                Vec2::from_angle(random.next_f32() * TAU)
+            };
+
+            self.velocity.x = direction.x * Self::WALK_SPEED;
+            self.velocity.z = direction.y * Self::WALK_SPEED;
+        }
+    }
+
+    /// Applies gravity and moves the entity axis by axis, stopping it right
+    /// before blocks it would collide with.
+    pub fn update(&mut self, simulation: &Simulation, delta: f32) {
+        self.velocity.y -= Self::GRAVITY * delta;
+        self.is_on_ground = false;
+
+        let movement = self.velocity * delta;
+
+        for axis in 0..3 {
+            let mut position = self.position;
+
+            position[axis] += movement[axis];
+
+            if !simulation.collides(self.aabb_at(position)) {
+                self.position = position;
+
+                continue;
+            }
+
+            // Find how far the entity can move by halving the step
+            let direction = movement[axis].signum();
+            let mut step = movement[axis].abs();
+            let mut moved = 0.0;
+
+            #[allow(clippy::while_float)]
+            while step > 0.001 {
+                step /= 2.0;
+                position[axis] = direction.mul_add(moved + step, self.position[axis]);
+
+                if !simulation.collides(self.aabb_at(position)) {
+                    moved += step;
+                }
+            }
+
+            self.position[axis] = direction.mul_add(moved, self.position[axis]);
+            self.velocity[axis] = 0.0;
+
+            if axis == 1 && direction < 0.0 {
+                self.is_on_ground = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::Entity;
+    use crate::{BakedBlockModelLoader, simulation::Simulation};
+
+    #[test]
+    fn test_entity_rests_on_block() {
+        let mut simulation = Simulation::new(BakedBlockModelLoader::default(), 0..1, 0..1);
+
+        simulation
+            .chunk_manager_mut()
+            .set_block(Vec3::new(4.0, 100.0, 4.0), 1);
+
+        let mut entity = Entity::new(Vec3::new(4.5, 110.0, 4.5), Vec3::new(0.8, 0.8, 0.8));
+
+        entity.update(&simulation, 0.1);
+
+        assert!(entity.position.y < 110.0 && entity.velocity.y < 0.0);
+        assert!(!entity.is_on_ground);
+
+        for _ in 0..200 {
+            entity.update(&simulation, 1.0 / 60.0);
+        }
+
+        assert!(entity.is_on_ground);
+        assert!(
+            (entity.position.y - 101.0).abs() < 0.01,
+            "{}",
+            entity.position
+        );
+        assert!(entity.velocity.y.abs() < 1.0);
+        assert!((entity.position.x - 4.5).abs() < f32::EPSILON);
+    }
+}
@@ -1,39 +1,54 @@
 use std::{
-    collections::HashMap,
-    fs::DirEntry,
-    ops::Range,
-    path::{Path, PathBuf},
+    collections::HashSet,
+    ops::{Deref, DerefMut, Range},
+    path::Path,
 };
 
-use glam::{DVec3, IVec2, Mat4, U16Vec3, Vec2, Vec3, ivec3, u16vec3, vec3};
+use glam::{IVec2, Vec2, Vec3, Vec4};
 use glium::{
     Texture2d,
     uniforms::{MagnifySamplerFilter, MinifySamplerFilter, Sampler},
 };
 use meralus_engine::WindowDisplay;
-use meralus_shared::Color;
-use meralus_world::{
-    Axis, CHUNK_SIZE, CHUNK_SIZE_F32, CHUNK_SIZE_U16, Chunk, ChunkManager, Face, SUBCHUNK_COUNT_U16,
-};
-use owo_colors::OwoColorize;
+use meralus_shared::{AsValue, Color, Random};
+use meralus_world::{AIR_BLOCK, Face, Inventory};
 
 use crate::{
     Aabb, BakedBlockModelLoader, Block, BlockManager, TextureLoader,
-    loaders::BakedBlockModel,
-    raycast::{HitType, RayCastResult},
-    renderers::Voxel,
-    vertex_ao,
+    block_updates::BlockUpdates,
+    drops,
+    entity::Entity,
+    history::{BlockEdit, EditHistory},
+    loaders::{LoadingResult, ResourceRoots},
+    meshing::GRASS_COLOR,
+    particles::ParticleSystem,
+    schematic::Schematic,
+    simulation::Simulation,
+    sky::SkyGradient,
+    spatial::SpatialHash,
+    weather::{Weather, WeatherState},
 };
 
-const GRASS_COLOR: Color = Color::from_hsl(120.0, 0.4, 0.75);
+/// Size of cells entities are bucketed into for spatial queries.
+const ENTITY_CELL_SIZE: f64 = 8.0;
+/// Amount of block edits that can be undone.
+const EDIT_HISTORY_SIZE: usize = 256;
+/// Largest amount of neighbour updates processed in a single tick.
+const BLOCK_UPDATE_LIMIT: usize = 4096;
 
 pub struct Game {
     textures: TextureLoader,
     blocks: BlockManager,
-    models: BakedBlockModelLoader,
-    chunk_manager: ChunkManager,
+    simulation: Simulation,
     players: Vec<Player>,
-    root: PathBuf,
+    entities: Vec<Entity>,
+    /// Entities indexed by their bounding boxes, rebuilt as they move.
+    entity_grid: SpatialHash,
+    history: EditHistory,
+    block_updates: BlockUpdates,
+    resources: ResourceRoots,
+    sky: SkyGradient,
+    weather: WeatherState,
 }
 
 pub struct Player {
@@ -42,115 +57,57 @@ pub struct Player {
     pub is_me: bool,
 }
 
-struct LightNode(U16Vec3, IVec2);
+// Game is the simulation plus everything needed to render it, so world logic
+// is accessed directly through it.
+impl Deref for Game {
+    type Target = Simulation;
 
-impl LightNode {
-    pub const fn get_position(&self) -> U16Vec3 {
-        self.0
+    fn deref(&self) -> &Self::Target {
+        &self.simulation
     }
 }
 
-struct BfsLight {
-    queue: Vec<LightNode>,
-}
-
-impl BfsLight {
-    const fn new() -> Self {
-        Self { queue: Vec::new() }
-    }
-
-    fn push(&mut self, node: LightNode) {
-        self.queue.push(node);
-    }
-
-    fn calculate(
-        &mut self,
-        chunk_manager: &mut ChunkManager,
-        blocks: &BakedBlockModelLoader,
-        is_sky_light: bool,
-    ) {
-        while let Some(node) = self.queue.pop() {
-            if let Some(chunk) = chunk_manager.get_chunk_mut(&node.1) {
-                let local_position = node.get_position();
-                let world_position = chunk.to_world(local_position);
-
-                let light_level = chunk.get_light(local_position, is_sky_light);
-
-                for face in Face::ALL {
-                    let neighbour_pos = world_position + face.as_normal();
-                    let neighbour_position = neighbour_pos.as_vec3();
-
-                    if let Some(chunk) =
-                        chunk_manager.get_chunk_mut(&ChunkManager::to_local(neighbour_position))
-                    {
-                        let local_position = chunk.to_local(neighbour_position);
-
-                        if !chunk.contains_local_position(local_position) {
-                            continue;
-                        }
-
-                        if chunk
-                            .get_block_unchecked(local_position)
-                            .is_none_or(|block| !blocks.get(block.into()).unwrap().is_opaque())
-                            && chunk.get_light(local_position, is_sky_light) + 2 <= light_level
-                        {
-                            chunk.set_light(
-                                local_position,
-                                is_sky_light,
-                                if is_sky_light && face == Face::Bottom && light_level == 15 {
-                                    light_level
-                                } else {
-                                    light_level - 1
-                                },
-                            );
-
-                            self.queue.push(LightNode(local_position, chunk.origin));
-                        }
-                    }
-                }
-            }
-        }
+impl DerefMut for Game {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.simulation
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq)]
-pub struct Colliders {
-    pub top: Option<DVec3>,
-    pub bottom: Option<DVec3>,
-    pub left: Option<DVec3>,
-    pub right: Option<DVec3>,
-    pub front: Option<DVec3>,
-    pub back: Option<DVec3>,
-}
-
 impl Game {
-    #[must_use]
+    /// Creates the game with blocks declared in the manifest of the resource
+    /// roots.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the block manifest cannot be found in
+    /// resource roots or cannot be successfully parsed.
     pub fn new(
         display: &WindowDisplay,
-        root: impl Into<PathBuf>,
+        resources: ResourceRoots,
         x_range: Range<i32>,
         z_range: Range<i32>,
-    ) -> Self {
-        Self {
+    ) -> LoadingResult<Self> {
+        let mut models = BakedBlockModelLoader::default();
+
+        models.load_manifest(&resources)?;
+
+        Ok(Self {
             textures: TextureLoader::new(display),
             blocks: BlockManager::new(),
-            models: BakedBlockModelLoader::default(),
+            simulation: Simulation::new(models, x_range, z_range),
             players: Vec::new(),
-            root: root.into(),
-            chunk_manager: ChunkManager::from_range(x_range, &z_range),
-        }
+            entities: Vec::new(),
+            entity_grid: SpatialHash::new(ENTITY_CELL_SIZE),
+            history: EditHistory::new(EDIT_HISTORY_SIZE),
+            block_updates: BlockUpdates::new(BLOCK_UPDATE_LIMIT),
+            resources,
+            sky: SkyGradient::default(),
+            weather: WeatherState::default(),
+        })
     }
 
-    pub const fn chunk_manager(&self) -> &ChunkManager {
-        &self.chunk_manager
-    }
-
-    pub const fn chunk_manager_mut(&mut self) -> &mut ChunkManager {
-        &mut self.chunk_manager
-    }
-
-    pub fn generate_world(&mut self, seed: u32) {
-        self.chunk_manager.generate_surface(seed);
+    pub const fn simulation(&self) -> &Simulation {
+        &self.simulation
     }
 
     pub fn add_player(&mut self, player: Player) {
@@ -161,167 +118,214 @@ impl Game {
         &self.players
     }
 
-    pub fn update_block_sky_light(&mut self, position: Vec3) {
-        let mut bfs_light = BfsLight::new();
+    pub fn spawn_entity(&mut self, entity: Entity) {
+        self.entity_grid.insert(self.entities.len(), entity.aabb());
+        self.entities.push(entity);
+    }
 
-        for face in Face::ALL {
-            let position = position + face.as_normal().as_vec3();
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
 
-            if let Some(chunk) = self
-                .chunk_manager
-                .get_chunk(&ChunkManager::to_local(position))
-            {
-                let local = chunk.to_local(position);
+    /// Lets every entity wander around and moves it according to physics.
+    pub fn update_entities(&mut self, random: &mut Random, delta: f32) {
+        for entity in &mut self.entities {
+            entity.wander(random, delta);
+            entity.update(&self.simulation, delta);
+        }
 
-                if !chunk.contains_local_position(local) {
-                    continue;
-                }
+        self.entity_grid.clear();
 
-                if chunk.get_block_unchecked(local).is_none() {
-                    bfs_light.push(LightNode(local, chunk.origin));
-                }
-            }
+        for (index, entity) in self.entities.iter().enumerate() {
+            self.entity_grid.insert(index, entity.aabb());
         }
+    }
+
+    /// Returns entities whose bounding boxes are at most `radius` away from
+    /// the center.
+    pub fn entities_in_radius(&self, center: Vec3, radius: f32) -> impl Iterator<Item = &Entity> {
+        self.entity_grid
+            .query_radius(center.as_dvec3(), f64::from(radius))
+            .into_iter()
+            .map(|index| &self.entities[index])
+    }
 
-        bfs_light.calculate(&mut self.chunk_manager, &self.models, true);
+    /// Returns entities whose bounding boxes intersect with the given one.
+    pub fn entities_in(&self, aabb: Aabb) -> impl Iterator<Item = &Entity> {
+        self.entity_grid
+            .query_aabb(aabb)
+            .into_iter()
+            .map(|index| &self.entities[index])
     }
 
-    pub fn generate_lights(&mut self) {
-        let mut bfs_light = BfsLight::new();
+    /// Returns origins of chunks whose meshes have to be updated after the
+    /// block at the position is changed, which includes neighbouring chunks
+    /// when the block lies on their border.
+    pub fn chunks_affected_by(&self, position: Vec3) -> Vec<IVec2> {
+        self.simulation.chunk_manager().chunks_affected_by(position)
+    }
 
-        for chunk in self.chunk_manager.chunks_mut() {
-            for z in 0..CHUNK_SIZE {
-                for x in 0..CHUNK_SIZE {
-                    let position = u16vec3(x as u16, 255, z as u16);
+    /// Sets the block and updates light around it, returning origins of
+    /// chunks that have to be remeshed. Neighbours of the block are notified
+    /// about the change on the next tick.
+    fn apply_edit(&mut self, position: Vec3, block: u8) -> HashSet<IVec2> {
+        let mut chunks = self.simulation.edit_block(position, block);
 
-                    if chunk
-                        .get_block_unchecked(position)
-                        .is_none_or(|block| !self.models.get(block.into()).unwrap().is_opaque())
-                    {
-                        chunk.set_sky_light(position, 15);
+        self.block_updates.notify_neighbours(position);
 
-                        bfs_light.push(LightNode(position, chunk.origin));
-                    }
-                }
-            }
+        if self
+            .blocks
+            .get(block.into())
+            .is_some_and(|block| block.falls())
+        {
+            self.block_updates.schedule(position);
         }
 
-        bfs_light.calculate(&mut self.chunk_manager, &self.models, true);
+        chunks.extend(self.chunks_affected_by(position));
+
+        chunks
     }
 
-    pub fn set_block_light(&mut self, position: Vec3, light_level: u8) {
-        let mut bfs_light = BfsLight::new();
+    /// Same as [`Game::apply_edit`], but the edit is recorded, so that it can
+    /// be undone.
+    pub fn edit_block(&mut self, position: Vec3, block: u8) -> HashSet<IVec2> {
+        let old = self
+            .simulation
+            .chunk_manager()
+            .get_block(position)
+            .unwrap_or(AIR_BLOCK);
 
-        if let Some(chunk) = self
-            .chunk_manager
-            .get_chunk_mut(&ChunkManager::to_local(position))
-        {
-            let position = chunk.to_local(position);
+        self.history.record(BlockEdit {
+            position,
+            old,
+            new: block,
+        });
 
-            chunk.set_block_light(position, light_level);
+        self.apply_edit(position, block)
+    }
 
-            bfs_light.push(LightNode(position, chunk.origin));
-        }
+    /// Reverts the last block edit, returning origins of chunks that have to
+    /// be remeshed, or [`None`] if there is nothing to undo.
+    pub fn undo(&mut self) -> Option<HashSet<IVec2>> {
+        let edit = self.history.undo()?;
 
-        bfs_light.calculate(&mut self.chunk_manager, &self.models, false);
+        Some(self.apply_edit(edit.position, edit.old))
     }
 
-    pub fn register_block<T: Block + 'static>(&mut self, block: T) {
-        let id = block.id();
+    /// Reapplies the last undone block edit, returning origins of chunks that
+    /// have to be remeshed, or [`None`] if there is nothing to redo.
+    pub fn redo(&mut self) -> Option<HashSet<IVec2>> {
+        let edit = self.history.redo()?;
 
-        self.load_block(self.root.join("models").join(id).with_extension("json"));
+        Some(self.apply_edit(edit.position, edit.new))
+    }
 
-        self.blocks.register(block);
+    /// Copies blocks of the box between two corners, both of which are
+    /// included.
+    pub fn copy_region(&self, min: Vec3, max: Vec3) -> Schematic {
+        Schematic::copy(self.simulation.chunk_manager(), min, max)
     }
 
-    pub fn load_block<P: AsRef<Path>>(&mut self, path: P) {
-        self.models
-            .load(&mut self.textures, &self.root, path)
-            .unwrap();
+    /// Pastes the schematic with its minimum corner at the given position,
+    /// returning origins of chunks that have to be remeshed.
+    pub fn paste(&mut self, schematic: &Schematic, at: Vec3) -> HashSet<IVec2> {
+        schematic.paste(&mut self.simulation, at)
     }
 
-    pub fn collides(&self, aabb: Aabb) -> bool {
-        let min = aabb.min.floor().as_ivec3().to_array();
-        let max = aabb.max.ceil().as_ivec3().to_array();
+    /// Processes neighbour updates queued since the last tick, returning
+    /// origins of chunks that have to be remeshed.
+    pub fn update_blocks(&mut self) -> HashSet<IVec2> {
+        self.block_updates
+            .process(&self.blocks, &mut self.simulation)
+    }
 
-        for y in min[1]..max[1] {
-            for z in min[2]..max[2] {
-                for x in min[0]..max[0] {
-                    let position = ivec3(x, y, z).as_dvec3();
+    /// Returns gradient the sky color is sampled from over the day.
+    pub const fn sky(&self) -> &SkyGradient {
+        &self.sky
+    }
 
-                    if self.chunk_manager.contains_block(position.as_vec3()) {
-                        let block = Aabb::new(position, position + DVec3::ONE);
+    pub const fn weather(&self) -> &WeatherState {
+        &self.weather
+    }
 
-                        if aabb.intersects_with_x(block)
-                            && aabb.intersects_with_y(block)
-                            && aabb.intersects_with_z(block)
-                        {
-                            return true;
-                        }
-                    }
-                }
-            }
-        }
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.weather.set(weather);
+    }
 
-        false
-    }
-
-    pub fn get_colliders(&self, collider_position: DVec3, aabb: Aabb) -> Colliders {
-        let min = aabb.min.floor().as_ivec3().to_array();
-        let max = aabb.max.ceil().as_ivec3().to_array();
-
-        let mut colliders = Colliders::default();
-
-        for y in min[1]..max[1] {
-            for z in min[2]..max[2] {
-                for x in min[0]..max[0] {
-                    let position = ivec3(x, y, z).as_dvec3();
-
-                    if self.chunk_manager.contains_block(position.as_vec3()) {
-                        let block = Aabb::new(position, position + DVec3::ONE);
-
-                        if aabb.intersects_with_x(block)
-                            && aabb.intersects_with_y(block)
-                            && aabb.intersects_with_z(block)
-                        {
-                            let colliding_position = position - collider_position.floor();
-
-                            if colliding_position.x < 0.0 {
-                                colliders.left = Some(position);
-                            } else if colliding_position.x > 0.0 {
-                                colliders.right = Some(position);
-                            } else if colliding_position.y < 0.0 {
-                                colliders.bottom = Some(position);
-                            } else if colliding_position.y > 0.0 {
-                                colliders.top = Some(position);
-                            } else if colliding_position.z < 0.0 {
-                                colliders.back = Some(position);
-                            } else if colliding_position.z > 0.0 {
-                                colliders.front = Some(position);
-                            }
-                        }
-                    }
-                }
-            }
+    /// Advances weather transition, emitting precipitation around the given
+    /// position.
+    pub fn update_weather(&mut self, delta: f32, particles: &mut ParticleSystem, center: Vec3) {
+        self.weather.update(delta, particles, center);
+    }
+
+    /// Returns block placed at the given position, which is [`None`] for air
+    /// and unloaded chunks.
+    pub fn get_block_at(&self, position: Vec3) -> Option<&dyn Block> {
+        self.simulation
+            .chunk_manager()
+            .get_block(position)
+            .and_then(|id| self.blocks.get(usize::from(id)))
+    }
+
+    /// Adds items dropped by the block at the given position to the
+    /// inventory.
+    pub fn collect_drops(&self, position: Vec3, inventory: &mut Inventory, random: &mut Random) {
+        if let Some(block) = self.get_block_at(position) {
+            drops::collect_drops(
+                block,
+                self.simulation.models().manifest(),
+                inventory,
+                random,
+            );
         }
+    }
 
-        colliders
+    /// Registers block and loads its model.
+    ///
+    /// # Panics
+    ///
+    /// Panics if blocks are registered in a different order than the one
+    /// declared in the block manifest (starting at id `1`, as `0` is air).
+    pub fn register_block<T: Block + 'static>(&mut self, block: T) {
+        let name = block.id();
+
+        self.load_block(Path::new("models").join(name).with_extension("json"));
+
+        let id = self.blocks.register(block);
+
+        assert_eq!(
+            self.simulation
+                .models()
+                .manifest()
+                .id(name)
+                .map(usize::from),
+            Some(id),
+            "block `{name}` is registered with an id different from the manifest one"
+        );
     }
 
+    pub fn load_block<P: AsRef<Path>>(&mut self, path: P) {
+        self.simulation
+            .models_mut()
+            .load(&mut self.textures, &self.resources, path)
+            .unwrap();
+    }
+
+    /// Loads every model declared in the block manifest. Models missing from
+    /// it (like `cuboid` templates) are skipped, as they have no id.
     pub fn load_buitlin_blocks(&mut self) {
-        if let Ok(root) = self.root.join("models").read_dir()
-            && let Ok(mut root) = root.collect::<Result<Vec<_>, _>>()
-        {
-            root.sort_by_key(DirEntry::file_name);
-
-            for entry in root {
-                if entry.metadata().is_ok_and(|metadata| metadata.is_file())
-                    && !entry.file_name().to_string_lossy().starts_with("cuboid")
-                {
-                    self.models
-                        .load(&mut self.textures, &self.root, entry.path())
-                        .unwrap();
-                }
+        for path in self.resources.list("models") {
+            if path.file_stem().is_some_and(|name| {
+                self.simulation
+                    .models()
+                    .manifest()
+                    .id(name.to_string_lossy())
+                    .is_some()
+            }) {
+                self.simulation
+                    .models_mut()
+                    .load(&mut self.textures, &self.resources, path)
+                    .unwrap();
             }
         }
     }
@@ -334,6 +338,12 @@ impl Game {
         self.textures.load(path).unwrap();
     }
 
+    /// Uploads all loaded textures to a new atlas created with the given
+    /// display.
+    pub fn reload_textures(&mut self, display: &WindowDisplay) {
+        self.textures.reload(display).unwrap();
+    }
+
     pub const fn get_texture_atlas(&self) -> &Texture2d {
         self.textures.get_atlas()
     }
@@ -354,396 +364,33 @@ impl Game {
         self.textures.get_texture(name.as_ref())
     }
 
-    fn raycast_into(position: Vec3, start: DVec3, end: DVec3, aabb: Aabb) -> Option<RayCastResult> {
-        aabb.calculate_intercept(start - position.as_dvec3(), end - position.as_dvec3())
-            .map(|raytraceresult| {
-                RayCastResult::new3(
-                    raytraceresult.hit_vec + position.as_dvec3(),
-                    raytraceresult.hit_side,
-                    position,
-                )
-            })
-    }
-
-    #[allow(clippy::too_many_lines, clippy::cognitive_complexity)]
-    pub fn raycast(
-        &self,
-        mut origin: DVec3,
-        target: DVec3,
-        last_uncollidable_block: bool,
-    ) -> Option<RayCastResult> {
-        if origin.is_nan() || target.is_nan() {
-            None
-        } else {
-            let mut start = origin.floor();
-            let end = target.floor();
-
-            let mut position = start.as_vec3();
-            let block = self.get_model_for(position);
-
-            if let Some(block) = block {
-                let result =
-                    Self::raycast_into(position, origin, target, Aabb::from(block.bounding_box));
-
-                if result.is_some() {
-                    return result;
-                }
-            }
-
-            let mut result: Option<RayCastResult> = None;
-
-            for _ in 0..200 {
-                if origin.is_nan() {
-                    return None;
-                }
-
-                if (start.x - end.x).abs() < 0.0001
-                    && (start.y - end.y).abs() < 0.0001
-                    && (start.z - end.z).abs() < 0.0001
-                {
-                    return if last_uncollidable_block {
-                        result
-                    } else {
-                        None
-                    };
-                }
-
-                let mut modify_d3 = true;
-                let mut modify_d4 = true;
-                let mut modify_d5 = true;
-
-                let mut d0 = 999.0f64;
-                let mut d1 = 999.0f64;
-                let mut d2 = 999.0f64;
-
-                if end.x > start.x {
-                    d0 = start.x + 1.0;
-                } else if end.x < start.x {
-                    d0 = start.x + 0.0;
-                } else {
-                    modify_d3 = false;
-                }
-
-                if end.y > start.y {
-                    d1 = start.y + 1.0;
-                } else if end.y < start.y {
-                    d1 = start.y + 0.0;
-                } else {
-                    modify_d4 = false;
-                }
-
-                if end.z > start.z {
-                    d2 = start.z + 1.0;
-                } else if end.z < start.z {
-                    d2 = start.z + 0.0;
-                } else {
-                    modify_d5 = false;
-                }
-
-                let mut d3 = 999.0f64;
-                let mut d4 = 999.0f64;
-                let mut d5 = 999.0f64;
-
-                let d6 = target.x - origin.x;
-                let d7 = target.y - origin.y;
-                let d8 = target.z - origin.z;
-
-                if modify_d3 {
-                    d3 = (d0 - origin.x) / d6;
-                }
-
-                if modify_d4 {
-                    d4 = (d1 - origin.y) / d7;
-                }
-
-                if modify_d5 {
-                    d5 = (d2 - origin.z) / d8;
-                }
-
-                if d3 == -0.0 {
-                    d3 = -0.0001;
-                }
-
-                if d4 == -0.0 {
-                    d4 = -0.0001;
-                }
-
-                if d5 == -0.0 {
-                    d5 = -0.0001;
-                }
-
-                let facing_at = if d3 < d4 && d3 < d5 {
-                    origin = DVec3::new(d0, d7.mul_add(d3, origin.y), d8.mul_add(d3, origin.z));
-
-                    if end.x > start.x {
-                        Face::Left
-                    } else {
-                        Face::Right
-                    }
-                } else if d4 < d5 {
-                    origin = DVec3::new(d6.mul_add(d4, origin.x), d1, d8.mul_add(d4, origin.z));
-
-                    if end.y > start.y {
-                        Face::Bottom
-                    } else {
-                        Face::Top
-                    }
-                } else {
-                    origin = DVec3::new(d6.mul_add(d5, origin.x), d7.mul_add(d5, origin.y), d2);
-
-                    if end.z > start.z {
-                        Face::Front
-                    } else {
-                        Face::Back
-                    }
-                };
-
-                start = origin.floor()
-                    - match facing_at {
-                        Face::Right => DVec3::X,
-                        Face::Top => DVec3::Y,
-                        Face::Back => DVec3::Z,
-                        Face::Bottom | Face::Left | Face::Front => DVec3::ZERO,
-                    };
-
-                position = start.as_vec3();
-
-                let block = self.get_model_for(position);
-
-                if let Some(block) = block {
-                    let result = Self::raycast_into(
-                        position,
-                        origin,
-                        target,
-                        Aabb::from(block.bounding_box),
-                    );
-
-                    if result.is_some() {
-                        return result;
-                    }
-                } else {
-                    result.replace(RayCastResult::new(
-                        HitType::None,
-                        origin,
-                        facing_at,
-                        position,
-                    ));
-                }
-            }
-
-            if last_uncollidable_block {
-                result
-            } else {
-                None
-            }
-        }
-    }
-
-    pub fn compute_chunk_mesh_at(&self, position: &IVec2) -> Option<[(Face, [Vec<Voxel>; 2]); 6]> {
-        self.chunk_manager
-            .get_chunk(position)
-            .map(|chunk| self.compute_chunk_mesh(chunk))
-    }
-
-    pub fn get_model_for(&self, position: Vec3) -> Option<&BakedBlockModel> {
-        self.chunk_manager
-            .get_block(position)
-            .and_then(|block| self.models.get(block.into()))
-    }
-
-    #[allow(clippy::too_many_lines)]
-    pub fn compute_chunk_mesh(&self, chunk: &Chunk) -> [(Face, [Vec<Voxel>; 2]); 6] {
-        let origin = chunk.origin.as_vec2();
-        let mut voxels = Face::ALL.map(|face| (face, [const { Vec::new() }; 2]));
-
-        for y in 0..(CHUNK_SIZE_U16 * SUBCHUNK_COUNT_U16) {
-            for z in 0..CHUNK_SIZE_U16 {
-                for x in 0..CHUNK_SIZE_U16 {
-                    let local_position = u16vec3(x, y, z);
-                    let world_position =
-                        local_position.as_vec3() + (vec3(origin.x, 0.0, origin.y) * CHUNK_SIZE_F32);
-
-                    if let Some(model) = chunk
-                        .get_block(local_position)
-                        .and_then(|block_id| self.models.get(block_id.into()))
-                    {
-                        let position = local_position.as_vec3()
-                            + (vec3(origin.x, 0.0, origin.y) * CHUNK_SIZE_F32);
-
-                        for element in &model.elements {
-                            let matrix = element.rotation.map(|rotation| {
-                                let angle = rotation.angle.to_radians();
-
-                                let matrix;
-                                let mut scale = Vec3::ZERO;
-
-                                match rotation.axis {
-                                    Axis::X => {
-                                        matrix = Mat4::from_rotation_x(angle);
-
-                                        scale.y = 1.0;
-                                        scale.z = 1.0;
-                                    }
-                                    Axis::Y => {
-                                        matrix = Mat4::from_rotation_y(angle);
-
-                                        scale.x = 1.0;
-                                        scale.z = 1.0;
-                                    }
-                                    Axis::Z => {
-                                        matrix = Mat4::from_rotation_z(angle);
-
-                                        scale.x = 1.0;
-                                        scale.y = 1.0;
-                                    }
-                                }
-
-                                scale = Vec3::ONE;
-
-                                (matrix, rotation.origin, scale)
-                            });
-
-                            for model_face in element.faces.iter().flatten() {
-                                let neighbour_position =
-                                    world_position + model_face.face.as_normal().as_vec3();
-
-                                let culled = model_face.cull_face.is_some_and(|cull_face| {
-                                    let neighbour = self.chunk_manager.get_block(
-                                        world_position + cull_face.as_normal().as_vec3(),
-                                    );
-
-                                    neighbour
-                                        .and_then(|neighbour| self.models.get(neighbour.into()))
-                                        .is_some_and(|model| {
-                                            if model.is_opaque() {
-                                                true
-                                            } else {
-                                                let opposite_face = cull_face.opposite();
-
-                                                model.elements.iter().any(|element| {
-                                                    element.faces[opposite_face.normal_index()]
-                                                        .as_ref()
-                                                        .is_some_and(|face| {
-                                                            if face.is_opaque {
-                                                                true
-                                                            } else {
-                                                                face.uv.eq(&model_face.uv)
-                                                            }
-                                                        })
-                                                })
-                                            }
-                                        })
-                                });
-
-                                if !culled {
-                                    let mut vertices =
-                                        model_face.face.as_vertices().map(|vertice| {
-                                            Vec3::from_array(element.cube.origin.to_array())
-                                                + vertice
-                                                    * Vec3::from_array(element.cube.size.to_array())
-                                        });
-
-                                    let mut uvs = model_face.face.as_uv();
-
-                                    let mut aos =
-                                        model_face.face.as_vertice_corners().map(|corner| {
-                                            let [side1, side2, corner] = corner
-                                                .get_neighbours(model_face.face)
-                                                .map(|neighbour| {
-                                                    self.chunk_manager
-                                                        .get_block(position + neighbour.as_vec3())
-                                                        .is_some_and(|block| {
-                                                            self.models
-                                                                .get(block.into())
-                                                                .unwrap()
-                                                                .ambient_occlusion
-                                                        })
-                                                });
-
-                                            vertex_ao(side1, side2, corner)
-                                        });
-
-                                    // let mut aos_flipped = false;
-
-                                    if aos[1] + aos[2] > aos[0] + aos[3] {
-                                        // aos_flipped = true;
-
-                                        // aos = aos[1], aos[2], aos[3], aos[0]
-
-                                        vertices.swap(0, 1);
-                                        vertices.swap(1, 2);
-                                        vertices.swap(2, 3);
-
-                                        aos.swap(0, 1);
-                                        aos.swap(1, 2);
-                                        aos.swap(2, 3);
-
-                                        uvs.swap(0, 1);
-                                        uvs.swap(1, 2);
-                                        uvs.swap(2, 3);
-                                    }
-
-                                    let vertices =
-                                        matrix.map_or(vertices, |(matrix, origin, scale)| {
-                                            vertices.map(|vertice| {
-                                                matrix.transform_point3(vertice - origin) * scale
-                                                    + origin
-                                            })
-                                        });
-
-                                    let voxels = &mut voxels[model_face.face.normal_index()].1;
-                                    let voxels = if model_face.is_opaque {
-                                        &mut voxels[0]
-                                    } else {
-                                        &mut voxels[1]
-                                    };
-
-                                    voxels.push(Voxel {
-                                        position,
-                                        vertices,
-                                        face: model_face.face,
-                                        origin: chunk.origin,
-                                        aos,
-                                        light: self.chunk_manager.get_light(neighbour_position),
-                                        color: if model.name == "grass_block" && model_face.tint {
-                                            GRASS_COLOR
-                                        } else {
-                                            Color::WHITE
-                                        },
-                                        uvs: uvs.map(|uv| {
-                                            model_face.uv.offset + uv * model_face.uv.scale
-                                        }),
-                                        is_opaque: model_face.is_opaque,
-                                    });
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        voxels
+    /// Returns the color of the top face of the block at the given position,
+    /// tinted the same way as when meshing it.
+    pub fn get_block_color(&self, position: Vec3) -> Option<Color> {
+        self.get_item_color(self.simulation.chunk_manager().get_block(position)?)
     }
 
-    #[must_use]
-    pub fn compute_world_mesh(&self) -> HashMap<(IVec2, Face), [Vec<Voxel>; 2]> {
-        let mut meshes = HashMap::new();
-
-        for chunk in self.chunk_manager.chunks() {
-            for (face, data) in self.compute_chunk_mesh(chunk) {
-                meshes.insert((chunk.origin, face), data);
-            }
-
-            println!(
-                "[{:18}] Generated mesh for chunk at {}",
-                "INFO/Rendering".bright_green(),
-                format!("{:>2} {:>2}", chunk.origin.x, chunk.origin.y)
-                    .bright_blue()
-                    .bold()
-            );
-        }
-
-        meshes
+    /// Same as [`Game::get_block_color`], but for the block with the given
+    /// id, which is how its item is displayed.
+    pub fn get_item_color(&self, id: u8) -> Option<Color> {
+        let model = self.model_for_id(usize::from(id))?;
+        let faces = model
+            .elements
+            .iter()
+            .flat_map(|element| element.faces.iter().flatten())
+            .collect::<Vec<_>>();
+
+        let face = faces
+            .iter()
+            .find(|face| face.face == Face::Top)
+            .or_else(|| faces.first())?;
+
+        Some(if model.name == "grass_block" && face.tint {
+            Color::from(
+                AsValue::<Vec4>::as_value(&face.color) * AsValue::<Vec4>::as_value(&GRASS_COLOR),
+            )
+        } else {
+            face.color
+        })
     }
 }
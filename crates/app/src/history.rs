@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+
+use glam::Vec3;
+
+/// Block replaced at a position, which can be reverted by setting the old
+/// block back.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockEdit {
+    pub position: Vec3,
+    pub old: u8,
+    pub new: u8,
+}
+
+/// Bounded history of block edits, allowing to undo them and to redo undone
+/// ones until something else is edited.
+#[derive(Debug, Clone)]
+pub struct EditHistory {
+    done: VecDeque<BlockEdit>,
+    undone: Vec<BlockEdit>,
+    capacity: usize,
+}
+
+impl EditHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            done: VecDeque::with_capacity(capacity),
+            undone: Vec::new(),
+            capacity,
+        }
+    }
+
+    /// Remembers the edit, forgetting the oldest one when the history is
+    /// full. Undone edits can't be redone after this.
+    pub fn record(&mut self, edit: BlockEdit) {
+        if self.done.len() == self.capacity {
+            self.done.pop_front();
+        }
+
+        self.done.push_back(edit);
+        self.undone.clear();
+    }
+
+    /// Returns the last edit, whose old block has to be set back.
+    pub fn undo(&mut self) -> Option<BlockEdit> {
+        let edit = self.done.pop_back()?;
+
+        self.undone.push(edit);
+
+        Some(edit)
+    }
+
+    /// Returns the last undone edit, whose new block has to be set again.
+    pub fn redo(&mut self) -> Option<BlockEdit> {
+        let edit = self.undone.pop()?;
+
+        self.done.push_back(edit);
+
+        Some(edit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use meralus_world::AIR_BLOCK;
+
+    use super::{BlockEdit, EditHistory};
+    use crate::{BakedBlockModelLoader, simulation::Simulation};
+
+    #[test]
+    fn test_undo_redo() {
+        let mut simulation = Simulation::new(BakedBlockModelLoader::default(), 0..1, 0..1);
+        let mut history = EditHistory::new(8);
+        let position = Vec3::new(4.0, 100.0, 4.0);
+
+        for block in [1, 2] {
+            history.record(BlockEdit {
+                position,
+                old: simulation
+                    .chunk_manager()
+                    .get_block(position)
+                    .unwrap_or(AIR_BLOCK),
+                new: block,
+            });
+
+            simulation.edit_block(position, block);
+        }
+
+        let edit = history.undo().unwrap();
+
+        simulation.edit_block(edit.position, edit.old);
+
+        assert_eq!(simulation.chunk_manager().get_block(position), Some(1));
+
+        let edit = history.undo().unwrap();
+
+        simulation.edit_block(edit.position, edit.old);
+
+        assert_eq!(simulation.chunk_manager().get_block(position), None);
+        assert!(history.undo().is_none());
+
+        let edit = history.redo().unwrap();
+
+        simulation.edit_block(edit.position, edit.new);
+
+        assert_eq!(simulation.chunk_manager().get_block(position), Some(1));
+
+        // a new edit makes undone ones impossible to redo
+        history.record(BlockEdit {
+            position,
+            old: 1,
+            new: 3,
+        });
+
+        assert!(history.redo().is_none());
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut history = EditHistory::new(3);
+
+        for new in 1..=5 {
+            history.record(BlockEdit {
+                position: Vec3::ZERO,
+                old: new - 1,
+                new,
+            });
+        }
+
+        let undone = std::iter::from_fn(|| history.undo())
+            .map(|edit| edit.new)
+            .collect::<Vec<_>>();
+
+        assert_eq!(undone, [5, 4, 3]);
+    }
+}
@@ -0,0 +1,60 @@
+#![allow(
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::unreadable_literal,
+    clippy::missing_panics_doc
+)]
+
+pub mod aabb;
+pub mod block_updates;
+pub mod blocks;
+pub mod camera;
+pub mod clock;
+pub mod drops;
+pub mod entity;
+pub mod game;
+pub mod history;
+pub mod keyboard;
+pub mod loaders;
+pub mod meshing;
+pub mod mining;
+pub mod multiplayer;
+pub mod nameplate;
+pub mod particles;
+pub mod player;
+pub mod quality;
+pub mod raycast;
+pub mod renderers;
+pub mod save;
+pub mod schematic;
+pub mod simulation;
+pub mod sky;
+pub mod spatial;
+pub mod transform;
+pub mod util;
+pub mod weather;
+
+use glium::{Blend, BlendingFunction, LinearBlendingFactor};
+
+pub use self::{
+    aabb::Aabb,
+    game::Game,
+    loaders::{BakedBlockModelLoader, Block, BlockManager, ResourceRoots, TextureLoader},
+    player::PlayerController,
+    transform::Transform,
+    util::{AsColor, CameraExt, get_movement_direction, get_rotation_directions, vertex_ao},
+};
+
+const BLENDING: Blend = Blend {
+    color: BlendingFunction::Addition {
+        source: LinearBlendingFactor::SourceAlpha,
+        destination: LinearBlendingFactor::OneMinusSourceAlpha,
+    },
+    alpha: BlendingFunction::Addition {
+        source: LinearBlendingFactor::One,
+        destination: LinearBlendingFactor::OneMinusSourceAlpha,
+    },
+    constant_value: (0.0, 0.0, 0.0, 0.0),
+};
@@ -1,15 +1,59 @@
 use std::{fs, path::Path};
 
-use meralus_world::{BlockModel, Property, TexturePath, TextureRef};
+use glam::Vec3;
+use meralus_world::{AIR_BLOCK, BlockModel, Property, TexturePath, TextureRef};
+use owo_colors::OwoColorize;
 
-use super::{LoadingError, LoadingResult, ModelLoadingError, texture::TextureLoader};
+use super::{
+    LoadingError, LoadingResult, ModelLoadingError, resources::ResourceRoots,
+    texture::TextureLoader,
+};
+use crate::{drops::Drops, simulation::Simulation};
+
+/// Seconds it takes to break a block with hardness of `1.0` by hand.
+const BREAK_TIME_PER_HARDNESS: f32 = 1.5;
 
 pub trait Block {
     fn id(&self) -> &'static str;
 
+    /// Resistance of the block to mining, which the time it takes to break
+    /// grows proportionally to.
+    fn hardness(&self) -> f32 {
+        1.0
+    }
+
+    /// Seconds it takes to break the block by hand.
+    fn break_time(&self) -> f32 {
+        self.hardness() * BREAK_TIME_PER_HARDNESS
+    }
+
+    /// Items dropped when the block is broken.
+    fn drops(&self) -> Drops {
+        Drops::Itself
+    }
+
+    /// Whether the block falls down one block per tick while there is air
+    /// below it, like sand. Falling blocks don't get
+    /// [`Block::on_neighbour_changed`] called.
+    fn falls(&self) -> bool {
+        false
+    }
+
     fn get_properties(&self) -> Vec<Property> {
         Vec::new()
     }
+
+    /// Called on the tick after a block next to this one was changed,
+    /// returning positions of blocks changed in response, whose neighbours
+    /// get notified in turn.
+    fn on_neighbour_changed(
+        &self,
+        _simulation: &mut Simulation,
+        _position: Vec3,
+        _neighbour: Vec3,
+    ) -> Vec<Vec3> {
+        Vec::new()
+    }
 }
 
 pub struct BlockManager {
@@ -27,17 +71,27 @@ impl BlockManager {
         Self { blocks: Vec::new() }
     }
 
+    /// Returns block with the given id, which is always [`None`] for air.
     pub fn get(&self, id: usize) -> Option<&dyn Block> {
-        self.blocks.get(id).map(|v| &**v)
+        id.checked_sub(usize::from(AIR_BLOCK) + 1)
+            .and_then(|index| self.blocks.get(index))
+            .map(|v| &**v)
     }
 
-    pub fn register<T: Block + 'static>(&mut self, block: T) {
+    /// Registers block, returning its id. Id of air is reserved, so the first
+    /// registered block gets the id following it.
+    pub fn register<T: Block + 'static>(&mut self, block: T) -> usize {
         self.blocks.push(Box::new(block) as Box<dyn Block>);
+
+        usize::from(AIR_BLOCK) + self.blocks.len()
     }
 
-    fn load_block<P: AsRef<Path>, R: AsRef<Path>>(root: R, path: P) -> LoadingResult<BlockModel> {
+    fn load_block<P: AsRef<Path>>(resources: &ResourceRoots, path: P) -> LoadingResult<BlockModel> {
         let path = path.as_ref().with_extension("json");
-        let data = fs::read(&path).map_err(|_| LoadingError::Model(ModelLoadingError::NotFound))?;
+        let data = resources
+            .resolve(&path)
+            .and_then(|path| fs::read(path).ok())
+            .ok_or(LoadingError::Model(ModelLoadingError::NotFound))?;
         let block = BlockModel::from_slice(&data)
             .map_err(|err| LoadingError::Model(ModelLoadingError::ParsingFailed(err)))?;
 
@@ -46,10 +100,14 @@ impl BlockManager {
             .as_ref()
             .and_then(|parent| path.parent().map(|dir| dir.join(parent)))
         {
-            let mut parent_block = Self::load_block(root.as_ref(), parent)?;
+            let mut parent_block = Self::load_block(resources, parent)?;
 
             parent_block.textures.extend(block.textures);
             parent_block.elements.extend(block.elements);
+            parent_block.transparent |= block.transparent;
+            parent_block.cull_same_block |= block.cull_same_block;
+            parent_block.connected_texture =
+                block.connected_texture.or(parent_block.connected_texture);
 
             parent_block
         } else {
@@ -59,34 +117,76 @@ impl BlockManager {
         Ok(block)
     }
 
+    /// Loads block model at the path relative to resource roots, along with
+    /// its textures.
+    ///
     /// # Errors
     ///
     /// An error will be returned if:
     /// - The passed path does not contain a filename.
     /// - The passed path cannot be read.
     /// - The passed path data cannot be successfully parsed.
+    /// - The parsed model is invalid (see [`BlockModel::validate`]).
     /// - An error occurred while loading some texture (see
     ///   [`TextureLoader::load`]).
-    pub fn load<P: AsRef<Path>, R: AsRef<Path>>(
+    pub fn load<P: AsRef<Path>>(
         textures: &mut TextureLoader,
-        root: R,
+        resources: &ResourceRoots,
         path: P,
     ) -> LoadingResult<BlockModel> {
-        let block = Self::load_block(root.as_ref(), path)?;
+        let path = path.as_ref();
+        let block = Self::load_block(resources, path)?;
+
+        if let Err(problems) = block.validate() {
+            for problem in &problems {
+                println!(
+                    "[{:18}] Invalid model at {}: {problem}",
+                    " ERR/ModelLoader".bright_red(),
+                    path.display().bright_blue().bold()
+                );
+            }
+
+            return Err(LoadingError::Model(ModelLoadingError::Invalid(problems)));
+        }
 
         for texture_ref in block.textures.values() {
             if let TextureRef::Path(TexturePath(mod_name, path)) = texture_ref
                 && mod_name == "game"
             {
-                textures.load(
-                    root.as_ref()
-                        .join("textures")
-                        .join(path)
-                        .with_extension("png"),
-                )?;
+                let path = Path::new("textures").join(path).with_extension("png");
+
+                textures.load(resources.resolve(&path).unwrap_or(path))?;
             }
         }
 
         Ok(block)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use meralus_world::AIR_BLOCK;
+
+    use super::{Block, BlockManager};
+
+    struct TestBlock(&'static str);
+
+    impl Block for TestBlock {
+        fn id(&self) -> &'static str {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_air_id_is_reserved() {
+        let mut blocks = BlockManager::new();
+
+        assert_eq!(blocks.register(TestBlock("dirt")), 1);
+        assert_eq!(blocks.register(TestBlock("stone")), 2);
+
+        assert!(blocks.get(AIR_BLOCK.into()).is_none());
+        assert_eq!(blocks.get(1).map(Block::id), Some("dirt"));
+        assert_eq!(blocks.get(2).map(Block::id), Some("stone"));
+        assert!(blocks.get(3).is_none());
+    }
+}
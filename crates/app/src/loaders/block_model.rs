@@ -1,20 +1,52 @@
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, fs, path::Path};
 
 use glam::{Vec2, Vec3};
 use glamour::ToRaw;
-use meralus_shared::Cube3D;
-use meralus_world::{ElementRotation, Face, Faces, JsonError, TexturePath, TextureRef};
+use meralus_shared::{Color, Cube3D};
+use meralus_world::{
+    BlockManifest, ElementRotation, Face, Faces, JsonError, ModelProblem, TexturePath, TextureRef,
+};
 use owo_colors::OwoColorize;
 
-use super::{LoadingResult, block::BlockManager, texture::TextureLoader};
-use crate::{Game, loaders::LoadingError};
+use super::{LoadingResult, block::BlockManager, resources::ResourceRoots, texture::TextureLoader};
+use crate::{loaders::LoadingError, simulation::Simulation};
 
-#[derive(Debug, PartialEq)]
+/// Number of connected texture variants, one for every combination of
+/// connected neighbours on the four sides of a face.
+pub const CONNECTED_VARIANTS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct FaceUV {
     pub offset: Vec2,
     pub scale: Vec2,
 }
 
+impl FaceUV {
+    /// Maps the optional start and size of the used part of the texture onto
+    /// the area the texture occupies in the atlas.
+    pub fn new(offset: Vec2, scale: Vec2, uv: Option<[Vec2; 2]>) -> Self {
+        uv.map_or(Self { offset, scale }, |[start, size]| Self {
+            offset: offset + start * scale,
+            scale: scale * size,
+        })
+    }
+
+    /// Splits the texture into a 4x4 grid of connected texture variants,
+    /// ordered by rows starting at the texture origin, so that the variant
+    /// index is the mask of connected sides (see
+    /// [`meshing::connected_variant`]).
+    ///
+    /// [`meshing::connected_variant`]: crate::meshing::connected_variant
+    pub fn connected_variants(self) -> [Self; CONNECTED_VARIANTS] {
+        let scale = self.scale / 4.0;
+
+        std::array::from_fn(|index| Self {
+            offset: self.offset + Vec2::new((index % 4) as f32, (index / 4) as f32) * scale,
+            scale,
+        })
+    }
+}
+
 #[derive(Debug)]
 pub struct BlockModelFace {
     pub texture_id: usize,
@@ -23,12 +55,15 @@ pub struct BlockModelFace {
     pub tint: bool,
     pub uv: FaceUV,
     pub is_opaque: bool,
+    /// Average color of the face texture.
+    pub color: Color,
 }
 
 impl BlockModelFace {
-    pub fn culled(&self, game: &Game, position: Vec3) -> bool {
+    pub fn culled(&self, simulation: &Simulation, position: Vec3) -> bool {
         self.cull_face.is_some_and(|cull_face| {
-            game.chunk_manager()
+            simulation
+                .chunk_manager()
                 .contains_block(position + cull_face.as_normal().as_vec3())
         })
     }
@@ -46,6 +81,13 @@ pub struct BakedBlockModel {
     pub name: String,
     pub bounding_box: Cube3D,
     pub ambient_occlusion: bool,
+    /// Whether the model is see-through and never hides faces of its
+    /// neighbours.
+    pub transparent: bool,
+    /// Whether faces between neighbouring blocks of this model are hidden.
+    pub cull_same_block: bool,
+    /// Connected texture variants replacing textures of every face.
+    pub connected: Option<[FaceUV; CONNECTED_VARIANTS]>,
     pub elements: Vec<BlockModelElement>,
 }
 
@@ -53,14 +95,38 @@ const ERROR: [f32; 3] = [0.00001; 3];
 
 impl BakedBlockModel {
     pub fn is_opaque(&self) -> bool {
-        self.elements.iter().any(|BlockModelElement { cube, .. }| {
-            (cube.size.to_raw() - Vec3::ONE).abs().to_array() < ERROR
+        !self.transparent
+            && self.elements.iter().any(|BlockModelElement { cube, .. }| {
+                (cube.size.to_raw() - Vec3::ONE).abs().to_array() < ERROR
+            })
+    }
+
+    /// Checks whether this model, placed next to the `cull_face` side of
+    /// another block, hides `model_face` of that block.
+    pub fn culls(&self, cull_face: Face, model_face: &BlockModelFace) -> bool {
+        if self.transparent {
+            return false;
+        }
+
+        if self.is_opaque() {
+            return true;
+        }
+
+        let opposite_face = cull_face.opposite();
+
+        self.elements.iter().any(|element| {
+            element.faces[opposite_face.normal_index()]
+                .as_ref()
+                .is_some_and(|face| face.is_opaque || face.uv.eq(&model_face.uv))
         })
     }
 }
 
 #[derive(Debug, Default)]
 pub struct BakedBlockModelLoader {
+    manifest: BlockManifest,
+    /// Indices of loaded models by block ids declared in the manifest.
+    ids: HashMap<usize, usize>,
     models: Vec<BakedBlockModel>,
 }
 
@@ -81,6 +147,9 @@ pub enum ModelLoadingError {
     InvalidPath,
     NotFound,
     ParsingFailed(JsonError),
+    Invalid(Vec<ModelProblem>),
+    /// Model isn't declared in the block manifest, so it has no id.
+    NotInManifest,
 }
 
 impl BakedBlockModelLoader {
@@ -90,18 +159,61 @@ impl BakedBlockModelLoader {
     }
 
     pub fn get(&self, value: usize) -> Option<&BakedBlockModel> {
-        self.models.get(value)
+        self.ids
+            .get(&value)
+            .and_then(|&index| self.models.get(index))
+    }
+
+    /// Creates loader with the given manifest instead of loading it from
+    /// resources.
+    pub fn with_manifest(manifest: BlockManifest) -> Self {
+        Self {
+            manifest,
+            ..Self::default()
+        }
+    }
+
+    pub const fn manifest(&self) -> &BlockManifest {
+        &self.manifest
+    }
+
+    /// Loads `blocks.json` manifest declaring ids of blocks, which is required
+    /// before loading any models.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if the manifest cannot be found in resource
+    /// roots or cannot be successfully parsed.
+    pub fn load_manifest(&mut self, resources: &ResourceRoots) -> LoadingResult<()> {
+        let data = resources
+            .resolve("blocks.json")
+            .and_then(|path| fs::read(path).ok())
+            .ok_or(LoadingError::Model(ModelLoadingError::NotFound))?;
+
+        self.manifest = BlockManifest::from_slice(&data)
+            .map_err(|err| LoadingError::Model(ModelLoadingError::ParsingFailed(err)))?;
+
+        Ok(())
+    }
+
+    /// Adds already baked model of the block with the given id, which allows
+    /// using models built without any textures (e.g. on a server).
+    pub fn insert(&mut self, id: u8, model: BakedBlockModel) -> &BakedBlockModel {
+        self.ids.insert(usize::from(id), self.models.len());
+        self.models.push(model);
+
+        self.models.last().unwrap()
     }
 
     /// # Errors
     ///
-    /// An error will be returned if the passed path does not contain a filename
-    /// or an error occurred while loading the block model (see
-    /// [`BlockManager::load`]).
-    pub fn load<P: AsRef<Path>, R: AsRef<Path>>(
+    /// An error will be returned if the passed path does not contain a
+    /// filename, the model is not declared in the manifest or an error
+    /// occurred while loading the block model (see [`BlockManager::load`]).
+    pub fn load<P: AsRef<Path>>(
         &mut self,
         textures: &mut TextureLoader,
-        root: R,
+        resources: &ResourceRoots,
         path: P,
     ) -> LoadingResult<&BakedBlockModel> {
         let path = path.as_ref();
@@ -116,11 +228,23 @@ impl BakedBlockModelLoader {
             .file_stem()
             .ok_or(LoadingError::Model(ModelLoadingError::InvalidPath))?
             .to_string_lossy();
+        let id = self
+            .manifest
+            .id(&name)
+            .ok_or(LoadingError::Model(ModelLoadingError::NotInManifest))?;
 
-        let block = BlockManager::load(textures, root.as_ref(), path)?;
+        let block = BlockManager::load(textures, resources, path)?;
 
         let mut bounding_box: Option<Cube3D> = None;
 
+        let connected = block.connected_texture.as_ref().map(|texture| {
+            let texture = get_texture(&block.textures, texture).unwrap();
+            let name = texture.1.file_stem().unwrap().to_string_lossy();
+            let (offset, scale, _) = textures.get_texture(&name).unwrap();
+
+            FaceUV { offset, scale }.connected_variants()
+        });
+
         let elements = block
             .elements
             .into_iter()
@@ -142,18 +266,11 @@ impl BakedBlockModelLoader {
                     faces: match element.faces {
                         Faces::All(data) => Face::ALL.map(|face| {
                             let texture = get_texture(&block.textures, &data.texture).unwrap();
-                            let (offset, scale, alpha) = textures
-                                .get_texture(texture.1.file_stem().unwrap().to_string_lossy())
-                                .unwrap();
-
-                            let uv = if let Some([start, end]) = data.uv {
-                                FaceUV {
-                                    offset: offset + start,
-                                    scale: scale * end,
-                                }
-                            } else {
-                                FaceUV { offset, scale }
-                            };
+                            let name = texture.1.file_stem().unwrap().to_string_lossy();
+                            let (offset, scale, alpha) = textures.get_texture(&name).unwrap();
+                            let color = textures.get_color(&name).unwrap();
+
+                            let uv = FaceUV::new(offset, scale, data.uv);
 
                             Some(BlockModelFace {
                                 texture_id: 0,
@@ -161,7 +278,8 @@ impl BakedBlockModelLoader {
                                 cull_face: data.cull_face,
                                 uv,
                                 tint: data.tint,
-                                is_opaque: alpha == 255,
+                                is_opaque: !block.transparent && alpha == 255,
+                                color,
                             })
                         }),
                         Faces::Unique(face_map) => {
@@ -169,18 +287,11 @@ impl BakedBlockModelLoader {
 
                             for (face, data) in face_map {
                                 let texture = get_texture(&block.textures, &data.texture).unwrap();
-                                let (offset, scale, alpha) = textures
-                                    .get_texture(texture.1.file_stem().unwrap().to_string_lossy())
-                                    .unwrap();
-
-                                let uv = if let Some([start, end]) = data.uv {
-                                    FaceUV {
-                                        offset: offset + start,
-                                        scale: scale * end,
-                                    }
-                                } else {
-                                    FaceUV { offset, scale }
-                                };
+                                let name = texture.1.file_stem().unwrap().to_string_lossy();
+                                let (offset, scale, alpha) = textures.get_texture(&name).unwrap();
+                                let color = textures.get_color(&name).unwrap();
+
+                                let uv = FaceUV::new(offset, scale, data.uv);
 
                                 faces[face.normal_index()] = Some(BlockModelFace {
                                     texture_id: 0,
@@ -188,7 +299,8 @@ impl BakedBlockModelLoader {
                                     cull_face: data.cull_face,
                                     uv,
                                     tint: data.tint,
-                                    is_opaque: alpha == 255,
+                                    is_opaque: !block.transparent && alpha == 255,
+                                    color,
                                 });
                             }
 
@@ -199,13 +311,130 @@ impl BakedBlockModelLoader {
             })
             .collect();
 
-        self.models.push(BakedBlockModel {
+        Ok(self.insert(id, BakedBlockModel {
             name: name.to_string(),
             ambient_occlusion: block.ambient_occlusion,
+            transparent: block.transparent,
+            cull_same_block: block.cull_same_block,
+            connected,
             elements,
             bounding_box: bounding_box.unwrap_or(Cube3D::ONE),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec2;
+    use meralus_shared::{Color, Cube3D};
+    use meralus_world::{AIR_BLOCK, BlockManifest, BlockModel, Face, Faces};
+
+    use super::{
+        BakedBlockModel, BakedBlockModelLoader, BlockModelElement, BlockModelFace, FaceUV,
+    };
+
+    fn cube(name: &str, transparent: bool) -> BakedBlockModel {
+        BakedBlockModel {
+            name: String::from(name),
+            bounding_box: Cube3D::ONE,
+            ambient_occlusion: true,
+            transparent,
+            cull_same_block: false,
+            connected: None,
+            elements: vec![BlockModelElement {
+                cube: Cube3D::ONE,
+                rotation: None,
+                faces: Face::ALL.map(|face| {
+                    Some(BlockModelFace {
+                        texture_id: 0,
+                        face,
+                        cull_face: Some(face),
+                        tint: false,
+                        uv: FaceUV::new(Vec2::ZERO, Vec2::ONE, None),
+                        is_opaque: !transparent,
+                        color: Color::WHITE,
+                    })
+                }),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_baked_uv() {
+        let offset = Vec2::new(0.5, 0.25);
+        let scale = Vec2::splat(0.125);
+
+        assert_eq!(FaceUV::new(offset, scale, None), FaceUV { offset, scale });
+
+        for uv in [
+            "[0.0, 0.5, 1.0, 1.0]",
+            r#"{ "start": [0.0, 0.5], "size": [1.0, 0.5] }"#,
+        ] {
+            let data = format!(
+                r##"{{ "elements": [{{ "start": [0, 0, 0], "end": [1, 1, 1], "all": {{ "texture": "#all", "uv": {uv} }} }}] }}"##
+            );
+            let model = BlockModel::from_slice(data.as_bytes()).unwrap();
+            let Faces::All(face) = &model.elements[0].faces else {
+                unreachable!();
+            };
+
+            assert_eq!(FaceUV::new(offset, scale, face.uv), FaceUV {
+                offset: Vec2::new(0.5, 0.3125),
+                scale: Vec2::new(0.125, 0.0625),
+            });
+        }
+    }
+
+    #[test]
+    fn test_transparent_culling() {
+        let [opaque, transparent] = [cube("dirt", false), cube("leaves", true)];
+
+        for face in Face::ALL {
+            let model_face = transparent.elements[0].faces[face.normal_index()]
+                .as_ref()
+                .unwrap();
+
+            assert!(!transparent.culls(face, model_face));
+            assert!(opaque.culls(face, model_face));
+        }
+
+        assert!(!transparent.is_opaque());
+    }
+
+    #[test]
+    fn test_manifest_ids() {
+        let loaders = [
+            (
+                r#"{ "1": "dirt", "2": "grass_block" }"#,
+                &["dirt", "grass_block"][..],
+            ),
+            (
+                r#"{ "1": "dirt", "2": "grass_block", "3": "cobblestone" }"#,
+                &["cobblestone", "dirt", "grass_block"][..],
+            ),
+        ]
+        .map(|(manifest, names)| {
+            let mut loader = BakedBlockModelLoader::with_manifest(
+                BlockManifest::from_slice(manifest.as_bytes()).unwrap(),
+            );
+
+            for name in names {
+                let id = loader.manifest.id(name).unwrap();
+
+                loader.insert(id, cube(name, false));
+            }
+
+            loader
         });
 
-        Ok(self.models.last().unwrap())
+        for (id, name) in [(1, "dirt"), (2, "grass_block")] {
+            for loader in &loaders {
+                assert_eq!(loader.get(id).unwrap().name, name);
+            }
+        }
+
+        assert_eq!(loaders[1].get(3).unwrap().name, "cobblestone");
+        assert!(loaders[0].get(3).is_none());
+        assert!(loaders[1].get(AIR_BLOCK.into()).is_none());
     }
 }
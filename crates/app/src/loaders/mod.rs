@@ -1,11 +1,16 @@
 mod block;
 mod block_model;
 mod block_states;
+mod resources;
 mod texture;
 
 pub use self::{
     block::{Block, BlockManager},
-    block_model::{BakedBlockModel, BakedBlockModelLoader, ModelLoadingError},
+    block_model::{
+        BakedBlockModel, BakedBlockModelLoader, BlockModelElement, BlockModelFace, FaceUV,
+        ModelLoadingError,
+    },
+    resources::ResourceRoots,
     texture::{TextureAtlas, TextureLoader, TextureLoadingError},
 };
 
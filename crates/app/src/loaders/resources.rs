@@ -0,0 +1,94 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+/// Ordered list of directories resources are looked up in, where files in
+/// later roots override the ones in earlier roots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceRoots {
+    roots: Vec<PathBuf>,
+}
+
+impl ResourceRoots {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            roots: vec![root.into()],
+        }
+    }
+
+    /// Adds root with higher priority than all of the existing ones.
+    pub fn push(&mut self, root: impl Into<PathBuf>) {
+        self.roots.push(root.into());
+    }
+
+    /// Returns path of the file relative to resource roots in the root with
+    /// the highest priority containing it.
+    pub fn resolve<P: AsRef<Path>>(&self, path: P) -> Option<PathBuf> {
+        self.roots
+            .iter()
+            .rev()
+            .map(|root| root.join(path.as_ref()))
+            .find(|path| path.is_file())
+    }
+
+    /// Returns paths of files inside of the directory relative to resource
+    /// roots, sorted by name and merged across all roots.
+    pub fn list<P: AsRef<Path>>(&self, directory: P) -> Vec<PathBuf> {
+        let directory = directory.as_ref();
+
+        self.roots
+            .iter()
+            .filter_map(|root| root.join(directory).read_dir().ok())
+            .flat_map(|entries| entries.filter_map(Result::ok))
+            .filter(|entry| entry.metadata().is_ok_and(|metadata| metadata.is_file()))
+            .map(|entry| directory.join(entry.file_name()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::Path};
+
+    use super::ResourceRoots;
+
+    #[test]
+    fn test_overriding_roots() {
+        let directory = env::temp_dir().join(format!("meralus-resources-{}", std::process::id()));
+        let [base, overrides] = ["base", "overrides"].map(|name| directory.join(name));
+
+        for (root, files) in [
+            (&base, ["models/dirt.json", "models/grass.json"]),
+            (&overrides, ["models/grass.json", "models/stone.json"]),
+        ] {
+            for file in files {
+                fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+                fs::write(root.join(file), file).unwrap();
+            }
+        }
+
+        let mut roots = ResourceRoots::new(&base);
+
+        roots.push(&overrides);
+
+        assert_eq!(
+            roots.resolve("models/grass.json"),
+            Some(overrides.join("models/grass.json"))
+        );
+        assert_eq!(
+            roots.resolve("models/dirt.json"),
+            Some(base.join("models/dirt.json"))
+        );
+        assert_eq!(roots.resolve("models/sand.json"), None);
+        assert_eq!(roots.list("models"), [
+            Path::new("models/dirt.json"),
+            Path::new("models/grass.json"),
+            Path::new("models/stone.json"),
+        ]);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -1,4 +1,10 @@
-use std::{borrow::Borrow, collections::HashMap, hash::Hash, io, path::Path};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    hash::Hash,
+    io, mem,
+    path::{Path, PathBuf},
+};
 
 use glam::{UVec2, Vec2, uvec2};
 use glium::{
@@ -7,6 +13,7 @@ use glium::{
 };
 use image::RgbaImage;
 use meralus_engine::WindowDisplay;
+use meralus_shared::Color;
 use owo_colors::OwoColorize;
 
 use super::LoadingResult;
@@ -53,25 +60,112 @@ const fn pack_rgba((r, g, b, a): (u8, u8, u8, u8)) -> u32 {
     (a as u32) << 24 | (r as u32) << 16 | (g as u32) << 8 | b as u32
 }
 
-pub struct TextureAtlas<K: Hash + Eq> {
+/// CPU-side bookkeeping of a [`TextureAtlas`]: where every texture is placed
+/// and where the next one goes.
+struct AtlasLayout<K: Hash + Eq> {
     texture_map: HashMap<K, (Rect, u8)>,
     next_texture_offset: UVec2,
+    row_height: u32,
+    size: UVec2,
+}
+
+impl<K: Hash + Eq> AtlasLayout<K> {
+    fn new(size: UVec2) -> Self {
+        Self {
+            texture_map: HashMap::new(),
+            next_texture_offset: UVec2::ZERO,
+            row_height: 0,
+            size,
+        }
+    }
+
+    /// Places texture right after the previous one, moving to the next row
+    /// when it doesn't fit into the current one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there's no space left for the texture in the atlas.
+    fn allocate(&mut self, key: K, width: u32, height: u32, alpha: u8) -> Rect {
+        self.try_allocate(key, width, height, alpha)
+            .unwrap_or_else(|| {
+                panic!(
+                    "texture atlas is full: no space left for {width}x{height} texture in {}x{} \
+                     atlas",
+                    self.size.x, self.size.y
+                )
+            })
+    }
+
+    /// Same as [`AtlasLayout::allocate`], but returns `None` instead of
+    /// panicking when there's no space left, leaving the layout unchanged.
+    fn try_allocate(&mut self, key: K, width: u32, height: u32, alpha: u8) -> Option<Rect> {
+        let mut offset = self.next_texture_offset;
+        let mut row_height = self.row_height;
+
+        if offset.x + width > self.size.x {
+            offset = uvec2(0, offset.y + row_height);
+            row_height = 0;
+        }
+
+        if width > self.size.x || offset.y + height > self.size.y {
+            return None;
+        }
+
+        let rect = Rect {
+            left: offset.x,
+            bottom: offset.y,
+            width,
+            height,
+        };
+
+        self.texture_map.insert(key, (rect, alpha));
+
+        self.next_texture_offset = uvec2(rect.left + rect.width, rect.bottom);
+        self.row_height = row_height.max(height);
+
+        Some(rect)
+    }
+
+    fn remove<Q: ?Sized + Hash + Eq>(&mut self, key: &Q) -> Option<(Rect, u8)>
+    where
+        K: Borrow<Q>,
+    {
+        self.texture_map.remove(key)
+    }
+
+    /// Places all remaining textures again from the very beginning, returning
+    /// the old and the new rect of every texture.
+    fn repack(&mut self) -> Vec<(Rect, Rect)> {
+        let mut entries = self.texture_map.drain().collect::<Vec<_>>();
+
+        entries.sort_by_key(|(_, (rect, _))| (rect.bottom, rect.left));
+
+        self.next_texture_offset = UVec2::ZERO;
+        self.row_height = 0;
+
+        entries
+            .into_iter()
+            .map(|(key, (rect, alpha))| (rect, self.allocate(key, rect.width, rect.height, alpha)))
+            .collect()
+    }
+}
+
+pub struct TextureAtlas<K: Hash + Eq> {
+    layout: AtlasLayout<K>,
     atlas: Texture2d,
 }
 
 impl<K: Hash + Eq> TextureAtlas<K> {
     pub fn new(display: &WindowDisplay, size: u32) -> Self {
         Self {
-            texture_map: HashMap::new(),
-            next_texture_offset: UVec2::ZERO,
+            layout: AtlasLayout::new(UVec2::splat(size)),
             atlas: Texture2d::empty(display, size, size).expect("failed to create atlas"),
         }
     }
 
     pub fn with_mipmaps(display: &WindowDisplay, size: u32, mipmaps: u32) -> Self {
         Self {
-            texture_map: HashMap::new(),
-            next_texture_offset: UVec2::ZERO,
+            layout: AtlasLayout::new(UVec2::splat(size)),
             atlas: Texture2d::empty_with_mipmaps(
                 display,
                 MipmapsOption::EmptyMipmapsMax(mipmaps),
@@ -86,37 +180,40 @@ impl<K: Hash + Eq> TextureAtlas<K> {
         &self.atlas
     }
 
+    fn to_uv(&self, rect: Rect) -> (Vec2, Vec2) {
+        (
+            Vec2::new(
+                rect.left as f32 / self.atlas.width() as f32,
+                rect.bottom as f32 / self.atlas.height() as f32,
+            ),
+            Vec2::new(
+                rect.width as f32 / self.atlas.width() as f32,
+                rect.height as f32 / self.atlas.height() as f32,
+            ),
+        )
+    }
+
     pub fn get_rect<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<(Vec2, Vec2, u8)>
     where
         K: Borrow<Q>,
     {
-        self.texture_map.get(key).copied().map(
-            |(
-                Rect {
-                    left,
-                    bottom,
-                    width,
-                    height,
-                },
-                alpha,
-            )| {
-                (
-                    Vec2::new(
-                        left as f32 / self.atlas.width() as f32,
-                        bottom as f32 / self.atlas.height() as f32,
-                    ),
-                    Vec2::new(
-                        width as f32 / self.atlas.width() as f32,
-                        height as f32 / self.atlas.height() as f32,
-                    ),
-                    alpha,
-                )
-            },
-        )
+        self.layout
+            .texture_map
+            .get(key)
+            .copied()
+            .map(|(rect, alpha)| {
+                let (offset, size) = self.to_uv(rect);
+
+                (offset, size, alpha)
+            })
     }
 
     pub fn rects(&self) -> usize {
-        self.texture_map.len()
+        self.layout.texture_map.len()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.layout.texture_map.keys()
     }
 
     pub fn generate_mipmaps(&self, level: usize) {
@@ -189,7 +286,7 @@ impl<K: Hash + Eq> TextureAtlas<K> {
     where
         K: Borrow<Q>,
     {
-        self.texture_map.contains_key(key)
+        self.layout.texture_map.contains_key(key)
     }
 
     pub fn append(&mut self, key: K, image: RgbaImage) -> (Vec2, Vec2, u8) {
@@ -197,39 +294,114 @@ impl<K: Hash + Eq> TextureAtlas<K> {
             return rect;
         }
 
-        let dimensions = image.dimensions();
+        let alpha = min_alpha(&image);
+        let rect = self
+            .layout
+            .allocate(key, image.width(), image.height(), alpha);
 
-        let alpha = image.pixels().map(|pixel| pixel.0[3]).min().unwrap_or(0);
-        let image = RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions);
+        self.write(rect, &image);
 
-        let offset = Rect {
-            left: self.next_texture_offset.x,
-            bottom: self.next_texture_offset.y,
-            width: image.width,
-            height: image.height,
-        };
+        let (offset, size) = self.to_uv(rect);
 
-        self.atlas.write(offset, image);
-        self.texture_map.insert(key, (offset, alpha));
+        (offset, size, alpha)
+    }
 
-        self.next_texture_offset = uvec2(offset.left + offset.width, offset.bottom);
+    /// Same as [`TextureAtlas::append`], but returns `None` instead of
+    /// panicking when there's no space left for the texture.
+    pub fn try_append(&mut self, key: K, image: &RgbaImage) -> Option<(Vec2, Vec2, u8)> {
+        if let Some(rect) = self.get_rect(&key) {
+            return Some(rect);
+        }
 
-        (
-            Vec2::new(
-                offset.left as f32 / self.atlas.width() as f32,
-                offset.bottom as f32 / self.atlas.height() as f32,
-            ),
-            Vec2::new(
-                offset.width as f32 / self.atlas.width() as f32,
-                offset.height as f32 / self.atlas.height() as f32,
-            ),
-            alpha,
-        )
+        let alpha = min_alpha(image);
+        let rect = self
+            .layout
+            .try_allocate(key, image.width(), image.height(), alpha)?;
+
+        self.write(rect, image);
+
+        let (offset, size) = self.to_uv(rect);
+
+        Some((offset, size, alpha))
     }
+
+    fn write(&self, rect: Rect, image: &RgbaImage) {
+        self.atlas.write(
+            rect,
+            RawImage2d::from_raw_rgba_reversed(image.as_raw(), image.dimensions()),
+        );
+    }
+
+    /// Removes the texture from the atlas. The space it occupied is reclaimed
+    /// only after calling [`repack`].
+    ///
+    /// [`repack`]: TextureAtlas::repack
+    pub fn remove<Q: ?Sized + Hash + Eq>(&mut self, key: &Q) -> Option<(Vec2, Vec2, u8)>
+    where
+        K: Borrow<Q>,
+    {
+        self.layout.remove(key).map(|(rect, alpha)| {
+            let (offset, size) = self.to_uv(rect);
+
+            (offset, size, alpha)
+        })
+    }
+
+    /// Rebuilds the atlas from the remaining textures, closing gaps left by
+    /// removed ones. Moved textures are uploaded again from the given images,
+    /// so that the atlas doesn't have to be read back from the GPU.
+    /// Previously returned UVs become invalid, and mipmaps have to be
+    /// generated again.
+    pub fn repack(&mut self, images: &HashMap<K, RgbaImage>) {
+        let moved = self
+            .layout
+            .repack()
+            .into_iter()
+            .filter(|(from, to)| from != to)
+            .map(|(_, to)| to)
+            .collect::<Vec<_>>();
+
+        for (key, (rect, _)) in &self.layout.texture_map {
+            if moved.contains(rect)
+                && let Some(image) = images.get(key)
+            {
+                self.write(*rect, image);
+            }
+        }
+    }
+}
+
+/// Returns the lowest alpha of the image pixels, which is stored along with
+/// its rect in the atlas.
+fn min_alpha(image: &RgbaImage) -> u8 {
+    image.pixels().map(|pixel| pixel.0[3]).min().unwrap_or(0)
+}
+
+fn average_color(image: &RgbaImage) -> Color {
+    let (sum, count) = image.pixels().filter(|pixel| pixel.0[3] > 0).fold(
+        ([0u64; 3], 0u64),
+        |([r, g, b], count), pixel| {
+            let [pr, pg, pb, _] = pixel.0.map(u64::from);
+
+            ([r + pr, g + pg, b + pb], count + 1)
+        },
+    );
+
+    if count == 0 {
+        return Color::new(0, 0, 0, 0);
+    }
+
+    let [r, g, b] = sum.map(|channel| (channel / count) as u8);
+
+    Color::new(r, g, b, 255)
 }
 
 pub struct TextureLoader {
     atlas: TextureAtlas<String>,
+    colors: HashMap<String, Color>,
+    /// Paths of loaded textures in loading order, used to upload them again.
+    paths: Vec<PathBuf>,
+    mipmap_level: usize,
 }
 
 #[derive(Debug)]
@@ -244,13 +416,41 @@ impl TextureLoader {
     pub fn new(display: &WindowDisplay) -> Self {
         Self {
             atlas: TextureAtlas::with_mipmaps(display, Self::ATLAS_SIZE, 4),
+            colors: HashMap::new(),
+            paths: Vec::new(),
+            mipmap_level: 0,
+        }
+    }
+
+    /// Creates the atlas again and loads every texture into it in the same
+    /// order, so that texture UVs stay the same.
+    ///
+    /// # Errors
+    ///
+    /// An error will be returned if any of the textures cannot be read anymore.
+    pub fn reload(&mut self, display: &WindowDisplay) -> LoadingResult<()> {
+        self.atlas = TextureAtlas::with_mipmaps(display, Self::ATLAS_SIZE, 4);
+
+        for path in mem::take(&mut self.paths) {
+            self.load(path)?;
+        }
+
+        if self.mipmap_level > 0 {
+            self.atlas.generate_mipmaps(self.mipmap_level);
         }
+
+        Ok(())
     }
 
     pub fn get_texture<T: AsRef<str>>(&self, name: T) -> Option<(Vec2, Vec2, u8)> {
         self.atlas.get_rect(name.as_ref())
     }
 
+    /// Returns the average color of the non-transparent pixels of the texture.
+    pub fn get_color<T: AsRef<str>>(&self, name: T) -> Option<Color> {
+        self.colors.get(name.as_ref()).copied()
+    }
+
     pub const fn get_atlas(&self) -> &Texture2d {
         self.atlas.get_texture()
     }
@@ -260,6 +460,7 @@ impl TextureLoader {
     }
 
     pub fn generate_mipmaps(&mut self, level: usize) {
+        self.mipmap_level = level;
         self.atlas.generate_mipmaps(level);
     }
 
@@ -291,7 +492,9 @@ impl TextureLoader {
                 if let Ok(value) = value.decode() {
                     let image = value.to_rgba8();
 
+                    self.colors.insert(name.clone(), average_color(&image));
                     self.atlas.append(name, image);
+                    self.paths.push(path.to_path_buf());
                 }
             }
             Err(err) => return Err(LoadingError::Texture(TextureLoadingError::Io(err))),
@@ -300,3 +503,86 @@ impl TextureLoader {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::{UVec2, uvec2};
+    use glium::Rect;
+
+    use super::AtlasLayout;
+
+    #[test]
+    fn test_repack_reclaims_space() {
+        let mut layout = AtlasLayout::new(UVec2::splat(256));
+
+        layout.allocate("a", 16, 16, 255);
+        layout.allocate("b", 32, 16, 255);
+        layout.allocate("c", 16, 16, 255);
+
+        assert!(layout.remove("b").is_some());
+
+        let moves = layout.repack();
+
+        assert_eq!(moves.len(), 2);
+        assert_eq!(layout.texture_map["c"].0.left, 16);
+
+        assert_eq!(layout.allocate("d", 32, 16, 255), Rect {
+            left: 32,
+            bottom: 0,
+            width: 32,
+            height: 16,
+        });
+    }
+
+    #[test]
+    fn test_allocation_wraps_rows() {
+        let mut layout = AtlasLayout::new(uvec2(64, 64));
+        let rects = (0..12)
+            .map(|i| layout.allocate(i, 16 + (i % 2) * 8, 8 + (i % 3) * 4, 255))
+            .collect::<Vec<_>>();
+
+        assert!(rects.iter().filter(|rect| rect.left == 0).count() >= 3);
+
+        for (i, a) in rects.iter().enumerate() {
+            assert!(a.left + a.width <= 64 && a.bottom + a.height <= 64);
+
+            for b in &rects[i + 1..] {
+                assert!(
+                    a.left + a.width <= b.left
+                        || b.left + b.width <= a.left
+                        || a.bottom + a.height <= b.bottom
+                        || b.bottom + b.height <= a.bottom,
+                    "{a:?} overlaps {b:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_try_allocate_full_atlas() {
+        let mut layout = AtlasLayout::new(uvec2(32, 32));
+
+        for i in 0..4 {
+            assert!(layout.try_allocate(i, 16, 16, 255).is_some());
+        }
+
+        assert_eq!(layout.try_allocate(4, 16, 16, 255), None);
+        assert_eq!(layout.texture_map.len(), 4);
+
+        assert!(layout.remove(&0).is_some());
+
+        layout.repack();
+
+        assert!(layout.try_allocate(4, 16, 16, 255).is_some());
+    }
+
+    #[test]
+    #[should_panic = "texture atlas is full"]
+    fn test_full_atlas() {
+        let mut layout = AtlasLayout::new(uvec2(32, 32));
+
+        for i in 0..5 {
+            layout.allocate(i, 16, 16, 255);
+        }
+    }
+}
@@ -7,51 +7,57 @@
     clippy::missing_panics_doc
 )]
 
-mod aabb;
-mod blocks;
-mod camera;
-mod clock;
-mod game;
-mod keyboard;
-mod loaders;
-mod player;
-mod raycast;
-mod renderers;
-mod transform;
 mod ui;
-mod util;
 
-use std::{f32, fs, net::SocketAddrV4, ops::Not, time::Duration};
+use std::{
+    collections::HashSet,
+    f32, fs, io,
+    net::SocketAddrV4,
+    ops::{Not, Range},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    thread::JoinHandle,
+    time::Duration,
+};
 
-use blocks::{AirBlock, DirtBlock, GrassBlock};
-use camera::Camera;
 use clap::Parser;
-use clock::Clock;
-use glam::{IVec2, Mat4, Quat, UVec2, Vec2, Vec3, vec3};
+use glam::{IVec2, Mat4, Quat, UVec2, Vec2, Vec3, vec2, vec3};
 use glamour::{FromRaw, ToRaw};
-use glium::{
-    Blend, BlendingFunction, LinearBlendingFactor, Rect, Surface, pixel_buffer::PixelBuffer,
-};
-use keyboard::KeyboardController;
+use glium::{Rect, Surface, pixel_buffer::PixelBuffer};
 use meralus_animation::{Animation, AnimationPlayer, Curve, RepeatMode};
+use meralus_app::{
+    Aabb, Block, CameraExt, Game, PlayerController, ResourceRoots,
+    blocks::{DirtBlock, GlassBlock, GrassBlock, SandBlock, WaterBlock},
+    camera::Camera,
+    clock::Clock,
+    entity::Entity,
+    game,
+    keyboard::KeyboardController,
+    mining::BreakProgress,
+    multiplayer,
+    nameplate::{Nameplate, NameplateOptions},
+    particles::ParticleSystem,
+    quality::QualityController,
+    renderers::{
+        DebugDraw, FONT, FONT_BOLD, ParticleRenderer, ShapeRenderer, TextEffect, TextRenderer,
+        VoxelRenderer,
+    },
+    save::{self, PlayerState},
+    schematic::Schematic,
+    simulation::{MAX_REGION_VOLUME, WorldGenerator},
+    util::{self, BufferExt},
+};
 use meralus_engine::{
-    Application, CursorGrabMode, KeyCode, MouseButton, State, WindowContext, WindowDisplay,
+    Application, CursorGrabMode, InputRecording, KeyCode, Listener, MouseButton, SoundRegistry,
+    State, WindowContext, WindowDisplay,
+};
+use meralus_shared::{Client, Color, Cube3D, Point2D, Point3D, Random, Rect2D, Size2D, Size3D};
+use meralus_world::{
+    AIR_BLOCK, CHUNK_SIZE, CHUNK_SIZE_F32, ChunkCache, ChunkManager, Inventory, coords,
 };
-use meralus_shared::{Color, Cube3D, Lerp, Point2D, Point3D, Rect2D, Size2D, Size3D};
-use meralus_world::{CHUNK_HEIGHT_F32, CHUNK_SIZE_F32, CHUNK_SIZE_U16, Chunk, ChunkManager};
 use owo_colors::OwoColorize;
-use renderers::{FONT, FONT_BOLD, Line, ShapeRenderer, TextRenderer, VoxelRenderer};
+use tokio::{runtime::Handle, signal, task};
 use ui::UiContext;
-use util::{BufferExt, cube_outline};
-
-pub use self::{
-    aabb::Aabb,
-    game::Game,
-    loaders::{BakedBlockModelLoader, Block, BlockManager, TextureLoader},
-    player::PlayerController,
-    transform::Transform,
-    util::{AsColor, CameraExt, get_movement_direction, get_rotation_directions, vertex_ao},
-};
 
 pub const TICK_RATE: Duration = Duration::from_millis(50);
 pub const FIXED_FRAMERATE: Duration = Duration::from_secs(1)
@@ -59,27 +65,6 @@ pub const FIXED_FRAMERATE: Duration = Duration::from_secs(1)
     .expect("failed to calculate fixed framerate somehow");
 const TEXT_COLOR: Color = Color::from_hsl(120.0, 0.5, 0.4);
 const BG_COLOR: Color = Color::from_hsl(120.0, 0.4, 0.75);
-const DAY_COLOR: Color = Color::from_hsl(220.0, 0.5, 0.75);
-const NIGHT_COLOR: Color = Color::from_hsl(220.0, 0.35, 0.25);
-const BLENDING: Blend = Blend {
-    color: BlendingFunction::Addition {
-        source: LinearBlendingFactor::SourceAlpha,
-        destination: LinearBlendingFactor::OneMinusSourceAlpha,
-    },
-    alpha: BlendingFunction::Addition {
-        source: LinearBlendingFactor::One,
-        destination: LinearBlendingFactor::OneMinusSourceAlpha,
-    },
-    constant_value: (0.0, 0.0, 0.0, 0.0),
-};
-
-fn get_sky_color((after_day, progress): (bool, f32)) -> Color {
-    if after_day {
-        DAY_COLOR.lerp(&NIGHT_COLOR, progress)
-    } else {
-        NIGHT_COLOR.lerp(&DAY_COLOR, progress)
-    }
-}
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -88,6 +73,85 @@ struct Args {
     host: Option<SocketAddrV4>,
     #[arg(short, long, group = "net")]
     nickname: Option<String>,
+    /// Directories overriding files of the base resources, in order of
+    /// increasing priority.
+    #[arg(long = "resource-pack")]
+    resource_packs: Vec<PathBuf>,
+    /// File to record input into, which can be replayed later.
+    #[arg(long, conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// File with recorded input to replay instead of handling live one.
+    #[arg(long)]
+    replay: Option<PathBuf>,
+    /// Seed the world is generated with.
+    #[arg(long, default_value_t = WORLD_SEED)]
+    seed: u32,
+    /// Amount of chunks generated in every direction from the world center.
+    #[arg(
+        long,
+        default_value_t = 3,
+        value_parser = clap::value_parser!(u16).range(1..=MAX_WORLD_RADIUS),
+    )]
+    radius: u16,
+    /// Generates flat terrain instead of hills.
+    #[arg(long)]
+    flat: bool,
+    /// Seconds between saves of changed chunks and the player.
+    #[arg(
+        long,
+        default_value_t = 300,
+        value_parser = clap::value_parser!(u64).range(1..),
+    )]
+    autosave_interval: u64,
+    /// Frame rate the render distance is lowered to keep up with.
+    #[arg(
+        long,
+        default_value_t = 60,
+        value_parser = clap::value_parser!(u16).range(1..),
+    )]
+    target_fps: u16,
+    /// Largest amount of chunks kept loaded, above which least recently seen
+    /// ones are saved and dropped. Raised to fit every chunk within the render
+    /// distance.
+    #[arg(
+        long,
+        default_value_t = 4096,
+        value_parser = clap::value_parser!(u16).range(1..),
+    )]
+    chunk_budget: u16,
+}
+
+impl Args {
+    const fn world_generator(&self) -> WorldGenerator {
+        if self.flat {
+            WorldGenerator::Flat
+        } else {
+            WorldGenerator::Surface { seed: self.seed }
+        }
+    }
+
+    /// Returns range of chunk coordinates generated along both horizontal
+    /// axes.
+    fn world_range(&self) -> Range<i32> {
+        let radius = i32::from(self.radius);
+
+        -radius..radius
+    }
+
+    /// Returns render distance at which the whole world is rendered from any
+    /// of its chunks.
+    const fn max_render_distance(&self) -> u16 {
+        self.radius * 2
+    }
+
+    /// Returns the chunk budget, raised to fit every chunk within the largest
+    /// render distance, so that chunks in view aren't dropped and loaded back
+    /// over and over.
+    fn chunk_budget(&self) -> usize {
+        let side = usize::from(self.max_render_distance()) * 2 + 1;
+
+        usize::from(self.chunk_budget).max(side * side)
+    }
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -97,13 +161,14 @@ struct Debugging {
     wireframe: bool,
     draw_borders: bool,
     inventory_open: bool,
-    chunk_borders: Vec<Line>,
     vertices: usize,
     draw_calls: usize,
 }
 
 enum Action {
     UpdateChunkMesh(IVec2),
+    PlaySound(&'static str),
+    PlaySoundAt(&'static str, Vec3),
 }
 
 struct GameLoop {
@@ -112,17 +177,44 @@ struct GameLoop {
     camera: Camera,
     player: PlayerController,
     window_matrix: Mat4,
+    /// Scale of the UI, following the scale factor of the window.
+    ui_scale: f32,
     debugging: Debugging,
     player_controllable: bool,
     animation_player: AnimationPlayer,
+    particles: ParticleSystem,
+    particle_renderer: ParticleRenderer,
     text_renderer: TextRenderer,
     voxel_renderer: VoxelRenderer,
     shape_renderer: ShapeRenderer,
+    debug_draw: DebugDraw,
     ticks: usize,
     tick_sum: usize,
     accel: Duration,
     clock: Clock,
     action_queue: Vec<Action>,
+    /// Distance walked on the ground since the last footstep sound.
+    walked: f32,
+    /// Whether the left mouse button is held, breaking the targeted block.
+    breaking: bool,
+    break_progress: BreakProgress,
+    inventory: Inventory,
+    /// Source of randomness for gameplay, like chance-based block drops.
+    random: Random,
+    /// Server the game is connected to, if it was started with a host.
+    server: Option<(SocketAddrV4, Client)>,
+    autosave_interval: Duration,
+    /// Time passed since the last autosave was started.
+    since_autosave: Duration,
+    /// Autosave running in the background, returning the amount of saved
+    /// chunks.
+    autosave: Option<JoinHandle<io::Result<usize>>>,
+    /// Last two blocks marked as corners of the region to copy.
+    selection: [Option<Vec3>; 2],
+    /// Last copied region, which is pasted in front of the looked at block.
+    clipboard: Option<Schematic>,
+    quality: QualityController,
+    chunk_cache: ChunkCache,
     fixed_accel: Duration,
     tick_accel: Duration,
 
@@ -130,62 +222,223 @@ struct GameLoop {
 }
 
 const INVENTORY_HOTBAR_SLOTS: u8 = 10;
+/// Number of inventory slots, with the first ones making up the hotbar.
+const INVENTORY_SLOTS: usize = INVENTORY_HOTBAR_SLOTS as usize * 4;
+const WORLD_SEED: u32 = 12723;
+/// Largest world radius that can be passed from the command line, in chunks.
+const MAX_WORLD_RADIUS: i64 = 32;
+/// Render distance isn't lowered below this on slow hardware, in chunks.
+const MIN_RENDER_DISTANCE: u16 = 2;
+/// Positions entities are dropped from when the world is created.
+const ENTITY_SPAWNS: [Vec3; 3] = [
+    vec3(6.0, 270.0, 6.0),
+    vec3(-8.0, 270.0, 4.0),
+    vec3(3.0, 270.0, -10.0),
+];
+const ENTITY_SIZE: Vec3 = vec3(0.9, 0.9, 0.9);
+const ENTITY_COLOR: Color = Color::from_hsl(30.0, 0.5, 0.6);
+/// Radius around the player entities are counted in by the debug overlay.
+const NEARBY_ENTITY_RADIUS: f32 = 32.0;
+const PARTICLE_LIMIT: usize = 1024;
+const NAMEPLATE_HEIGHT: f32 = 2.0;
+const SELECTION_WIDTH: f32 = 2.0;
+const CROSSHAIR_SIZE: f32 = 8.0;
+/// Gap between the crosshair and the break progress bar below it.
+const BREAK_PROGRESS_OFFSET: f32 = 6.0;
+/// Distance walked on the ground between footstep sounds.
+const FOOTSTEP_DISTANCE: f32 = 1.8;
+const BREAK_SOUND: &str = "block_break";
+const PLACE_SOUND: &str = "block_place";
+const FOOTSTEP_SOUND: &str = "footstep";
+/// Directory the world is saved to.
+const WORLD_DIRECTORY: &str = "world";
+/// File the copied region is saved to, so that it can be pasted in another
+/// session.
+const CLIPBOARD_FILE: &str = "clipboard.json";
+
+/// Set when the process is interrupted, so that the game can exit as if the
+/// window was closed and save the world.
+static EXIT_REQUESTED: AtomicBool = AtomicBool::new(false);
 
 impl GameLoop {
     fn destroy_looking_at(&mut self) {
-        if let Some(looking_at) = self.player.looking_at {
-            let local = self
+        if let Some(looking_at) = self.player.looking_at
+            && self
                 .game
                 .chunk_manager()
-                .to_chunk_local(looking_at.position);
+                .to_chunk_local(looking_at.position)
+                .is_some()
+        {
+            if let Some(color) = self.game.get_block_color(looking_at.position) {
+                self.particles.emit_burst(looking_at.position, color);
+            }
 
-            if let Some(local) = local {
-                self.game
-                    .chunk_manager_mut()
-                    .set_block(looking_at.position, 0);
+            self.game
+                .collect_drops(looking_at.position, &mut self.inventory, &mut self.random);
 
-                if local.y >= 255 {
-                    self.game
-                        .chunk_manager_mut()
-                        .set_sky_light(looking_at.position, 15);
-                }
+            let changed = self.game.edit_block(looking_at.position, AIR_BLOCK);
 
-                self.game.update_block_sky_light(looking_at.position);
+            self.action_queue
+                .extend(changed.into_iter().map(Action::UpdateChunkMesh));
+            self.action_queue.push(Action::PlaySoundAt(
+                BREAK_SOUND,
+                looking_at.position + Vec3::splat(0.5),
+            ));
+            self.player.update_looking_at(&self.game);
+        }
+    }
 
-                let chunk = ChunkManager::to_local(looking_at.position);
+    /// Returns the block of the item in the selected hotbar slot.
+    fn selected_block(&self) -> Option<u8> {
+        self.inventory.slots()[usize::from(self.inventory_slot)].map(|stack| stack.item)
+    }
 
-                if local.x == 0 {
-                    let chunk = chunk - IVec2::X;
+    /// Places the selected block in front of the looked at face of the block,
+    /// unless the cell is taken by a block, the player or an entity.
+    fn place_looking_at(&mut self) {
+        if let Some(looking_at) = self.player.looking_at
+            && let Some(selected) = self.selected_block()
+        {
+            let position = looking_at.position + looking_at.hit_side.as_normal().as_vec3();
 
-                    if self.game.chunk_manager().contains_chunk(&chunk) {
-                        self.action_queue.push(Action::UpdateChunkMesh(chunk));
-                    }
-                } else if local.x == (CHUNK_SIZE_U16 - 1) {
-                    let chunk = chunk + IVec2::X;
+            if self.game.chunk_manager().to_chunk_local(position).is_none()
+                || self.game.chunk_manager().contains_block(position)
+            {
+                return;
+            }
 
-                    if self.game.chunk_manager().contains_chunk(&chunk) {
-                        self.action_queue.push(Action::UpdateChunkMesh(chunk));
-                    }
-                }
+            let block = Aabb::new(position.as_dvec3(), (position + Vec3::ONE).as_dvec3());
 
-                if local.z == 0 {
-                    let chunk = chunk - IVec2::Y;
+            // Blocks can't be placed inside of the player or entities.
+            if self.player.collider().intersects(block)
+                || self.game.entities_in(block).next().is_some()
+            {
+                return;
+            }
 
-                    if self.game.chunk_manager().contains_chunk(&chunk) {
-                        self.action_queue.push(Action::UpdateChunkMesh(chunk));
-                    }
-                } else if local.z == (CHUNK_SIZE_U16 - 1) {
-                    let chunk = chunk + IVec2::Y;
+            let changed = self.game.edit_block(position, selected);
 
-                    if self.game.chunk_manager().contains_chunk(&chunk) {
-                        self.action_queue.push(Action::UpdateChunkMesh(chunk));
-                    }
-                }
+            self.action_queue
+                .extend(changed.into_iter().map(Action::UpdateChunkMesh));
+            self.action_queue.push(Action::PlaySoundAt(
+                PLACE_SOUND,
+                position + Vec3::splat(0.5),
+            ));
+            self.player.update_looking_at(&self.game);
+        }
+    }
 
-                self.action_queue.push(Action::UpdateChunkMesh(chunk));
-                self.player.update_looking_at(&self.game);
+    /// Places a block light source in front of the looked at face of the
+    /// block, or removes the one that is already there.
+    fn toggle_light_looking_at(&mut self) {
+        if let Some(looking_at) = self.player.looking_at {
+            let position = looking_at.position + looking_at.hit_side.as_normal().as_vec3();
+            let changed = if self.game.chunk_manager().get_block_light(position) == 15 {
+                self.game.remove_block_light(position)
+            } else {
+                self.game.set_block_light(position, 15)
+            };
+
+            self.action_queue
+                .extend(changed.into_iter().map(Action::UpdateChunkMesh));
+        }
+    }
+
+    /// Marks the looked at block as a corner of the region to copy, replacing
+    /// the older one of two corners.
+    fn mark_selection_corner(&mut self) {
+        if let Some(looking_at) = self.player.looking_at {
+            self.selection = [self.selection[1], Some(looking_at.position)];
+        }
+    }
+
+    /// Copies blocks between the marked corners and saves them, so that they
+    /// can be pasted in later sessions.
+    fn copy_selection(&mut self) {
+        let [Some(first), Some(second)] = self.selection else {
+            return;
+        };
+
+        let schematic = self.game.copy_region(first, second);
+        let size = schematic.size();
+        let saved = fs::create_dir_all(WORLD_DIRECTORY)
+            .and_then(|()| schematic.save(&Path::new(WORLD_DIRECTORY).join(CLIPBOARD_FILE)));
+
+        match saved {
+            Ok(()) => println!(
+                "[{:18}] Copied {} region",
+                "INFO/Schematic".bright_green(),
+                format!("{}x{}x{}", size.x, size.y, size.z).bright_blue()
+            ),
+            Err(error) => println!(
+                "[{:18}] Failed to save copied region: {error}",
+                " ERR/Schematic".bright_red(),
+            ),
+        }
+
+        self.clipboard = Some(schematic);
+    }
+
+    /// Pastes the copied region in front of the looked at face of the block,
+    /// loading it from the disk if nothing was copied in this session.
+    fn paste_clipboard(&mut self) {
+        let Some(looking_at) = self.player.looking_at else {
+            return;
+        };
+
+        if self.clipboard.is_none() {
+            match Schematic::load(&Path::new(WORLD_DIRECTORY).join(CLIPBOARD_FILE)) {
+                Ok(schematic) => self.clipboard = Some(schematic),
+                Err(error) => {
+                    println!(
+                        "[{:18}] Failed to load copied region: {error}",
+                        " ERR/Schematic".bright_red(),
+                    );
+
+                    return;
+                }
             }
         }
+
+        if let Some(schematic) = &self.clipboard {
+            let position = looking_at.position + looking_at.hit_side.as_normal().as_vec3();
+            let changed = self.game.paste(schematic, position);
+
+            self.action_queue
+                .extend(changed.into_iter().map(Action::UpdateChunkMesh));
+            self.player.update_looking_at(&self.game);
+        }
+    }
+
+    /// Fills the marked region with the selected block, or replaces blocks of
+    /// the looked at kind in it when `replace` is set.
+    fn fill_selection(&mut self, replace: bool) {
+        let ([Some(first), Some(second)], Some(block)) = (self.selection, self.selected_block())
+        else {
+            return;
+        };
+
+        let looked_at = self
+            .player
+            .looking_at
+            .and_then(|looking_at| self.game.chunk_manager().get_block(looking_at.position));
+        let changed = match (replace, looked_at) {
+            (false, _) => self.game.fill_region(first, second, block),
+            (true, Some(from)) => self.game.replace_region(first, second, from, block),
+            (true, None) => return,
+        };
+
+        if let Some(changed) = changed {
+            self.action_queue
+                .extend(changed.into_iter().map(Action::UpdateChunkMesh));
+            self.player.update_looking_at(&self.game);
+        } else {
+            println!(
+                "[{:18}] Region is larger than {} blocks",
+                " ERR/WorldEdit".bright_red(),
+                MAX_REGION_VOLUME.bright_blue()
+            );
+        }
     }
 
     fn tick(&mut self) {
@@ -193,17 +446,127 @@ impl GameLoop {
 
         self.clock.tick();
 
-        let progress = self.clock.get_progress();
+        let changed = self.game.update_blocks();
 
-        self.voxel_renderer.set_sun_position(if progress > 0.5 {
-            1.0 - progress
-        } else {
-            progress
-        });
+        self.action_queue
+            .extend(changed.into_iter().map(Action::UpdateChunkMesh));
+        self.voxel_renderer
+            .set_day_factor(self.clock.get_day_factor() * self.game.weather().light_factor());
+
+        self.since_autosave += TICK_RATE;
+
+        if self.since_autosave >= self.autosave_interval && self.autosave.is_none() {
+            self.since_autosave = Duration::ZERO;
+            self.autosave = Some(save::autosave(
+                PathBuf::from(WORLD_DIRECTORY),
+                self.game.chunk_manager_mut(),
+                self.player_state(),
+            ));
+        }
+
+        if self.autosave.as_ref().is_some_and(JoinHandle::is_finished) {
+            self.finish_autosave();
+        }
+
+        // Evicted chunks would race with files written by the autosave.
+        if self.autosave.is_none() {
+            self.evict_chunks();
+            self.reload_chunks();
+        }
+    }
+
+    /// Drops chunks over the budget along with their meshes.
+    fn evict_chunks(&mut self) {
+        match save::evict_chunks(
+            Path::new(WORLD_DIRECTORY),
+            &mut self.chunk_cache,
+            self.game.chunk_manager_mut(),
+        ) {
+            Ok(evicted) => {
+                for origin in evicted {
+                    self.voxel_renderer.remove_chunk(origin);
+                }
+            }
+            Err(error) => println!(
+                "[{:18}] Failed to save evicted chunks: {error}",
+                " ERR/Saving".bright_red(),
+            ),
+        }
+    }
+
+    /// Loads evicted chunks back once they are within render distance again,
+    /// remeshing their neighbours, whose faces on the seams were uncovered.
+    fn reload_chunks(&mut self) {
+        match save::reload_chunks(
+            Path::new(WORLD_DIRECTORY),
+            &mut self.chunk_cache,
+            self.game.chunk_manager_mut(),
+            ChunkManager::to_local(self.player.position),
+            i32::from(self.quality.render_distance()),
+        ) {
+            Ok(reloaded) => {
+                let changed = reloaded
+                    .into_iter()
+                    .flat_map(|origin| {
+                        [IVec2::ZERO, IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+                            .map(|offset| origin + offset)
+                    })
+                    .collect::<HashSet<_>>();
+
+                self.action_queue
+                    .extend(changed.into_iter().map(Action::UpdateChunkMesh));
+            }
+            Err(error) => println!(
+                "[{:18}] Failed to load evicted chunks: {error}",
+                " ERR/Saving".bright_red(),
+            ),
+        }
+    }
+
+    const fn player_state(&self) -> PlayerState {
+        PlayerState {
+            position: self.player.position,
+            yaw: self.player.yaw,
+            pitch: self.player.pitch,
+        }
+    }
+
+    /// Waits for the running autosave to finish, reporting its result.
+    fn finish_autosave(&mut self) {
+        let Some(autosave) = self.autosave.take() else {
+            return;
+        };
+
+        match autosave.join() {
+            Ok(Ok(chunks)) => println!(
+                "[{:18}] Autosaved {} chunks",
+                "INFO/Saving".bright_green(),
+                chunks.bright_blue().bold()
+            ),
+            Ok(Err(error)) => println!(
+                "[{:18}] Failed to autosave the world: {error}",
+                " ERR/Saving".bright_red(),
+            ),
+            Err(_) => println!(
+                "[{:18}] Autosave thread panicked",
+                " ERR/Saving".bright_red(),
+            ),
+        }
     }
 
     fn fixed_update(&mut self) {
+        self.game.update_weather(
+            FIXED_FRAMERATE.as_secs_f32(),
+            &mut self.particles,
+            self.player.position,
+        );
+        self.particles.update(FIXED_FRAMERATE.as_secs_f32());
+        self.game
+            .update_entities(&mut self.random, FIXED_FRAMERATE.as_secs_f32());
+
         if self.player_controllable {
+            let previous = self.player.position;
+
             self.player.handle_physics(
                 &self.game,
                 &self.keyboard,
@@ -211,6 +574,36 @@ impl GameLoop {
                 FIXED_FRAMERATE.as_secs_f32(),
             );
 
+            if self.player.is_on_ground {
+                let moved = self.player.position - previous;
+
+                self.walked += vec2(moved.x, moved.z).length();
+
+                if self.walked >= FOOTSTEP_DISTANCE {
+                    self.walked = 0.0;
+                    self.action_queue.push(Action::PlaySound(FOOTSTEP_SOUND));
+                }
+            }
+
+            if self.breaking
+                && let Some(looking_at) = self.player.looking_at
+            {
+                let break_time = self
+                    .game
+                    .get_block_at(looking_at.position)
+                    .map_or(0.0, Block::break_time);
+
+                if self.break_progress.advance(
+                    looking_at.position,
+                    break_time,
+                    FIXED_FRAMERATE.as_secs_f32(),
+                ) {
+                    self.destroy_looking_at();
+                }
+            } else {
+                self.break_progress.reset();
+            }
+
             self.camera.position = self.player.position;
             self.camera.up = self.player.up;
             self.camera.target = self.player.position + self.player.front;
@@ -222,28 +615,152 @@ impl GameLoop {
 
 const SLOT_SIZE: f32 = 48.0f32;
 
+fn create_text_renderer(display: &WindowDisplay) -> TextRenderer {
+    let mut text_renderer = TextRenderer::new(display, 4096 / 2).unwrap();
+
+    text_renderer.add_font(display, "default", FONT);
+    text_renderer.add_font(display, "default_bold", FONT_BOLD);
+
+    text_renderer
+}
+
+/// Joins the server passed from the command line, adding players on it to the
+/// game. Returns `None` when playing alone or if the server can't be joined.
+fn join_server(args: &Args, game: &mut Game) -> Option<(SocketAddrV4, Client)> {
+    let (host, nickname) = args.host.zip(args.nickname.clone())?;
+    // The game is created from the event loop, which runs inside of the async
+    // runtime, so the connection is awaited in place.
+    let result = task::block_in_place(|| {
+        Handle::current().block_on(multiplayer::join(host, nickname.clone()))
+    });
+
+    match result {
+        Ok((client, players)) => {
+            println!(
+                "[{:18}] Joined {} as {}, {} players online",
+                "INFO/Network".bright_green(),
+                host.bright_blue().bold(),
+                nickname.bright_blue().bold(),
+                players.len().bright_blue().bold()
+            );
+
+            for player in players {
+                game.add_player(game::Player {
+                    is_me: player.nickname == nickname,
+                    nickname: player.nickname,
+                    position: player.position,
+                });
+            }
+
+            Some((host, client))
+        }
+        Err(error) => {
+            println!(
+                "[{:18}] Failed to join {host}, playing alone: {error}",
+                " ERR/Network".bright_red(),
+            );
+
+            None
+        }
+    }
+}
+
+fn load_sounds(resources: &ResourceRoots) -> SoundRegistry {
+    let mut sounds = SoundRegistry::new();
+
+    for path in resources.list("sounds") {
+        if let Some(path) = resources.resolve(path)
+            && let Err(error) = sounds.load_file(&path)
+        {
+            println!(
+                "[{:18}] Failed to load sound {}: {error}",
+                " ERR/Audio".bright_red(),
+                path.display()
+            );
+        }
+    }
+
+    println!(
+        "[{:18}] Loaded {} sounds",
+        "INFO/Audio".bright_green(),
+        sounds.len().bright_blue().bold()
+    );
+
+    sounds
+}
+
 impl State for GameLoop {
     fn new(context: WindowContext, display: &WindowDisplay) -> Self {
         context.set_cursor_grab(CursorGrabMode::Confined);
         context.set_cursor_visible(false);
 
-        let mut game = Game::new(display, "./resources", -3..3, -3..3);
+        let args = Args::parse();
+        let mut resources = ResourceRoots::new("./resources");
+
+        for root in &args.resource_packs {
+            resources.push(root);
+        }
+
+        context.set_sounds(load_sounds(&resources));
+
+        let mut game = match Game::new(display, resources, args.world_range(), args.world_range()) {
+            Ok(game) => game,
+            Err(error) => {
+                println!(
+                    "[{:18}] Failed to load the block manifest: {error:?}",
+                    " ERR/Loading".bright_red(),
+                );
+
+                process::exit(1);
+            }
+        };
 
-        game.register_block(AirBlock);
         game.register_block(DirtBlock);
         game.register_block(GrassBlock);
+        game.register_block(WaterBlock);
+        game.register_block(SandBlock);
+        game.register_block(GlassBlock);
 
         game.generate_mipmaps(4);
 
-        game.generate_world(12723);
-        game.generate_lights();
-        game.set_block_light(vec3(-13.0, 217.0, 0.0), 15);
+        let directory = Path::new(WORLD_DIRECTORY);
 
-        println!(
-            "[{:18}] Generated {} chunks",
-            "INFO/WorldGen".bright_green(),
-            game.chunk_manager().len().bright_blue().bold(),
-        );
+        match save::load_chunks(directory) {
+            Ok(Some(chunk_manager)) => {
+                *game.chunk_manager_mut() = chunk_manager;
+
+                println!(
+                    "[{:18}] Loaded {} chunks from {}",
+                    "INFO/Saving".bright_green(),
+                    game.chunk_manager().len().bright_blue().bold(),
+                    WORLD_DIRECTORY.bright_blue().bold()
+                );
+            }
+            result => {
+                if let Err(error) = result {
+                    println!(
+                        "[{:18}] Failed to load the world, generating a new one: {error}",
+                        " ERR/Saving".bright_red(),
+                    );
+                }
+
+                game.generate_world(args.world_generator());
+                game.generate_lights();
+                game.set_block_light(vec3(-13.0, 217.0, 0.0), 15);
+
+                println!(
+                    "[{:18}] Generated {} chunks",
+                    "INFO/WorldGen".bright_green(),
+                    game.chunk_manager().len().bright_blue().bold(),
+                );
+            }
+        }
+
+        for position in ENTITY_SPAWNS {
+            game.spawn_entity(Entity::new(position, ENTITY_SIZE));
+        }
+
+        let server = join_server(&args, &mut game);
 
         let world_mesh = game.compute_world_mesh();
 
@@ -253,15 +770,22 @@ impl State for GameLoop {
             (world_mesh.len() * 6).bright_blue().bold()
         );
 
-        let player = PlayerController {
+        let mut player = PlayerController {
             position: vec3(2.0, 275.0, 2.0),
             ..Default::default()
         };
 
-        let mut text_renderer = TextRenderer::new(display, 4096 / 2).unwrap();
-
-        text_renderer.add_font(display, "default", FONT);
-        text_renderer.add_font(display, "default_bold", FONT_BOLD);
+        match save::load_player(directory) {
+            Ok(Some(state)) => {
+                player.position = state.position;
+                player.set_rotation(state.yaw, state.pitch);
+            }
+            Ok(None) => {}
+            Err(error) => println!(
+                "[{:18}] Failed to load the player: {error}",
+                " ERR/Saving".bright_red(),
+            ),
+        }
 
         let mut animation_player = AnimationPlayer::default();
 
@@ -290,32 +814,25 @@ impl State for GameLoop {
             Animation::new(0.0, 1.0, 400, Curve::EASE_IN_OUT_EXPO, RepeatMode::Once),
         );
 
+        animation_player.add_group("inventory", ["scale", "opacity", "scale-vertical"]);
+
         Self {
             keyboard: KeyboardController::default(),
             animation_player,
-            text_renderer,
+            particles: ParticleSystem::new(PARTICLE_LIMIT),
+            particle_renderer: ParticleRenderer::new(display, PARTICLE_LIMIT).unwrap(),
+            text_renderer: create_text_renderer(display),
             voxel_renderer: VoxelRenderer::new(display, world_mesh),
-            shape_renderer: ShapeRenderer::new(display),
+            shape_renderer: ShapeRenderer::new(display).unwrap(),
+            debug_draw: DebugDraw::new(),
             window_matrix: Mat4::IDENTITY,
+            ui_scale: 1.0,
             debugging: Debugging {
                 night: false,
                 overlay: false,
                 wireframe: false,
                 draw_borders: false,
                 inventory_open: false,
-                chunk_borders: game.chunk_manager().chunks().fold(
-                    Vec::new(),
-                    |mut lines, Chunk { origin, .. }| {
-                        let origin = origin.as_vec2() * CHUNK_SIZE_F32;
-
-                        lines.extend(cube_outline(Cube3D::new(
-                            Point3D::new(origin.x, 0.0, origin.y),
-                            Size3D::new(CHUNK_SIZE_F32, CHUNK_HEIGHT_F32, CHUNK_SIZE_F32),
-                        )));
-
-                        lines
-                    },
-                ),
                 vertices: 0,
                 draw_calls: 0,
             },
@@ -335,6 +852,23 @@ impl State for GameLoop {
             player_controllable: true,
             clock: Clock::default(),
             action_queue: Vec::new(),
+            walked: 0.0,
+            breaking: false,
+            break_progress: BreakProgress::default(),
+            inventory: Inventory::new(INVENTORY_SLOTS),
+            random: Random::new(u64::from(args.seed)),
+            server,
+            autosave_interval: Duration::from_secs(args.autosave_interval),
+            since_autosave: Duration::ZERO,
+            autosave: None,
+            selection: [None; 2],
+            clipboard: None,
+            quality: QualityController::new(
+                args.target_fps,
+                MIN_RENDER_DISTANCE,
+                args.max_render_distance(),
+            ),
+            chunk_cache: ChunkCache::new(args.chunk_budget()),
             inventory_slot: 0,
         }
     }
@@ -342,14 +876,8 @@ impl State for GameLoop {
     fn handle_window_resize(&mut self, size: UVec2, scale_factor: f64) {
         let size = size.as_vec2();
 
-        self.window_matrix = Mat4::orthographic_rh_gl(
-            0.,
-            size.x / scale_factor as f32,
-            size.y / scale_factor as f32,
-            0.,
-            -1.,
-            1.,
-        );
+        self.window_matrix = Mat4::orthographic_rh_gl(0., size.x, size.y, 0., -1., 1.);
+        self.ui_scale = scale_factor as f32;
 
         self.camera.aspect_ratio = size.x / size.y;
     }
@@ -358,9 +886,59 @@ impl State for GameLoop {
         self.keyboard.handle_keyboard_input(key, is_pressed, repeat);
     }
 
+    fn on_context_lost(&mut self) {
+        println!(
+            "[{:18}] OpenGL context lost, recreating it",
+            "WARN/Rendering".bright_yellow(),
+        );
+    }
+
+    fn on_context_restored(&mut self, display: &WindowDisplay) {
+        self.game.reload_textures(display);
+        self.voxel_renderer.restore(display);
+        self.shape_renderer = ShapeRenderer::new(display).unwrap();
+        self.particle_renderer = ParticleRenderer::new(display, PARTICLE_LIMIT).unwrap();
+        self.text_renderer = create_text_renderer(display);
+
+        println!(
+            "[{:18}] OpenGL context restored",
+            "INFO/Rendering".bright_green(),
+        );
+    }
+
+    fn on_exit(&mut self) {
+        // The final save would race with files written by the autosave.
+        self.finish_autosave();
+
+        match save::save_world(
+            Path::new(WORLD_DIRECTORY),
+            self.game.chunk_manager(),
+            self.player_state(),
+        ) {
+            Ok(()) => println!(
+                "[{:18}] Saved {} chunks into {}",
+                "INFO/Saving".bright_green(),
+                self.game.chunk_manager().len().bright_blue().bold(),
+                WORLD_DIRECTORY.bright_blue().bold()
+            ),
+            Err(error) => println!(
+                "[{:18}] Failed to save the world: {error}",
+                " ERR/Saving".bright_red(),
+            ),
+        }
+    }
+
     fn handle_mouse_button(&mut self, button: MouseButton, is_pressed: bool) {
-        if button == MouseButton::Left && is_pressed {
-            self.destroy_looking_at();
+        if button == MouseButton::Left {
+            self.breaking = is_pressed;
+
+            if !is_pressed {
+                self.break_progress.reset();
+            }
+        } else if button == MouseButton::Right && is_pressed {
+            self.place_looking_at();
+        } else if button == MouseButton::Middle && is_pressed {
+            self.toggle_light_looking_at();
         }
     }
 
@@ -405,6 +983,8 @@ impl State for GameLoop {
         }
 
         if self.accel >= Duration::from_secs(1) {
+            self.text_renderer.reset_used_glyphs();
+
             self.ticks = self.tick_sum;
             self.accel = Duration::ZERO;
             self.tick_sum = 0;
@@ -422,17 +1002,56 @@ impl State for GameLoop {
             }
         }
 
-        if self.keyboard.is_key_pressed_once(KeyCode::Escape) {
+        if self.keyboard.is_key_pressed_once(KeyCode::Escape)
+            || EXIT_REQUESTED.load(Ordering::Relaxed)
+        {
             context.close_window();
         }
 
         self.animation_player.advance(delta.as_secs_f32());
+        self.quality.update(delta);
 
         if self.keyboard.is_key_pressed_once(KeyCode::KeyR) {
             self.animation_player.enable();
             self.animation_player.play("loading-screen");
         }
 
+        if self.keyboard.is_key_pressed(KeyCode::ControlLeft) {
+            let changed = if self.keyboard.is_key_pressed_once(KeyCode::KeyZ) {
+                self.game.undo()
+            } else if self.keyboard.is_key_pressed_once(KeyCode::KeyY) {
+                self.game.redo()
+            } else {
+                None
+            };
+
+            if let Some(changed) = changed {
+                self.action_queue
+                    .extend(changed.into_iter().map(Action::UpdateChunkMesh));
+                self.player.update_looking_at(&self.game);
+            }
+        }
+
+        if self.keyboard.is_key_pressed_once(KeyCode::KeyM) {
+            self.mark_selection_corner();
+        }
+
+        if self.keyboard.is_key_pressed_once(KeyCode::KeyJ) {
+            self.copy_selection();
+        }
+
+        if self.keyboard.is_key_pressed_once(KeyCode::KeyP) {
+            self.paste_clipboard();
+        }
+
+        if self.keyboard.is_key_pressed_once(KeyCode::KeyF) {
+            self.fill_selection(false);
+        }
+
+        if self.keyboard.is_key_pressed_once(KeyCode::KeyH) {
+            self.fill_selection(true);
+        }
+
         if self.keyboard.is_key_pressed_once(KeyCode::KeyT) {
             self.debugging.wireframe = !self.debugging.wireframe;
         }
@@ -472,16 +1091,17 @@ impl State for GameLoop {
                 opacity.to(0.0);
             }
 
-            self.animation_player.play("scale");
-            self.animation_player.play("opacity");
-            self.animation_player.play("scale-vertical");
+            self.animation_player.play_group("inventory");
         }
 
         if self.keyboard.is_key_pressed_once(KeyCode::KeyN) {
             self.debugging.night = !self.debugging.night;
 
-            self.voxel_renderer
-                .set_sun_position(if self.debugging.night { -0.5 } else { 0.5 });
+            self.voxel_renderer.set_day_factor(if self.debugging.night {
+                Clock::MIN_DAY_FACTOR
+            } else {
+                1.0
+            });
         }
 
         if self.keyboard.is_key_pressed_once(KeyCode::KeyO) {
@@ -502,6 +1122,12 @@ impl State for GameLoop {
             self.animation_player.play("overlay-width");
         }
 
+        context.set_listener(Listener {
+            position: self.camera.position,
+            front: self.camera.target - self.camera.position,
+            up: self.camera.up,
+        });
+
         while let Some(action) = self.action_queue.pop() {
             match action {
                 Action::UpdateChunkMesh(origin) => {
@@ -509,13 +1135,73 @@ impl State for GameLoop {
                         self.voxel_renderer.set_chunk(display, origin, chunk);
                     }
                 }
+                Action::PlaySound(id) => {
+                    context.play_sound(id);
+                }
+                Action::PlaySoundAt(id, position) => {
+                    context.play_sound_at(id, position);
+                }
             }
         }
 
+        if self.keyboard.is_key_pressed_once(KeyCode::KeyG) {
+            let weather = self.game.weather().current().next();
+
+            println!(
+                "[{:18}] Changing weather to {}",
+                "INFO/Weather".bright_green(),
+                format!("{weather:?}").bright_blue()
+            );
+
+            self.game.set_weather(weather);
+        }
+
         if self.keyboard.is_key_pressed_once(KeyCode::KeyB) {
             self.debugging.draw_borders = !self.debugging.draw_borders;
         }
 
+        if self.keyboard.is_key_pressed_once(KeyCode::KeyC) {
+            let origin = ChunkManager::to_local(self.player.position);
+
+            if let Some(chunk) = self.game.chunk_manager().get_chunk(&origin) {
+                let path = format!("debug/chunk_{}_{}.bin", origin.x, origin.y);
+
+                println!(
+                    "[{:18}] Chunk {} {}: {}",
+                    "INFO/ChunkManager".bright_green(),
+                    origin.x.bright_blue(),
+                    origin.y.bright_blue(),
+                    chunk.summary()
+                );
+
+                if fs::exists("debug").is_ok_and(Not::not)
+                    && let Err(error) = fs::create_dir("debug")
+                {
+                    println!(
+                        "[{:18}] Failed to create debug directory: {error}",
+                        " ERR/ChunkManager".bright_red(),
+                    );
+                } else if let Err(error) = fs::write(&path, chunk.serialize()) {
+                    println!(
+                        "[{:18}] Failed to save chunk: {error}",
+                        " ERR/ChunkManager".bright_red(),
+                    );
+                } else {
+                    let raw_len = usize::from(chunk.height()) * CHUNK_SIZE * CHUNK_SIZE * 2;
+                    let serialized_len = chunk.serialized_len();
+
+                    println!(
+                        "[{:18}] Successfully saved chunk to {} ({} bytes, {:.1}x smaller than \
+                         raw blocks)",
+                        "INFO/ChunkManager".bright_green(),
+                        path.bright_blue(),
+                        serialized_len.bright_blue(),
+                        raw_len as f32 / serialized_len as f32
+                    );
+                }
+            }
+        }
+
         if self.keyboard.is_key_pressed_once(KeyCode::KeyL) {
             let atlas = self.game.get_texture_atlas();
 
@@ -593,10 +1279,15 @@ impl State for GameLoop {
         let (width, height) = display.get_framebuffer_dimensions();
         let mut frame = display.draw();
 
-        let [r, g, b] = get_sky_color(self.clock.get_visual_progress()).to_linear();
+        let sky_color = self.game.sky().sample(self.clock.get_progress());
+        let [r, g, b] = self.game.weather().tint_sky(sky_color).to_linear();
 
         frame.clear_color_and_depth((r, g, b, 1.0), 1.0);
 
+        self.voxel_renderer.set_render_distance(
+            ChunkManager::to_local(self.player.position),
+            self.quality.render_distance(),
+        );
         self.voxel_renderer.render(
             &mut frame,
             &self.player.frustum,
@@ -605,6 +1296,12 @@ impl State for GameLoop {
             self.debugging.wireframe,
         );
 
+        self.chunk_cache.advance();
+
+        for origin in self.voxel_renderer.visible_chunks() {
+            self.chunk_cache.touch(origin);
+        }
+
         {
             let (draw_calls, vertices) = self.voxel_renderer.get_debug_info();
 
@@ -612,36 +1309,102 @@ impl State for GameLoop {
             self.debugging.vertices += vertices;
         }
 
+        self.particle_renderer.render(
+            &mut frame,
+            &self.camera,
+            self.particles.particles(),
+            &mut self.debugging.draw_calls,
+        );
+
         if self.debugging.draw_borders {
-            self.shape_renderer.set_matrix(self.camera.matrix());
-            self.shape_renderer.draw_lines(
-                &mut frame,
-                display,
-                &self.debugging.chunk_borders,
-                &mut self.debugging.draw_calls,
-                &mut self.debugging.vertices,
+            for chunk in self.game.chunk_manager().chunks() {
+                let origin = coords::chunk_to_world(chunk.origin).as_vec3();
+
+                self.debug_draw.cube(
+                    Cube3D::new(
+                        Point3D::from_raw(origin),
+                        Size3D::new(CHUNK_SIZE_F32, f32::from(chunk.height()), CHUNK_SIZE_F32),
+                    ),
+                    Color::BLUE,
+                );
+            }
+
+            let origin =
+                coords::chunk_to_world(ChunkManager::to_local(self.player.position)).as_vec3();
+
+            for (axis, color) in [
+                (Vec3::X, Color::RED),
+                (Vec3::Y, Color::GREEN),
+                (Vec3::Z, Color::BLUE),
+            ] {
+                self.debug_draw
+                    .line(origin, origin + axis * CHUNK_SIZE_F32, color);
+            }
+
+            let center = vec2(width as f32, height as f32) / 2.0;
+
+            self.debug_draw.rect(
+                center - vec2(CROSSHAIR_SIZE, 1.0),
+                vec2(CROSSHAIR_SIZE * 2.0, 2.0),
+                Color::WHITE,
+            );
+            self.debug_draw.rect(
+                center - vec2(1.0, CROSSHAIR_SIZE),
+                vec2(2.0, CROSSHAIR_SIZE * 2.0),
+                Color::WHITE,
+            );
+
+            let progress = self.break_progress.progress();
+
+            if progress > 0.0 {
+                self.debug_draw.rect(
+                    center + vec2(-CROSSHAIR_SIZE, CROSSHAIR_SIZE + BREAK_PROGRESS_OFFSET),
+                    vec2(CROSSHAIR_SIZE * 2.0 * progress, 2.0),
+                    Color::WHITE,
+                );
+            }
+        }
+
+        for entity in self.game.entities() {
+            let origin = entity.position - vec3(entity.size.x, 0.0, entity.size.z) / 2.0;
+
+            self.debug_draw.thick_cube(
+                Cube3D::new(
+                    Point3D::from_raw(origin),
+                    Size3D::new(entity.size.x, entity.size.y, entity.size.z),
+                ),
+                ENTITY_COLOR,
+                SELECTION_WIDTH,
             );
-            self.shape_renderer.set_default_matrix();
         }
 
         if let Some(result) = self.player.looking_at
             && let Some(model) = self.game.get_model_for(result.position)
         {
-            self.shape_renderer.set_matrix(self.camera.matrix());
-            self.shape_renderer.draw_lines(
-                &mut frame,
-                display,
-                &cube_outline(model.bounding_box + Point3D::from_raw(result.position)),
-                &mut self.debugging.draw_calls,
-                &mut self.debugging.vertices,
+            self.debug_draw.thick_cube(
+                model.bounding_box + Point3D::from_raw(result.position),
+                Color::WHITE,
+                SELECTION_WIDTH,
             );
-            self.shape_renderer.set_default_matrix();
+        }
+
+        self.debug_draw
+            .flush(&mut frame, display, &mut self.shape_renderer, &self.camera);
+
+        {
+            let (draw_calls, vertices) = self.debug_draw.get_debug_info();
+
+            self.debugging.draw_calls += draw_calls;
+            self.debugging.vertices += vertices;
         }
 
         let animation_progress: f32 = self.animation_player.get_value("loading-screen").unwrap();
 
+        let ui_scale = self.ui_scale;
         let mut context = UiContext::new(self, display, &mut frame);
 
+        context.set_ui_scale(ui_scale);
+
         context.ui(|context, bounds| {
             let hotbar_width = f32::from(INVENTORY_HOTBAR_SLOTS) * SLOT_SIZE;
 
@@ -669,6 +1432,82 @@ impl State for GameLoop {
                 Size2D::new(SLOT_SIZE - 8.0, SLOT_SIZE - 8.0),
                 Color::from_hsl(0.0, 0.0, 0.5),
             );
+
+            let stacks = context.game_loop.inventory.slots()[..usize::from(INVENTORY_HOTBAR_SLOTS)]
+                .iter()
+                .enumerate()
+                .filter_map(|(index, stack)| stack.map(|stack| (index, stack)))
+                .collect::<Vec<_>>();
+
+            for (index, stack) in stacks {
+                let slot = origin + Point2D::new(index as f32 * SLOT_SIZE, 0.0).into();
+
+                if let Some(color) = context.game_loop.game.get_item_color(stack.item) {
+                    context.draw_rect(
+                        slot + Point2D::new(12.0, 12.0).into(),
+                        Size2D::new(SLOT_SIZE - 24.0, SLOT_SIZE - 24.0),
+                        color,
+                    );
+                }
+
+                context.draw_text(
+                    slot + Point2D::new(6.0, SLOT_SIZE - 20.0).into(),
+                    "default",
+                    stack.count.to_string(),
+                    14.0,
+                    Color::WHITE,
+                    None,
+                );
+            }
+        });
+
+        context.ui(|context, bounds| {
+            let camera = context.game_loop.camera;
+            let game = &context.game_loop.game;
+
+            let nameplates = game
+                .players()
+                .iter()
+                .filter(|player| !player.is_me)
+                .filter_map(|player| {
+                    let position = player.position + Vec3::Y * NAMEPLATE_HEIGHT;
+                    let (screen_position, _) = camera.unproject_position(
+                        bounds.size.width,
+                        bounds.size.height,
+                        position,
+                    )?;
+                    let nameplate = Nameplate::compute(
+                        camera.position,
+                        position,
+                        NameplateOptions::default(),
+                        |from, to| game.is_occluded(from, to),
+                    );
+
+                    nameplate
+                        .is_visible()
+                        .then(|| (player.nickname.clone(), screen_position, nameplate))
+                })
+                .collect::<Vec<_>>();
+
+            for (nickname, screen_position, nameplate) in nameplates {
+                let size = 18.0 * nameplate.scale;
+                let measured = context.measure_text("default", &nickname, size).unwrap();
+
+                context.draw_text(
+                    Point2D::new(
+                        screen_position.x - measured.width / 2.0,
+                        screen_position.y - measured.height,
+                    ),
+                    "default",
+                    nickname,
+                    size,
+                    Color::WHITE.with_alpha(nameplate.alpha),
+                    Some(TextEffect::Shadow {
+                        offset: Vec2::ONE,
+                        color: Color::BLACK.with_alpha(0.5 * nameplate.alpha),
+                    }),
+                );
+            }
         });
 
         context.ui(|context, bounds| {
@@ -708,7 +1547,7 @@ impl State for GameLoop {
                 context.padding(2.0, |context, bounds| {
                     context.clipped(bounds, |context, bounds| {
                         let measured = context
-                            .measure_text("default_bold", "Inventory", 18.0)
+                            .measure_text_detailed("default_bold", "Inventory", 18.0)
                             .unwrap();
 
                         context.draw_text(
@@ -717,6 +1556,10 @@ impl State for GameLoop {
                             "Inventory",
                             18.0,
                             Color::WHITE,
+                            Some(TextEffect::Shadow {
+                                offset: Vec2::splat(2.0),
+                                color: Color::BLACK.with_alpha(0.5 * opacity),
+                            }),
                         );
 
                         let size = bounds.size - Size2D::new(0.0, measured.height + 4.0);
@@ -777,6 +1620,34 @@ impl State for GameLoop {
             let version = display.get_opengl_version();
             let rendered_chunks = context.game_loop.voxel_renderer.rendered_chunks();
             let total_chunks = context.game_loop.voxel_renderer.total_chunks();
+            let render_distance = context.game_loop.quality.render_distance();
+            let blocks_in_reach = context
+                .game_loop
+                .player
+                .blocks_in_reach(&context.game_loop.game)
+                .len();
+            let particles = context.game_loop.particles.len();
+            let server = context
+                .game_loop
+                .server
+                .as_ref()
+                .map_or_else(|| String::from("none"), |(host, _)| host.to_string());
+            let entities = context.game_loop.game.entities().len();
+            let nearby_entities = context
+                .game_loop
+                .game
+                .entities_in_radius(context.game_loop.player.position, NEARBY_ENTITY_RADIUS)
+                .count();
+            let (sun_light, block_light) = context
+                .game_loop
+                .game
+                .chunk_manager()
+                .get_light_split(context.game_loop.player.position);
+            let light = VoxelRenderer::effective_light(
+                sun_light,
+                block_light,
+                context.game_loop.clock.get_day_factor(),
+            );
 
             let text = format!(
                 "OpenGL {}.{}
@@ -784,13 +1655,19 @@ Free GPU memory: {}
 Window size: {width}x{height}
 Player position: {:.2}
 Chunk: {} {}
+Light: {sun_light} sky, {block_light} block ({light:.2})
 Game Time: {hours:02}:{minutes:02}
 FPS: {:.0} ({:.2}ms)
 TPS: {}
+Server: {server}
 Looking at {}
+Blocks in reach: {blocks_in_reach}
 Draw calls: {draw_calls}
 Rendered chunks: {rendered_chunks} / {total_chunks}
+Render distance: {render_distance} chunks
 Rendered vertices: {vertices}
+Particles: {particles}
+Entities: {nearby_entities} nearby / {entities}
 Animation player:",
                 version.1,
                 version.2,
@@ -837,7 +1714,7 @@ Animation player:",
 
                 context.padding(2.0, |context, bounds| {
                     context.clipped(bounds, |context, bounds| {
-                        context.draw_text(bounds.origin, "default", text, 18.0, Color::WHITE);
+                        context.draw_text(bounds.origin, "default", text, 18.0, Color::WHITE, None);
                     });
                 });
             });
@@ -845,7 +1722,7 @@ Animation player:",
             let mut offset = 0.0;
 
             for i in 0..context.game_loop.animation_player.len() {
-                let (finished, elapsed, duration, text) = {
+                let (finished, elapsed, duration, curve, text) = {
                     let (name, animation) = context.game_loop.animation_player.get_at(i).unwrap();
                     let elapsed = animation.get_elapsed();
                     let duration = animation.get_duration();
@@ -854,6 +1731,7 @@ Animation player:",
                         animation.is_finished(),
                         elapsed,
                         duration,
+                        animation.get_curve(),
                         format!(
                             "#{name}: {:.2}, {:.1}% ({:.2}ms/{:.2}ms)",
                             animation.get::<f32>(),
@@ -885,6 +1763,7 @@ Animation player:",
                                     text,
                                     18.0,
                                     Color::WHITE,
+                                    None,
                                 );
 
                                 context.draw_rect(
@@ -896,6 +1775,17 @@ Animation player:",
                                         Color::new(120, 167, 255, 255)
                                     },
                                 );
+
+                                context.draw_curve(
+                                    Point2D::new(
+                                        bounds.origin.x + bounds.size.width - text_size.height,
+                                        bounds.origin.y,
+                                    ),
+                                    Size2D::new(text_size.height, text_size.height),
+                                    curve,
+                                    1.0,
+                                    Color::WHITE,
+                                );
                             });
                         });
                     },
@@ -947,6 +1837,7 @@ Animation player:",
                 "Meralus",
                 64.0,
                 TEXT_COLOR.with_alpha(animation_progress),
+                None,
             );
         });
 
@@ -960,26 +1851,80 @@ Animation player:",
 
 #[tokio::main]
 async fn main() {
-    // let args = Args::parse();
+    let args = Args::parse();
+
+    tokio::spawn(async {
+        if signal::ctrl_c().await.is_ok() {
+            EXIT_REQUESTED.store(true, Ordering::Relaxed);
+        }
+    });
+
+    let mut application = if let Some(path) = &args.replay {
+        let data = fs::read(path).expect("failed to read input recording");
+
+        Application::<GameLoop>::replaying(
+            InputRecording::from_slice(&data).expect("failed to parse input recording"),
+        )
+    } else if args.record.is_some() {
+        Application::recording(FIXED_FRAMERATE)
+    } else {
+        Application::default()
+    };
+
+    application.start().expect("failed to run app");
+
+    if let (Some(path), Some(recording)) = (args.record, application.recorded_input()) {
+        let result = recording
+            .to_vec()
+            .map_err(io::Error::from)
+            .and_then(|data| fs::write(&path, data));
+
+        match result {
+            Ok(()) => println!(
+                "[{:18}] Recorded {} frames of input into {}",
+                "INFO/Recording".bright_green(),
+                recording.len().bright_blue().bold(),
+                path.display()
+            ),
+            Err(error) => println!(
+                "[{:18}] Failed to save input recording: {error}",
+                " ERR/Recording".bright_red(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+    use meralus_app::simulation::WorldGenerator;
 
-    // if let Some(host) = args.host {
-    //     let stream = TcpStream::connect(host).await.unwrap();
-    //     let (mut stream, mut sink) = wrap_stream(stream);
+    use super::{Args, WORLD_SEED};
 
-    //     sink.send(IncomingPacket::PlayerConnected {
-    //         name: args.nickname.unwrap(),
-    //     })
-    //     .await
-    //     .unwrap();
+    #[test]
+    fn test_world_args() {
+        let args = Args::try_parse_from(["meralus", "--seed", "42"]).unwrap();
 
-    //     sink.send(IncomingPacket::GetPlayers).await.unwrap();
+        assert_eq!(args.world_generator(), WorldGenerator::Surface { seed: 42 });
+        assert_eq!(args.world_range(), -3..3);
 
-    //     if let Some(Ok(OutgoingPacket::PlayersList { players })) =
-    // stream.next().await {         println!("{players:#?}");
-    //     }
-    // }
+        let args = Args::try_parse_from(["meralus", "--flat", "--radius", "5"]).unwrap();
 
-    Application::<GameLoop>::default()
-        .start()
-        .expect("failed to run app");
+        assert_eq!(args.world_generator(), WorldGenerator::Flat);
+        assert_eq!(args.world_range(), -5..5);
+        assert_eq!(args.max_render_distance(), 10);
+        assert_eq!(args.chunk_budget(), 4096);
+        assert_eq!(args.seed, WORLD_SEED);
+
+        assert!(Args::try_parse_from(["meralus", "--radius", "0"]).is_err());
+        assert!(Args::try_parse_from(["meralus", "--radius", "33"]).is_err());
+        assert!(Args::try_parse_from(["meralus", "--seed", "-1"]).is_err());
+        assert!(Args::try_parse_from(["meralus", "--target-fps", "0"]).is_err());
+
+        // a budget too small for the chunks in view is raised
+        let args =
+            Args::try_parse_from(["meralus", "--radius", "5", "--chunk-budget", "16"]).unwrap();
+
+        assert_eq!(args.chunk_budget(), 21 * 21);
+    }
 }
@@ -0,0 +1,599 @@
+use std::collections::HashMap;
+
+use glam::{IVec2, IVec3, Mat4, Vec2, Vec3, u16vec3};
+use meralus_shared::Color;
+use meralus_world::{Axis, CHUNK_SIZE_I32, CHUNK_SIZE_U16, Chunk, Face};
+use owo_colors::OwoColorize;
+
+use crate::{loaders::FaceUV, renderers::Voxel, simulation::Simulation, vertex_ao};
+
+/// Color faces of grass blocks are tinted with.
+pub const GRASS_COLOR: Color = Color::from_hsl(120.0, 0.4, 0.75);
+
+/// Everything faces have to share to be merged into a single quad.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct MergeKey {
+    face: Face,
+    /// Position of the plane faces lie in along the face normal.
+    depth: i32,
+    /// Faces aren't merged across subchunks, since they are culled separately.
+    subchunk: i32,
+    tile: [u32; 4],
+    is_opaque: bool,
+    sun_light: u8,
+    block_light: u8,
+    ao: u32,
+    color: Color,
+}
+
+impl MergeKey {
+    fn new(voxel: &Voxel, depth: i32) -> Self {
+        let FaceUV { offset, scale } = voxel.tile;
+
+        Self {
+            face: voxel.face,
+            depth,
+            subchunk: (voxel.position.y as i32).div_euclid(CHUNK_SIZE_I32),
+            tile: [offset.x, offset.y, scale.x, scale.y].map(f32::to_bits),
+            is_opaque: voxel.is_opaque,
+            sun_light: voxel.sun_light,
+            block_light: voxel.block_light,
+            ao: voxel.aos[0].to_bits(),
+            color: voxel.color,
+        }
+    }
+}
+
+/// Returns the normal axis of the face followed by two axes of its plane.
+const fn axes(face: Face) -> [usize; 3] {
+    match face {
+        Face::Left | Face::Right => [0, 1, 2],
+        Face::Bottom | Face::Top => [1, 2, 0],
+        Face::Back | Face::Front => [2, 0, 1],
+    }
+}
+
+/// Returns directions in which the x and y texture coordinates of the face
+/// grow.
+fn texture_axes(face: Face) -> [IVec3; 2] {
+    let (vertices, uvs) = (face.as_vertices(), face.as_uv());
+    let mut axes = [IVec3::ZERO; 2];
+
+    for corner in [1, 3] {
+        let edge = vertices[corner] - vertices[0];
+        let uv_edge = uvs[corner] - uvs[0];
+        // Each edge runs along one of the texture axes.
+        let texture_axis = usize::from(uv_edge.x == 0.0);
+
+        axes[texture_axis] = (edge * uv_edge[texture_axis].signum()).as_ivec3();
+    }
+
+    axes
+}
+
+/// Returns index of the connected texture variant for the face.
+///
+/// The index is a mask with a bit set for every side of the texture whose
+/// neighbour in the plane of the face is connected: `1` for `+x`, `2` for
+/// `-x`, `4` for `+y` and `8` for `-y`. So the variant `0` has borders on
+/// every side, while `15` has none.
+pub fn connected_variant(face: Face, is_connected: impl Fn(IVec3) -> bool) -> usize {
+    let [x, y] = texture_axes(face);
+
+    [x, -x, y, -y]
+        .into_iter()
+        .enumerate()
+        .filter(|&(_, offset)| is_connected(offset))
+        .fold(0, |variant, (bit, _)| variant | 1 << bit)
+}
+
+/// Only faces covering a whole block side with the same ambient occlusion in
+/// every corner can be merged, anything else would look different stretched.
+fn is_mergeable(voxel: &Voxel) -> bool {
+    voxel.vertices == voxel.face.as_vertices()
+        && voxel.uvs == voxel.face.as_uv()
+        && voxel
+            .aos
+            .iter()
+            .all(|ao| ao.to_bits() == voxel.aos[0].to_bits())
+}
+
+/// Stretches the face of a single block over `size` blocks of its plane. The
+/// texture coordinates are stretched as well, so the texture repeats once per
+/// block instead of being stretched.
+fn stretch(mut voxel: Voxel, size: IVec2) -> Voxel {
+    let [_, u, v] = axes(voxel.face);
+    let mut scale = Vec3::ONE;
+
+    scale[u] = size.x as f32;
+    scale[v] = size.y as f32;
+
+    // Adjacent edges of the quad map onto different texture axes.
+    let uv_scale = [1, 3].into_iter().fold(Vec2::ONE, |uv_scale, corner| {
+        let edge = (voxel.vertices[corner] - voxel.vertices[0]).abs();
+        let uv_edge = (voxel.uvs[corner] - voxel.uvs[0]).abs();
+
+        uv_scale + uv_edge * (edge.dot(scale) - 1.0)
+    });
+
+    voxel.vertices = voxel.vertices.map(|vertice| vertice * scale);
+    voxel.uvs = voxel.uvs.map(|uv| uv * uv_scale);
+
+    voxel
+}
+
+/// Greedily merges faces lying in the same plane into rectangles, first
+/// growing them along the first axis and then along the second one.
+fn merge_plane(mut cells: HashMap<IVec2, Voxel>, merged: &mut Vec<Voxel>) {
+    let mut positions = cells.keys().copied().collect::<Vec<_>>();
+
+    positions.sort_unstable_by_key(|position| (position.y, position.x));
+
+    for start in positions {
+        if !cells.contains_key(&start) {
+            continue;
+        }
+
+        let mut size = IVec2::ONE;
+
+        while cells.contains_key(&(start + IVec2::new(size.x, 0))) {
+            size.x += 1;
+        }
+
+        while (0..size.x).all(|x| cells.contains_key(&(start + IVec2::new(x, size.y)))) {
+            size.y += 1;
+        }
+
+        let voxel = cells.remove(&start).unwrap();
+
+        for y in 0..size.y {
+            for x in 0..size.x {
+                cells.remove(&(start + IVec2::new(x, y)));
+            }
+        }
+
+        merged.push(stretch(voxel, size));
+    }
+}
+
+/// Merges adjacent coplanar faces that look the same into larger quads,
+/// leaving faces which can't be merged as they are.
+pub fn merge_faces(voxels: Vec<Voxel>) -> Vec<Voxel> {
+    let mut merged = Vec::with_capacity(voxels.len());
+    let mut planes = HashMap::<MergeKey, HashMap<IVec2, Voxel>>::new();
+
+    for voxel in voxels {
+        if is_mergeable(&voxel) {
+            let [normal, u, v] = axes(voxel.face);
+            let position = voxel.position.as_ivec3();
+
+            planes
+                .entry(MergeKey::new(&voxel, position[normal]))
+                .or_default()
+                .insert(IVec2::new(position[u], position[v]), voxel);
+        } else {
+            merged.push(voxel);
+        }
+    }
+
+    for cells in planes.into_values() {
+        merge_plane(cells, &mut merged);
+    }
+
+    merged
+}
+
+// Meshing only reads blocks, light and models, so it doesn't need a display
+// and lives on the simulation rather than on the game.
+impl Simulation {
+    pub fn compute_chunk_mesh_at(&self, position: &IVec2) -> Option<[(Face, [Vec<Voxel>; 2]); 6]> {
+        self.chunk_manager()
+            .get_chunk(position)
+            .map(|chunk| self.compute_chunk_mesh(chunk))
+    }
+
+    /// Computes the chunk mesh, merging faces of neighbouring blocks into
+    /// larger quads where possible.
+    pub fn compute_chunk_mesh(&self, chunk: &Chunk) -> [(Face, [Vec<Voxel>; 2]); 6] {
+        self.compute_chunk_faces(chunk)
+            .map(|(face, voxels)| (face, voxels.map(merge_faces)))
+    }
+
+    /// Computes every visible block face of the chunk as a separate quad.
+    #[allow(clippy::too_many_lines)]
+    fn compute_chunk_faces(&self, chunk: &Chunk) -> [(Face, [Vec<Voxel>; 2]); 6] {
+        let mut voxels = Face::ALL.map(|face| (face, [const { Vec::new() }; 2]));
+
+        for y in 0..chunk.height() {
+            for z in 0..CHUNK_SIZE_U16 {
+                for x in 0..CHUNK_SIZE_U16 {
+                    let local_position = u16vec3(x, y, z);
+                    let world_position = chunk.to_world(local_position).as_vec3();
+
+                    if let Some(block) = chunk.get_block(local_position)
+                        && let Some(model) = self.models().get(block.into())
+                    {
+                        for element in &model.elements {
+                            let matrix = element.rotation.map(|rotation| {
+                                let angle = rotation.angle.to_radians();
+
+                                let matrix;
+                                let mut scale = Vec3::ZERO;
+
+                                match rotation.axis {
+                                    Axis::X => {
+                                        matrix = Mat4::from_rotation_x(angle);
+
+                                        scale.y = 1.0;
+                                        scale.z = 1.0;
+                                    }
+                                    Axis::Y => {
+                                        matrix = Mat4::from_rotation_y(angle);
+
+                                        scale.x = 1.0;
+                                        scale.z = 1.0;
+                                    }
+                                    Axis::Z => {
+                                        matrix = Mat4::from_rotation_z(angle);
+
+                                        scale.x = 1.0;
+                                        scale.y = 1.0;
+                                    }
+                                }
+
+                                scale = Vec3::ONE;
+
+                                (matrix, rotation.origin, scale)
+                            });
+
+                            for model_face in element.faces.iter().flatten() {
+                                let neighbour_position =
+                                    world_position + model_face.face.as_normal().as_vec3();
+
+                                let culled = model_face.cull_face.is_some_and(|cull_face| {
+                                    let neighbour = self.chunk_manager().get_block(
+                                        world_position + cull_face.as_normal().as_vec3(),
+                                    );
+
+                                    // Liquids like water don't show faces between
+                                    // blocks of the same kind.
+                                    neighbour.is_some_and(|neighbour| {
+                                        (model.cull_same_block && neighbour == block)
+                                            || self.models().get(neighbour.into()).is_some_and(
+                                                |neighbour| neighbour.culls(cull_face, model_face),
+                                            )
+                                    })
+                                });
+
+                                if !culled {
+                                    let mut vertices =
+                                        model_face.face.as_vertices().map(|vertice| {
+                                            Vec3::from_array(element.cube.origin.to_array())
+                                                + vertice
+                                                    * Vec3::from_array(element.cube.size.to_array())
+                                        });
+
+                                    let mut uvs = model_face.face.as_uv();
+
+                                    let mut aos =
+                                        model_face.face.as_vertice_corners().map(|corner| {
+                                            let [side1, side2, corner] = corner
+                                                .get_neighbours(model_face.face)
+                                                .map(|neighbour| {
+                                                    self.chunk_manager()
+                                                        .get_block(
+                                                            world_position + neighbour.as_vec3(),
+                                                        )
+                                                        .is_some_and(|block| {
+                                                            self.models()
+                                                                .get(block.into())
+                                                                .unwrap()
+                                                                .ambient_occlusion
+                                                        })
+                                                });
+
+                                            vertex_ao(side1, side2, corner)
+                                        });
+
+                                    // let mut aos_flipped = false;
+
+                                    if aos[1] + aos[2] > aos[0] + aos[3] {
+                                        // aos_flipped = true;
+
+                                        // aos = aos[1], aos[2], aos[3], aos[0]
+
+                                        vertices.swap(0, 1);
+                                        vertices.swap(1, 2);
+                                        vertices.swap(2, 3);
+
+                                        aos.swap(0, 1);
+                                        aos.swap(1, 2);
+                                        aos.swap(2, 3);
+
+                                        uvs.swap(0, 1);
+                                        uvs.swap(1, 2);
+                                        uvs.swap(2, 3);
+                                    }
+
+                                    let vertices =
+                                        matrix.map_or(vertices, |(matrix, origin, scale)| {
+                                            vertices.map(|vertice| {
+                                                matrix.transform_point3(vertice - origin) * scale
+                                                    + origin
+                                            })
+                                        });
+
+                                    let voxels = &mut voxels[model_face.face.normal_index()].1;
+                                    let voxels = if model_face.is_opaque {
+                                        &mut voxels[0]
+                                    } else {
+                                        &mut voxels[1]
+                                    };
+
+                                    let (sun_light, block_light) =
+                                        self.chunk_manager().get_light_split(neighbour_position);
+
+                                    let tile = model.connected.map_or(model_face.uv, |variants| {
+                                        variants[connected_variant(model_face.face, |offset| {
+                                            self.chunk_manager()
+                                                .get_block(world_position + offset.as_vec3())
+                                                == Some(block)
+                                        })]
+                                    });
+
+                                    voxels.push(Voxel {
+                                        position: world_position,
+                                        vertices,
+                                        face: model_face.face,
+                                        origin: chunk.origin,
+                                        aos,
+                                        sun_light,
+                                        block_light,
+                                        color: if model.name == "grass_block" && model_face.tint {
+                                            GRASS_COLOR
+                                        } else {
+                                            Color::WHITE
+                                        },
+                                        uvs,
+                                        tile,
+                                        is_opaque: model_face.is_opaque,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        voxels
+    }
+
+    #[must_use]
+    pub fn compute_world_mesh(&self) -> HashMap<(IVec2, Face), [Vec<Voxel>; 2]> {
+        let mut meshes = HashMap::new();
+        let mut faces = 0;
+        let mut quads = 0;
+
+        for chunk in self.chunk_manager().chunks() {
+            for (face, data) in self.compute_chunk_faces(chunk) {
+                let data = data.map(|voxels| {
+                    faces += voxels.len();
+
+                    let voxels = merge_faces(voxels);
+
+                    quads += voxels.len();
+
+                    voxels
+                });
+
+                meshes.insert((chunk.origin, face), data);
+            }
+
+            println!(
+                "[{:18}] Generated mesh for chunk at {}",
+                "INFO/Rendering".bright_green(),
+                format!("{:>2} {:>2}", chunk.origin.x, chunk.origin.y)
+                    .bright_blue()
+                    .bold()
+            );
+        }
+
+        // Every quad is made of two triangles
+        println!(
+            "[{:18}] Merging faces cut triangles from {} to {}",
+            "INFO/Rendering".bright_green(),
+            (faces * 2).bright_blue().bold(),
+            (quads * 2).bright_blue().bold()
+        );
+
+        meshes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{IVec2, IVec3, Vec2, Vec3};
+    use meralus_shared::{Color, Cube3D};
+    use meralus_world::{BlockManifest, Face};
+
+    use super::{connected_variant, merge_faces};
+    use crate::{
+        BakedBlockModelLoader,
+        loaders::{BakedBlockModel, BlockModelElement, BlockModelFace, FaceUV},
+        renderers::Voxel,
+        simulation::Simulation,
+    };
+
+    /// Returns see-through cube models of glass, which keeps faces between
+    /// neighbouring glass blocks, and water, which hides them.
+    fn transparent_models() -> BakedBlockModelLoader {
+        let mut models = BakedBlockModelLoader::with_manifest(
+            BlockManifest::from_slice(br#"{ "1": "glass", "2": "water" }"#).unwrap(),
+        );
+
+        for (id, name, cull_same_block) in [(1, "glass", false), (2, "water", true)] {
+            models.insert(id, BakedBlockModel {
+                name: String::from(name),
+                bounding_box: Cube3D::ONE,
+                ambient_occlusion: true,
+                transparent: true,
+                cull_same_block,
+                connected: None,
+                elements: vec![BlockModelElement {
+                    cube: Cube3D::ONE,
+                    rotation: None,
+                    faces: Face::ALL.map(|face| {
+                        Some(BlockModelFace {
+                            texture_id: 0,
+                            face,
+                            cull_face: Some(face),
+                            tint: false,
+                            uv: FaceUV::new(Vec2::ZERO, Vec2::splat(0.25), None),
+                            is_opaque: false,
+                            color: Color::WHITE,
+                        })
+                    }),
+                }],
+            });
+        }
+
+        models
+    }
+
+    fn top_face(x: f32, z: f32, sun_light: u8) -> Voxel {
+        Voxel {
+            position: Vec3::new(x, 10.0, z),
+            origin: IVec2::ZERO,
+            vertices: Face::Top.as_vertices(),
+            aos: [1.0; 4],
+            uvs: Face::Top.as_uv(),
+            tile: FaceUV {
+                offset: Vec2::new(0.5, 0.0),
+                scale: Vec2::splat(0.25),
+            },
+            face: Face::Top,
+            is_opaque: true,
+            sun_light,
+            block_light: 0,
+            color: Color::WHITE,
+        }
+    }
+
+    #[test]
+    fn test_merge_faces() {
+        let mut voxels = Vec::new();
+
+        for z in 0..4 {
+            for x in 0..3 {
+                voxels.push(top_face(x as f32, z as f32, 15));
+            }
+        }
+
+        let merged = merge_faces(voxels);
+
+        assert_eq!(merged.len(), 1);
+
+        let quad = &merged[0];
+
+        assert_eq!(quad.position, Vec3::new(0.0, 10.0, 0.0));
+        assert_eq!(quad.vertices, [
+            Vec3::new(0.0, 1.0, 4.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(3.0, 1.0, 0.0),
+            Vec3::new(3.0, 1.0, 4.0),
+        ]);
+        // the texture repeats once per block
+        assert_eq!(quad.uvs, [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(4.0, 0.0),
+            Vec2::new(4.0, 3.0),
+            Vec2::new(0.0, 3.0),
+        ]);
+
+        // differently lit faces stay apart, forming a 2x1 and a 1x1 quad
+        let merged = merge_faces(vec![
+            top_face(0.0, 0.0, 15),
+            top_face(1.0, 0.0, 15),
+            top_face(2.0, 0.0, 7),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        assert!(
+            [Vec3::new(2.0, 1.0, 0.0), Vec3::new(1.0, 1.0, 0.0)]
+                .iter()
+                .all(|corner| merged.iter().any(|voxel| voxel.vertices[2] == *corner))
+        );
+
+        // faces with uneven ambient occlusion are left as they are
+        let mut shaded = top_face(3.0, 0.0, 15);
+
+        shaded.aos = [1.0, 0.5, 1.0, 1.0];
+
+        assert_eq!(merge_faces(vec![top_face(2.0, 0.0, 15), shaded]).len(), 2);
+    }
+
+    #[test]
+    fn test_transparent_neighbours() {
+        let mut simulation = Simulation::new(transparent_models(), 0..1, 0..1);
+        let [glass, water] = [2.0, 8.0].map(|z| [Vec3::new(2.0, 10.0, z), Vec3::new(3.0, 10.0, z)]);
+
+        for (block, [left, right]) in [(1, glass), (2, water)] {
+            simulation.chunk_manager_mut().set_block(left, block);
+            simulation.chunk_manager_mut().set_block(right, block);
+        }
+
+        let chunk = simulation.chunk_manager().get_chunk(&IVec2::ZERO).unwrap();
+        let mesh = simulation.compute_chunk_mesh(chunk);
+        let has_face = |face: Face, position: Vec3| {
+            mesh.iter()
+                .flat_map(|(_, [_, translucent])| translucent)
+                .any(|voxel| voxel.face == face && voxel.position == position)
+        };
+
+        // both inner faces between glass blocks are kept
+        assert!(has_face(Face::Right, glass[0]));
+        assert!(has_face(Face::Left, glass[1]));
+        // while water hides faces inside of it
+        assert!(!has_face(Face::Right, water[0]));
+        assert!(!has_face(Face::Left, water[1]));
+        // outer faces are kept either way
+        assert!(has_face(Face::Left, glass[0]) && has_face(Face::Left, water[0]));
+    }
+
+    #[test]
+    fn test_connected_variant() {
+        for face in Face::ALL {
+            let in_plane = |offset: IVec3| offset.dot(face.as_normal()) == 0;
+
+            // an isolated block has borders on every side
+            assert_eq!(connected_variant(face, |_| false), 0);
+            // blocks in front of and behind the face don't connect to it
+            assert_eq!(connected_variant(face, |offset| !in_plane(offset)), 0);
+            // a surrounded block has no borders
+            assert_eq!(connected_variant(face, in_plane), 15);
+        }
+
+        // texture coordinates of the top face grow towards -z and +x
+        assert_eq!(
+            connected_variant(Face::Top, |offset| offset == IVec3::NEG_Z),
+            1
+        );
+        assert_eq!(connected_variant(Face::Top, |offset| offset == IVec3::X), 4);
+
+        let variants = FaceUV {
+            offset: Vec2::new(0.5, 0.0),
+            scale: Vec2::splat(0.25),
+        }
+        .connected_variants();
+
+        assert_eq!(variants[0], FaceUV {
+            offset: Vec2::new(0.5, 0.0),
+            scale: Vec2::splat(0.0625),
+        });
+        assert_eq!(variants[15], FaceUV {
+            offset: Vec2::new(0.6875, 0.1875),
+            scale: Vec2::splat(0.0625),
+        });
+    }
+}
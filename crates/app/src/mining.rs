@@ -0,0 +1,101 @@
+use glam::Vec3;
+
+/// Time spent breaking the block the player is looking at, accumulated while
+/// the mouse button is held.
+#[derive(Debug, Default)]
+pub struct BreakProgress {
+    /// Position of the block being broken.
+    position: Option<Vec3>,
+    /// Seconds spent breaking the block.
+    elapsed: f32,
+    /// Seconds it takes to break the block.
+    break_time: f32,
+}
+
+impl BreakProgress {
+    /// Continues breaking the block at the given position, starting over if
+    /// it's not the one broken before. Returns whether the block is broken,
+    /// which also resets the progress.
+    pub fn advance(&mut self, position: Vec3, break_time: f32, delta: f32) -> bool {
+        if self.position != Some(position) {
+            self.position = Some(position);
+            self.elapsed = 0.0;
+        }
+
+        self.elapsed += delta;
+        self.break_time = break_time;
+
+        if self.elapsed >= self.break_time {
+            self.reset();
+
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns the part of the break time spent so far, in the range
+    /// `0.0..=1.0`.
+    pub fn progress(&self) -> f32 {
+        if self.break_time > 0.0 {
+            (self.elapsed / self.break_time).min(1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::BreakProgress;
+    use crate::{Block, blocks::DirtBlock};
+
+    struct StoneBlock;
+
+    impl Block for StoneBlock {
+        fn id(&self) -> &'static str {
+            "stone"
+        }
+
+        fn hardness(&self) -> f32 {
+            1.5
+        }
+    }
+
+    fn updates_to_break(block: &dyn Block) -> usize {
+        let mut progress = BreakProgress::default();
+        let mut updates = 1;
+
+        while !progress.advance(Vec3::ZERO, block.break_time(), 1.0 / 64.0) {
+            updates += 1;
+        }
+
+        updates
+    }
+
+    #[test]
+    fn test_harder_blocks_take_longer_to_break() {
+        let dirt = updates_to_break(&DirtBlock);
+        let stone = updates_to_break(&StoneBlock);
+
+        assert!(dirt > 1);
+        assert_eq!(stone, dirt * 3);
+
+        let mut progress = BreakProgress::default();
+
+        progress.advance(Vec3::ZERO, StoneBlock.break_time(), 0.5);
+
+        assert!(progress.progress() > 0.0);
+
+        // looking at another block starts breaking it from scratch
+        progress.advance(Vec3::X, StoneBlock.break_time(), 0.0);
+
+        assert!(progress.progress().abs() < f32::EPSILON);
+    }
+}
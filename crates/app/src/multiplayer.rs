@@ -0,0 +1,88 @@
+use std::{io, net::SocketAddrV4};
+
+use meralus_shared::{Client, IncomingPacket, OutgoingPacket, Player};
+use tokio::net::TcpStream;
+
+/// Connects to the server and registers the player with the given nickname,
+/// returning the connection along with players on the server, including the
+/// one that has just joined.
+pub async fn join(host: SocketAddrV4, nickname: String) -> io::Result<(Client, Vec<Player>)> {
+    let mut client = Client::new(TcpStream::connect(host).await?);
+
+    client
+        .send(IncomingPacket::PlayerConnected { name: nickname })
+        .await?;
+    client.send(IncomingPacket::GetPlayers).await?;
+
+    while let Some(packet) = client.receive().await {
+        if let OutgoingPacket::PlayersList { players } = packet? {
+            return Ok((client, players));
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "server closed the connection before sending players",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use glam::Vec3;
+    use meralus_shared::{IncomingPacket, OutgoingPacket, Player, ServerConnection};
+    use tokio::net::TcpListener;
+
+    use super::join;
+
+    #[tokio::test]
+    async fn test_join_registers_player() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let SocketAddr::V4(host) = listener.local_addr().unwrap() else {
+            unreachable!()
+        };
+
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut connection = ServerConnection::new(socket);
+            let mut players = vec![Player {
+                nickname: String::from("host"),
+                position: Vec3::ONE,
+            }];
+
+            while let Some(packet) = connection.receive().await {
+                match packet.unwrap() {
+                    IncomingPacket::PlayerConnected { name } => players.push(Player {
+                        nickname: name,
+                        position: Vec3::ZERO,
+                    }),
+                    IncomingPacket::GetPlayers => connection
+                        .send(OutgoingPacket::PlayersList {
+                            players: players.clone(),
+                        })
+                        .await
+                        .unwrap(),
+                    IncomingPacket::PlayerMoved { .. } => {}
+                }
+            }
+
+            players
+        });
+
+        let (client, players) = join(host, String::from("guest")).await.unwrap();
+
+        assert_eq!(
+            players
+                .iter()
+                .map(|player| player.nickname.as_str())
+                .collect::<Vec<_>>(),
+            ["host", "guest"]
+        );
+
+        // closing the connection lets the server finish
+        drop(client);
+
+        assert_eq!(server.await.unwrap(), players);
+    }
+}
@@ -0,0 +1,104 @@
+use glam::Vec3;
+
+/// How nameplates react to terrain between them and the camera and to their
+/// distance from it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NameplateOptions {
+    /// Opacity of nameplates of entities hidden behind terrain, with `0.0`
+    /// hiding them completely.
+    pub occluded_alpha: f32,
+    /// Distance at which nameplates are drawn at their full size, shrinking
+    /// when further away. `None` disables scaling.
+    pub scale_distance: Option<f32>,
+}
+
+impl NameplateOptions {
+    pub const MIN_SCALE: f32 = 0.25;
+
+    pub const fn default() -> Self {
+        Self {
+            occluded_alpha: 0.25,
+            scale_distance: Some(8.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nameplate {
+    pub alpha: f32,
+    pub scale: f32,
+}
+
+impl Nameplate {
+    /// Computes how the nameplate of the entity at `position` should look from
+    /// the camera, using `is_occluded` to check whether terrain is in between.
+    pub fn compute<F: FnOnce(Vec3, Vec3) -> bool>(
+        camera: Vec3,
+        position: Vec3,
+        options: NameplateOptions,
+        is_occluded: F,
+    ) -> Self {
+        Self {
+            alpha: if is_occluded(camera, position) {
+                options.occluded_alpha
+            } else {
+                1.0
+            },
+            scale: options.scale_distance.map_or(1.0, |scale_distance| {
+                (scale_distance / camera.distance(position)).clamp(NameplateOptions::MIN_SCALE, 1.0)
+            }),
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.alpha > 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::{Nameplate, NameplateOptions};
+
+    // a wall of terrain at `x = 5`
+    fn is_occluded(from: Vec3, to: Vec3) -> bool {
+        from.x.min(to.x) < 5.0 && from.x.max(to.x) > 5.0
+    }
+
+    #[test]
+    fn test_occlusion_fade() {
+        let options = NameplateOptions::default();
+
+        let clear = Nameplate::compute(Vec3::ZERO, Vec3::new(4.0, 0.0, 0.0), options, is_occluded);
+        let occluded =
+            Nameplate::compute(Vec3::ZERO, Vec3::new(6.0, 0.0, 0.0), options, is_occluded);
+
+        assert!((clear.alpha - 1.0).abs() < f32::EPSILON);
+        assert!(occluded.alpha < clear.alpha);
+        assert!(occluded.is_visible());
+
+        let hidden = Nameplate::compute(
+            Vec3::ZERO,
+            Vec3::new(6.0, 0.0, 0.0),
+            NameplateOptions {
+                occluded_alpha: 0.0,
+                ..options
+            },
+            is_occluded,
+        );
+
+        assert!(!hidden.is_visible());
+    }
+
+    #[test]
+    fn test_distance_scale() {
+        let options = NameplateOptions::default();
+
+        let near = Nameplate::compute(Vec3::ZERO, Vec3::new(0.0, 0.0, 4.0), options, is_occluded);
+        let far = Nameplate::compute(Vec3::ZERO, Vec3::new(0.0, 0.0, 16.0), options, is_occluded);
+
+        assert!((near.scale - 1.0).abs() < f32::EPSILON);
+        assert!((far.scale - 0.5).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,161 @@
+use std::f32::consts::PI;
+
+use glam::Vec3;
+use meralus_shared::Color;
+
+use crate::player::PlayerController;
+
+/// Angle between consecutive particles of a burst, spreading them evenly
+/// around the emission point.
+const GOLDEN_ANGLE: f32 = PI * 0.763_932;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Multiplier of gravity applied to the particle.
+    pub gravity: f32,
+    /// Remaining lifetime in seconds.
+    pub life: f32,
+    pub size: f32,
+    pub color: Color,
+}
+
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    limit: usize,
+}
+
+impl ParticleSystem {
+    pub const BURST_SIZE: usize = 24;
+    pub const BURST_SPEED: f32 = 3.0;
+    pub const LIFETIME: f32 = 0.8;
+    pub const SIZE: f32 = 0.1;
+
+    pub fn new(limit: usize) -> Self {
+        Self {
+            particles: Vec::with_capacity(limit),
+            limit,
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    pub fn len(&self) -> usize {
+        self.particles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.particles.is_empty()
+    }
+
+    /// Adds the particle, unless the particle limit is already reached.
+    pub fn emit(&mut self, particle: Particle) -> bool {
+        if self.particles.len() >= self.limit {
+            return false;
+        }
+
+        self.particles.push(particle);
+
+        true
+    }
+
+    /// Emits [`BURST_SIZE`] particles flying upwards and outwards from the
+    /// center of the block at the given position, returning how many of them
+    /// fit into the particle limit.
+    ///
+    /// [`BURST_SIZE`]: ParticleSystem::BURST_SIZE
+    #[allow(clippy::cast_precision_loss)]
+    pub fn emit_burst(&mut self, block_position: Vec3, color: Color) -> usize {
+        let origin = block_position + Vec3::splat(0.5);
+
+        (0..Self::BURST_SIZE)
+            .map(|index| {
+                let progress = index as f32 / Self::BURST_SIZE as f32;
+                let angle = index as f32 * GOLDEN_ANGLE;
+                let direction = Vec3::new(angle.cos(), 1.0 + progress, angle.sin()).normalize();
+
+                Particle {
+                    position: origin + direction * 0.25,
+                    velocity: direction * Self::BURST_SPEED * progress.mul_add(0.5, 0.75),
+                    gravity: 1.0,
+                    life: Self::LIFETIME * progress.mul_add(0.5, 0.75),
+                    size: Self::SIZE,
+                    color,
+                }
+            })
+            .filter(|particle| self.emit(*particle))
+            .count()
+    }
+
+    /// Moves particles according to their velocity and gravity, removing the
+    /// ones whose lifetime ran out.
+    pub fn update(&mut self, delta: f32) {
+        for particle in &mut self.particles {
+            particle.velocity.y -= PlayerController::GRAVITY * particle.gravity * delta;
+            particle.position += particle.velocity * delta;
+            particle.life -= delta;
+        }
+
+        self.particles.retain(|particle| particle.life > 0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use meralus_shared::Color;
+
+    use super::{Particle, ParticleSystem};
+
+    #[test]
+    fn test_emission() {
+        let mut system = ParticleSystem::new(ParticleSystem::BURST_SIZE + 4);
+
+        assert_eq!(
+            system.emit_burst(Vec3::ZERO, Color::BROWN),
+            ParticleSystem::BURST_SIZE
+        );
+        assert_eq!(system.len(), ParticleSystem::BURST_SIZE);
+
+        assert_eq!(system.emit_burst(Vec3::ZERO, Color::BROWN), 4);
+        assert_eq!(system.len(), ParticleSystem::BURST_SIZE + 4);
+    }
+
+    #[test]
+    fn test_expiration() {
+        let mut system = ParticleSystem::new(8);
+
+        system.emit(Particle {
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            gravity: 1.0,
+            life: 0.5,
+            size: ParticleSystem::SIZE,
+            color: Color::WHITE,
+        });
+        system.emit(Particle {
+            position: Vec3::ZERO,
+            velocity: Vec3::ZERO,
+            gravity: 1.0,
+            life: 1.0,
+            size: ParticleSystem::SIZE,
+            color: Color::WHITE,
+        });
+
+        system.update(0.25);
+
+        assert_eq!(system.len(), 2);
+        assert!(system.particles()[0].position.y < 0.0);
+
+        system.update(0.5);
+
+        assert_eq!(system.len(), 1);
+
+        system.update(0.5);
+
+        assert_eq!(system.len(), 0);
+    }
+}
@@ -7,8 +7,12 @@ use glam::{DVec3, FloatExt, Mat3, Mat4, Vec2, Vec3, Vec4, dvec3, vec3};
 use meralus_engine::KeyCode;
 
 use crate::{
-    Aabb, Camera, Game, KeyboardController, get_movement_direction, get_rotation_directions,
+    Aabb,
+    camera::Camera,
+    get_movement_direction, get_rotation_directions,
+    keyboard::KeyboardController,
     raycast::{HitType, RayCastResult},
+    simulation::Simulation,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -198,11 +202,26 @@ impl Default for PlayerController {
 
 impl PlayerController {
     pub const AFFECTED_BY_PHYSICS: bool = false;
+    /// Distance blocks can be looked at, broken and placed from.
+    pub const BLOCK_REACH_DISTANCE: f32 = 20.0;
     pub const GRAVITY: f32 = 9.81 * 1.5;
     pub const LOOK_SPEED: f32 = 0.1;
     pub const MOUSE_SENSE: f32 = 0.05;
     pub const MOVE_SPEED: f32 = 4.;
 
+    /// Returns bounding box the player collides with blocks by, with the
+    /// position being at the eyes.
+    fn collider_at(position: DVec3) -> Aabb {
+        Aabb::new(
+            position - dvec3(0.5, 2.0, 0.5),
+            position + dvec3(0.5, 0.0, 0.5),
+        )
+    }
+
+    pub fn collider(&self) -> Aabb {
+        Self::collider_at(self.position.as_dvec3())
+    }
+
     pub fn get_vector_for_rotation(&self) -> DVec3 {
         let f = (self.yaw - f32::consts::PI).cos();
         let f1 = (self.yaw - f32::consts::PI).sin();
@@ -214,7 +233,7 @@ impl PlayerController {
 
     pub fn handle_physics(
         &mut self,
-        game: &Game,
+        simulation: &Simulation,
         keyboard: &KeyboardController,
         camera: &mut Camera,
         delta: f32,
@@ -256,21 +275,35 @@ impl PlayerController {
             self.position.y -= 0.5;
         }
 
-        self.move_and_collide(game, delta);
+        self.move_and_collide(simulation, delta);
     }
 
-    pub fn update_looking_at(&mut self, game: &Game) {
-        let block_reach_distance = 20.0f32;
-
+    /// Returns start and end of the ray blocks within reach are looked at
+    /// along.
+    fn reach(&self) -> (DVec3, DVec3) {
         let origin = self.position;
-        let target = origin + (self.front * block_reach_distance);
+        let target = origin + (self.front * Self::BLOCK_REACH_DISTANCE);
+
+        (origin.as_dvec3(), target.as_dvec3())
+    }
 
-        self.looking_at = game
-            .raycast(origin.into(), target.into(), true)
+    pub fn update_looking_at(&mut self, simulation: &Simulation) {
+        let (origin, target) = self.reach();
+
+        self.looking_at = simulation
+            .raycast(origin, target, true)
             .filter(|result| result.hit_type == HitType::Block);
     }
 
-    pub fn move_and_collide(&mut self, game: &Game, delta: f32) {
+    /// Returns every block within reach in the looking direction, from the
+    /// nearest one.
+    pub fn blocks_in_reach(&self, simulation: &Simulation) -> Vec<RayCastResult> {
+        let (origin, target) = self.reach();
+
+        simulation.raycast_all(origin, target)
+    }
+
+    pub fn move_and_collide(&mut self, simulation: &Simulation, delta: f32) {
         let mut remaining_movement = self.velocity.as_dvec3() * f64::from(delta);
         let mut actual_movement = [0.0; 3];
 
@@ -283,13 +316,13 @@ impl PlayerController {
 
             test_pos[axis] += remaining_movement[axis];
 
-            let test_aabb = Aabb::new(
-                test_pos - dvec3(0.5, 2.0, 0.5),
-                test_pos + dvec3(0.5, 0.0, 0.5),
-            );
+            let test_aabb = Self::collider_at(test_pos);
 
-            if game.collides(test_aabb) {
-                self.is_on_ground = game.get_colliders(test_pos, test_aabb).bottom.is_some();
+            if simulation.collides(test_aabb) {
+                self.is_on_ground = simulation
+                    .get_colliders(test_pos, test_aabb)
+                    .bottom
+                    .is_some();
 
                 // Try smaller steps for more precision
                 let mut step = remaining_movement[axis].abs();
@@ -299,12 +332,9 @@ impl PlayerController {
                 while step > 0.001 {
                     test_pos[axis] = direction.mul_add(step, self.position[axis].into());
 
-                    let test_aabb = Aabb::new(
-                        test_pos - dvec3(0.5, 2.0, 0.5),
-                        test_pos + dvec3(0.5, 0.0, 0.5),
-                    );
+                    let test_aabb = Self::collider_at(test_pos);
 
-                    if !game.collides(test_aabb) {
+                    if !simulation.collides(test_aabb) {
                         self.position[axis] = test_pos[axis] as f32;
 
                         actual_movement[axis] += direction * step;
@@ -324,15 +354,25 @@ impl PlayerController {
             }
         }
 
-        self.update_looking_at(game);
+        self.update_looking_at(simulation);
     }
 
-    pub fn handle_mouse(&mut self, game: &Game, mouse_delta: Vec2) {
-        self.yaw += mouse_delta.x * Self::MOUSE_SENSE * Self::LOOK_SPEED;
-        self.pitch += mouse_delta.y * Self::MOUSE_SENSE * -Self::LOOK_SPEED;
+    pub fn handle_mouse(&mut self, simulation: &Simulation, mouse_delta: Vec2) {
+        self.set_rotation(
+            self.yaw + mouse_delta.x * Self::MOUSE_SENSE * Self::LOOK_SPEED,
+            self.pitch + mouse_delta.y * Self::MOUSE_SENSE * -Self::LOOK_SPEED,
+        );
+
+        self.update_looking_at(simulation);
 
-        self.pitch = if self.pitch > 1.5 { 1.5 } else { self.pitch };
-        self.pitch = if self.pitch < -1.5 { -1.5 } else { self.pitch };
+        // println!("{}", Self::calc_order(self.front));
+    }
+
+    /// Turns the player, clamping the pitch so that it can't look past
+    /// straight up or down.
+    pub fn set_rotation(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-1.5, 1.5);
 
         self.front = vec3(
             self.yaw.cos() * self.pitch.cos(),
@@ -341,12 +381,8 @@ impl PlayerController {
         )
         .normalize();
 
-        self.update_looking_at(game);
-
         self.right = self.front.cross(Vec3::Y).normalize();
         self.up = self.right.cross(self.front).normalize();
-
-        // println!("{}", Self::calc_order(self.front));
     }
 
     pub const fn calc_order(direction: Vec3) -> usize {
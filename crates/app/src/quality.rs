@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+/// Time frames have to stay over the budget before the render distance is
+/// lowered.
+const SLOW_PERIOD: Duration = Duration::from_secs(1);
+/// Part of the frame budget frames have to exceed for the render distance to
+/// be lowered, so that vsync running slightly below the target frame rate
+/// (like 59.94 Hz displays with a target of 60) doesn't count as slow.
+const SLOW_MARGIN: f32 = 1.1;
+/// Time frames have to stay well under the budget before the render distance
+/// is raised, which is longer than [`SLOW_PERIOD`] so that quality recovers
+/// cautiously.
+const FAST_PERIOD: Duration = Duration::from_secs(3);
+/// Part of the frame budget frames have to fit into for the render distance
+/// to grow. Frame times between it and the budget change nothing, so that
+/// the distance doesn't oscillate around the budget.
+const HEADROOM: f32 = 0.7;
+/// Weight of the newest frame in the smoothed frame time.
+const SMOOTHING: f32 = 0.1;
+
+/// Adapts render distance to the frame time, lowering it while frames take
+/// noticeably longer than the budget of the target frame rate and raising it
+/// back once there is enough headroom.
+#[derive(Debug, Clone)]
+pub struct QualityController {
+    /// Seconds a frame may take.
+    budget: f32,
+    /// Smoothed frame time in seconds, so that a single hitch doesn't count.
+    frame_time: f32,
+    over_budget: Duration,
+    under_budget: Duration,
+    render_distance: u16,
+    min_distance: u16,
+    max_distance: u16,
+}
+
+impl QualityController {
+    /// Creates a controller starting at the maximum render distance.
+    pub fn new(target_fps: u16, min_distance: u16, max_distance: u16) -> Self {
+        Self {
+            budget: 1.0 / f32::from(target_fps),
+            frame_time: 0.0,
+            over_budget: Duration::ZERO,
+            under_budget: Duration::ZERO,
+            render_distance: max_distance,
+            min_distance,
+            max_distance: max_distance.max(min_distance),
+        }
+    }
+
+    /// Render distance in chunks around the player.
+    pub const fn render_distance(&self) -> u16 {
+        self.render_distance
+    }
+
+    /// Accounts a rendered frame, changing the render distance by a chunk if
+    /// frames were over or well under the budget for long enough.
+    pub fn update(&mut self, delta: Duration) {
+        self.frame_time += (delta.as_secs_f32() - self.frame_time) * SMOOTHING;
+
+        if self.frame_time > self.budget * SLOW_MARGIN {
+            self.over_budget += delta;
+            self.under_budget = Duration::ZERO;
+        } else if self.frame_time < self.budget * HEADROOM {
+            self.under_budget += delta;
+            self.over_budget = Duration::ZERO;
+        } else {
+            self.over_budget = Duration::ZERO;
+            self.under_budget = Duration::ZERO;
+        }
+
+        if self.over_budget >= SLOW_PERIOD {
+            self.over_budget = Duration::ZERO;
+            self.render_distance = self
+                .render_distance
+                .saturating_sub(1)
+                .max(self.min_distance);
+        } else if self.under_budget >= FAST_PERIOD {
+            self.under_budget = Duration::ZERO;
+            self.render_distance = (self.render_distance + 1).min(self.max_distance);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::QualityController;
+
+    fn run(controller: &mut QualityController, fps: u32, seconds: u32) {
+        for _ in 0..fps * seconds {
+            controller.update(Duration::from_secs(1) / fps);
+        }
+    }
+
+    #[test]
+    fn test_adaptive_render_distance() {
+        let mut controller = QualityController::new(60, 2, 8);
+
+        assert_eq!(controller.render_distance(), 8);
+
+        // sustained slow frames lower the distance down to the minimum
+        run(&mut controller, 30, 3);
+
+        assert!(controller.render_distance() < 8);
+
+        run(&mut controller, 30, 10);
+
+        assert_eq!(controller.render_distance(), 2);
+
+        // frames just under the budget leave it as it is
+        run(&mut controller, 70, 10);
+
+        assert_eq!(controller.render_distance(), 2);
+
+        // fast frames restore it
+        run(&mut controller, 120, 30);
+
+        assert_eq!(controller.render_distance(), 8);
+    }
+
+    #[test]
+    fn test_vsync_below_target() {
+        let mut controller = QualityController::new(60, 2, 8);
+
+        for _ in 0..600 {
+            controller.update(Duration::from_secs_f64(1.0 / 59.94));
+        }
+
+        assert_eq!(controller.render_distance(), 8);
+    }
+}
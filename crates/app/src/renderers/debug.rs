@@ -0,0 +1,142 @@
+use glam::{Vec2, Vec3};
+use glium::{Frame, index::PrimitiveType};
+use meralus_engine::WindowDisplay;
+use meralus_shared::{Color, Cube3D};
+
+use super::{Line, Rectangle, ShapeRenderer, ShapeVertex};
+use crate::{camera::Camera, util::cube_outline};
+
+/// Immediate-mode drawing of debug shapes. Shapes are queued during the frame
+/// and drawn at once by [`DebugDraw::flush`], one draw call per kind of shape.
+#[derive(Default)]
+pub struct DebugDraw {
+    lines: Vec<Line>,
+    /// Lines with their width in pixels.
+    thick_lines: Vec<(Line, f32)>,
+    rects: Vec<Rectangle>,
+    draw_calls: usize,
+    vertices: usize,
+}
+
+impl DebugDraw {
+    pub const fn new() -> Self {
+        Self {
+            lines: Vec::new(),
+            thick_lines: Vec::new(),
+            rects: Vec::new(),
+            draw_calls: 0,
+            vertices: 0,
+        }
+    }
+
+    pub fn line(&mut self, start: Vec3, end: Vec3, color: Color) {
+        self.lines.push(Line::new(start, end, color));
+    }
+
+    pub fn cube(&mut self, cube: Cube3D, color: Color) {
+        self.lines.extend(cube_outline(cube, color));
+    }
+
+    /// Queues outline of the cube drawn with lines `width` pixels wide.
+    pub fn thick_cube(&mut self, cube: Cube3D, color: Color, width: f32) {
+        self.thick_lines
+            .extend(cube_outline(cube, color).map(|line| (line, width)));
+    }
+
+    /// Queues a rectangle in window coordinates.
+    pub fn rect(&mut self, position: Vec2, size: Vec2, color: Color) {
+        self.rects.push(Rectangle::new(
+            position.x, position.y, size.x, size.y, color,
+        ));
+    }
+
+    /// Drains queued lines into vertices of thin and thick lines
+    /// respectively.
+    fn take_vertices(&mut self, eye: Vec3, pixel_size: f32) -> [Vec<ShapeVertex>; 2] {
+        [
+            self.lines
+                .drain(..)
+                .flat_map(|line| line.as_vertices())
+                .collect(),
+            self.thick_lines
+                .drain(..)
+                .flat_map(|(line, width)| line.as_quad(eye, width * pixel_size))
+                .collect(),
+        ]
+    }
+
+    pub fn flush(
+        &mut self,
+        frame: &mut Frame,
+        display: &WindowDisplay,
+        shape_renderer: &mut ShapeRenderer,
+        camera: &Camera,
+    ) {
+        let (_, height) = display.get_framebuffer_dimensions();
+        let [lines, thick_lines] =
+            self.take_vertices(camera.position, camera.pixel_size(height as f32));
+
+        self.draw_calls = 0;
+        self.vertices = 0;
+
+        shape_renderer.set_matrix(camera.matrix());
+
+        for (vertices, ty) in [
+            (lines, PrimitiveType::LinesList),
+            (thick_lines, PrimitiveType::TrianglesList),
+        ] {
+            if !vertices.is_empty() {
+                shape_renderer.draw_shapes(frame, display, &vertices, ty);
+
+                self.draw_calls += 1;
+                self.vertices += vertices.len();
+            }
+        }
+
+        shape_renderer.set_default_matrix();
+        shape_renderer.draw_rects(
+            frame,
+            display,
+            &self.rects,
+            None,
+            &mut self.draw_calls,
+            &mut self.vertices,
+        );
+
+        self.rects.clear();
+    }
+
+    pub const fn get_debug_info(&self) -> (usize, usize) {
+        (self.draw_calls, self.vertices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec3};
+    use meralus_shared::{Color, Cube3D};
+
+    use super::DebugDraw;
+
+    #[test]
+    fn test_queued_vertices() {
+        let mut debug_draw = DebugDraw::new();
+
+        debug_draw.line(Vec3::ZERO, Vec3::X, Color::RED);
+        debug_draw.cube(Cube3D::ONE, Color::BLUE);
+        debug_draw.thick_cube(Cube3D::ONE, Color::WHITE, 2.0);
+        debug_draw.rect(Vec2::ZERO, Vec2::ONE, Color::BLACK);
+        debug_draw.rect(Vec2::ONE, Vec2::ONE, Color::BLACK);
+
+        assert_eq!(debug_draw.rects.len(), 2);
+
+        let [lines, thick_lines] = debug_draw.take_vertices(Vec3::splat(5.0), 0.01);
+
+        assert_eq!(lines.len(), 13 * 2);
+        assert_eq!(thick_lines.len(), 12 * 6);
+
+        let [lines, thick_lines] = debug_draw.take_vertices(Vec3::splat(5.0), 0.01);
+
+        assert!(lines.is_empty() && thick_lines.is_empty());
+    }
+}
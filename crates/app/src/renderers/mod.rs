@@ -2,11 +2,15 @@ use glium::Program;
 use meralus_engine::WindowDisplay;
 
 pub use self::{
-    shape::{Line, Rectangle, ShapeRenderer},
-    text::{FONT, FONT_BOLD, TextRenderer},
+    debug::DebugDraw,
+    particle::ParticleRenderer,
+    shape::{Line, Rectangle, ShapeRenderer, ShapeVertex},
+    text::{FONT, FONT_BOLD, TextEffect, TextMetrics, TextRenderer},
     voxel::{Voxel, VoxelRenderer},
 };
 
+mod debug;
+mod particle;
 mod shape;
 mod text;
 mod voxel;
@@ -0,0 +1,149 @@
+use glam::{Vec2, Vec3, vec2};
+use glium::{
+    Depth, DepthTest, DrawParameters, Frame, Program, Surface, VertexBuffer,
+    index::{NoIndices, PrimitiveType},
+    uniform,
+    vertex::BufferCreationError,
+};
+use meralus_engine::WindowDisplay;
+use meralus_shared::Color;
+
+use super::Shader;
+use crate::{BLENDING, camera::Camera, impl_vertex, particles::Particle};
+
+struct ParticleShader;
+
+impl Shader for ParticleShader {
+    const FRAGMENT: &str = include_str!("../../resources/shaders/particle.fs");
+    const VERTEX: &str = include_str!("../../resources/shaders/particle.vs");
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleVertex {
+    pub corner: Vec2,
+}
+
+impl_vertex! {
+    ParticleVertex {
+        corner: [f32; 2]
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleDataVertex {
+    pub center: Vec3,
+    pub size: f32,
+    pub color: Color,
+}
+
+impl_vertex! {
+    ParticleDataVertex {
+        center: [f32; 3],
+        size: f32,
+        color: [u8; 4]
+    }
+}
+
+pub struct ParticleRenderer {
+    quad: VertexBuffer<ParticleVertex>,
+    instances: VertexBuffer<ParticleDataVertex>,
+    shader: Program,
+}
+
+impl ParticleRenderer {
+    /// Creates a renderer drawing up to `particle_limit` particles at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if vertex buffers couldn't be created.
+    pub fn new(
+        display: &WindowDisplay,
+        particle_limit: usize,
+    ) -> Result<Self, BufferCreationError> {
+        Ok(Self {
+            quad: VertexBuffer::new(display, &[
+                ParticleVertex {
+                    corner: vec2(-0.5, 0.5),
+                },
+                ParticleVertex {
+                    corner: vec2(-0.5, -0.5),
+                },
+                ParticleVertex {
+                    corner: vec2(0.5, 0.5),
+                },
+                ParticleVertex {
+                    corner: vec2(0.5, -0.5),
+                },
+            ])?,
+            instances: VertexBuffer::empty_dynamic(display, particle_limit)?,
+            shader: ParticleShader::program(display),
+        })
+    }
+
+    /// Draws particles as quads facing the camera, all in a single instanced
+    /// draw call. Particles past the limit given in [`new`] are not drawn.
+    ///
+    /// [`new`]: ParticleRenderer::new
+    pub fn render(
+        &mut self,
+        frame: &mut Frame,
+        camera: &Camera,
+        particles: &[Particle],
+        draw_calls: &mut usize,
+    ) {
+        let count = particles.len().min(self.instances.len());
+
+        if count == 0 {
+            return;
+        }
+
+        self.instances.slice_mut(0..count).unwrap().write(
+            &particles[..count]
+                .iter()
+                .map(|particle| ParticleDataVertex {
+                    center: particle.position,
+                    size: particle.size,
+                    color: particle.color,
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        let front = (camera.target - camera.position).normalize();
+        let right = front.cross(camera.up).normalize();
+        let up = right.cross(front).normalize();
+
+        let uniforms = uniform! {
+            matrix: camera.matrix().to_cols_array_2d(),
+            camera_right: right.to_array(),
+            camera_up: up.to_array(),
+        };
+
+        frame
+            .draw(
+                (
+                    &self.quad,
+                    self.instances
+                        .slice(0..count)
+                        .unwrap()
+                        .per_instance()
+                        .unwrap(),
+                ),
+                NoIndices(PrimitiveType::TriangleStrip),
+                &self.shader,
+                &uniforms,
+                &DrawParameters {
+                    blend: BLENDING,
+                    depth: Depth {
+                        test: DepthTest::IfLessOrEqual,
+                        write: true,
+                        ..Depth::default()
+                    },
+                    ..DrawParameters::default()
+                },
+            )
+            .expect("failed to draw!");
+
+        *draw_calls += 1;
+    }
+}
@@ -1,8 +1,9 @@
 use glam::{Mat4, Vec2, Vec3};
 use glium::{
-    DrawParameters, Frame, Program, Surface, VertexBuffer,
+    DrawParameters, Frame, Program, Rect, Surface, VertexBuffer,
     index::{NoIndices, PrimitiveType},
     uniform,
+    vertex::BufferCreationError,
 };
 use meralus_engine::WindowDisplay;
 use meralus_shared::Color;
@@ -17,6 +18,13 @@ impl Shader for ShapeShader {
     const VERTEX: &str = include_str!("../../resources/shaders/shape.vs");
 }
 
+struct RectangleShader;
+
+impl Shader for RectangleShader {
+    const FRAGMENT: &str = include_str!("../../resources/shaders/rectangle.fs");
+    const VERTEX: &str = include_str!("../../resources/shaders/rectangle.vs");
+}
+
 #[repr(C)]
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ShapeVertex {
@@ -33,6 +41,68 @@ impl_vertex! {
     }
 }
 
+/// Corners of the unit quad every rectangle is drawn with, in triangle strip
+/// order.
+const UNIT_QUAD: [Vec2; 4] = [
+    Vec2::new(0.0, 0.0),
+    Vec2::new(0.0, 1.0),
+    Vec2::new(1.0, 0.0),
+    Vec2::new(1.0, 1.0),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RectangleVertex {
+    position: Vec2,
+}
+
+impl_vertex! {
+    RectangleVertex {
+        position: [f32; 2]
+    }
+}
+
+/// Per-instance data of a [`Rectangle`], drawn over a shared unit quad.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RectangleInstance {
+    pub screen_position: Vec2,
+    pub screen_size: Vec2,
+    pub color: Color,
+    pub transform: Mat4,
+}
+
+impl_vertex! {
+    RectangleInstance {
+        screen_position: [f32; 2],
+        screen_size: [f32; 2],
+        color: [u8; 4],
+        transform: [[f32; 4]; 4]
+    }
+}
+
+impl RectangleInstance {
+    /// Returns corners of the rectangle with its transform applied, the same
+    /// way as the rectangle shader does.
+    pub fn corners(&self) -> [Vec3; 4] {
+        UNIT_QUAD.map(|corner| {
+            self.transform
+                .transform_point3((self.screen_position + corner * self.screen_size).extend(0.0))
+        })
+    }
+
+    /// Checks whether any part of the rectangle can end up inside of clip
+    /// space after applying the given matrix.
+    pub fn is_visible(&self, matrix: Mat4) -> bool {
+        let corners = self.corners().map(|corner| matrix.project_point3(corner));
+
+        !(corners.iter().all(|corner| corner.x < -1.0)
+            || corners.iter().all(|corner| corner.x > 1.0)
+            || corners.iter().all(|corner| corner.y < -1.0)
+            || corners.iter().all(|corner| corner.y > 1.0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Line {
     pub start: Vec3,
     pub end: Vec3,
@@ -58,6 +128,33 @@ impl Line {
             },
         ]
     }
+
+    /// Expands the line into a quad facing the `eye`. The quad gets wider
+    /// with distance, so its width stays the same on screen: `width` is the
+    /// width of the line seen from a unit distance.
+    pub fn as_quad(&self, eye: Vec3, width: f32) -> [ShapeVertex; 6] {
+        let direction = self.end - self.start;
+        let side = |point: Vec3| {
+            direction.cross(eye - point).normalize_or_zero() * (width * eye.distance(point) / 2.0)
+        };
+
+        let start = side(self.start);
+        let end = side(self.end);
+
+        [
+            self.start - start,
+            self.start + start,
+            self.end + end,
+            self.start - start,
+            self.end + end,
+            self.end - end,
+        ]
+        .map(|position| ShapeVertex {
+            position,
+            color: self.color,
+            transform: Mat4::IDENTITY,
+        })
+    }
 }
 
 pub struct Rectangle {
@@ -77,46 +174,56 @@ impl Rectangle {
         }
     }
 
+    #[must_use]
     pub const fn with_matrix(mut self, matrix: Option<Mat4>) -> Self {
         self.matrix = matrix;
 
         self
     }
 
-    pub fn as_vertices(&self) -> [ShapeVertex; 6] {
-        let position = self.position.extend(0.0);
-
-        [
-            [0.0, 0.0],
-            [0.0, self.size.y],
-            [self.size.x, self.size.y],
-            [0.0, 0.0],
-            [self.size.x, 0.0],
-            [self.size.x, self.size.y],
-        ]
-        .map(|offset| {
-            let offset = Vec2::from_array(offset).extend(0.0);
-
-            ShapeVertex {
-                position: position + offset,
-                color: self.color,
-                transform: self.matrix.unwrap_or_default(),
-            }
-        })
+    pub fn as_instance(&self) -> RectangleInstance {
+        RectangleInstance {
+            screen_position: self.position,
+            screen_size: self.size,
+            color: self.color,
+            transform: self.matrix.unwrap_or_default(),
+        }
     }
 }
 
 pub struct ShapeRenderer {
     shader: Program,
+    rectangle_shader: Program,
+    rectangle: VertexBuffer<RectangleVertex>,
     matrix: Option<Mat4>,
 }
 
 impl ShapeRenderer {
-    pub fn new(display: &WindowDisplay) -> Self {
-        Self {
+    /// Creates a renderer for rectangles and lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if vertex buffers couldn't be created.
+    pub fn new(display: &WindowDisplay) -> Result<Self, BufferCreationError> {
+        let rectangle = VertexBuffer::new(
+            display,
+            &UNIT_QUAD.map(|position| RectangleVertex { position }),
+        )?;
+
+        Ok(Self {
             shader: ShapeShader::program(display),
+            rectangle_shader: RectangleShader::program(display),
+            rectangle,
             matrix: None,
-        }
+        })
+    }
+
+    fn get_matrix(&self, display: &WindowDisplay) -> Mat4 {
+        let (width, height) = display.get_framebuffer_dimensions();
+
+        self.matrix.unwrap_or_else(|| {
+            Mat4::orthographic_rh_gl(0., width as f32, height as f32, 0., -1., 1.)
+        })
     }
 
     pub const fn set_matrix(&mut self, matrix: Mat4) {
@@ -127,7 +234,7 @@ impl ShapeRenderer {
         self.matrix = None;
     }
 
-    fn draw_shapes(
+    pub fn draw_shapes(
         &self,
         frame: &mut Frame,
         display: &WindowDisplay,
@@ -136,14 +243,8 @@ impl ShapeRenderer {
     ) {
         let vertex_buffer = VertexBuffer::new(display, vertices).unwrap();
 
-        let (width, height) = display.get_framebuffer_dimensions();
-
-        let matrix = self.matrix.unwrap_or_else(|| {
-            Mat4::orthographic_rh_gl(0., width as f32, height as f32, 0., -1., 1.)
-        });
-
         let uniforms = uniform! {
-            matrix: matrix.to_cols_array_2d(),
+            matrix: self.get_matrix(display).to_cols_array_2d(),
         };
 
         frame
@@ -165,38 +266,113 @@ impl ShapeRenderer {
         frame: &mut Frame,
         display: &WindowDisplay,
         rects: &[Rectangle],
+        clip_area: Option<Rect>,
         draw_calls: &mut usize,
         rendered_vertices: &mut usize,
     ) {
-        let vertices = rects.iter().fold(Vec::new(), |mut vertices, rect| {
-            vertices.extend(rect.as_vertices());
+        let matrix = self.get_matrix(display);
+        let instances = rects
+            .iter()
+            .map(Rectangle::as_instance)
+            .filter(|instance| instance.is_visible(matrix))
+            .collect::<Vec<_>>();
+
+        if instances.is_empty() {
+            return;
+        }
 
-            vertices
-        });
+        let instance_buffer = VertexBuffer::new(display, &instances).unwrap();
 
-        self.draw_shapes(frame, display, &vertices, PrimitiveType::TrianglesList);
+        let uniforms = uniform! {
+            matrix: matrix.to_cols_array_2d(),
+        };
+
+        frame
+            .draw(
+                (&self.rectangle, instance_buffer.per_instance().unwrap()),
+                NoIndices(PrimitiveType::TriangleStrip),
+                &self.rectangle_shader,
+                &uniforms,
+                &DrawParameters {
+                    blend: BLENDING,
+                    scissor: clip_area,
+                    ..DrawParameters::default()
+                },
+            )
+            .expect("failed to draw!");
 
         *draw_calls += 1;
-        *rendered_vertices += vertices.len();
+        *rendered_vertices += instances.len() * self.rectangle.len();
     }
+}
 
-    pub fn draw_lines(
-        &self,
-        frame: &mut Frame,
-        display: &WindowDisplay,
-        lines: &[Line],
-        draw_calls: &mut usize,
-        rendered_vertices: &mut usize,
-    ) {
-        let vertices = lines.iter().fold(Vec::new(), |mut vertices, line| {
-            vertices.extend(line.as_vertices());
+#[cfg(test)]
+mod tests {
+    use glam::{Mat4, Vec3};
+    use meralus_shared::Color;
+
+    use super::{Line, Rectangle};
+
+    #[test]
+    fn test_thick_line_quad() {
+        let line = Line::new(Vec3::ZERO, Vec3::X, Color::WHITE);
+        let eye = Vec3::new(0.5, 2.0, 0.0);
+        let width = 0.1;
+
+        // Both ends are at the same distance from the eye, and the quad spreads
+        // along Z, perpendicular to both the line and the view direction
+        let half_width = width * eye.distance(Vec3::ZERO) / 2.0;
+        let expected = [
+            Vec3::new(0.0, 0.0, -half_width),
+            Vec3::new(0.0, 0.0, half_width),
+            Vec3::new(1.0, 0.0, half_width),
+            Vec3::new(0.0, 0.0, -half_width),
+            Vec3::new(1.0, 0.0, half_width),
+            Vec3::new(1.0, 0.0, -half_width),
+        ];
+
+        for (vertex, expected) in line.as_quad(eye, width).iter().zip(expected) {
+            assert!(vertex.position.abs_diff_eq(expected, 1e-6));
+            assert_eq!(vertex.color, Color::WHITE);
+        }
+    }
 
-            vertices
-        });
+    #[test]
+    fn test_rectangle_instances() {
+        let rects = (0..24 * 24)
+            .map(|i| Rectangle::new((i % 24) as f32, (i / 24) as f32, 1.0, 1.0, Color::WHITE))
+            .collect::<Vec<_>>();
 
-        self.draw_shapes(frame, display, &vertices, PrimitiveType::LinesList);
+        let instances = rects.iter().map(Rectangle::as_instance).collect::<Vec<_>>();
 
-        *draw_calls += 1;
-        *rendered_vertices += vertices.len();
+        assert_eq!(instances.len(), rects.len());
+
+        for (rect, instance) in rects.iter().zip(&instances) {
+            assert_eq!(instance.screen_position, rect.position);
+            assert_eq!(instance.screen_size, rect.size);
+        }
+    }
+
+    #[test]
+    fn test_rectangle_transform() {
+        let rect = Rectangle::new(10.0, 20.0, 4.0, 2.0, Color::WHITE)
+            .with_matrix(Some(Mat4::from_scale(Vec3::new(2.0, 2.0, 1.0))));
+
+        assert_eq!(rect.as_instance().corners(), [
+            Vec3::new(20.0, 40.0, 0.0),
+            Vec3::new(20.0, 44.0, 0.0),
+            Vec3::new(28.0, 40.0, 0.0),
+            Vec3::new(28.0, 44.0, 0.0),
+        ]);
+
+        let window = Mat4::orthographic_rh_gl(0.0, 100.0, 100.0, 0.0, -1.0, 1.0);
+
+        assert!(rect.as_instance().is_visible(window));
+        assert!(
+            !Rectangle::new(10.0, 20.0, 4.0, 2.0, Color::WHITE)
+                .with_matrix(Some(Mat4::from_translation(Vec3::new(200.0, 0.0, 0.0))))
+                .as_instance()
+                .is_visible(window)
+        );
     }
 }
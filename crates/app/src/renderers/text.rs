@@ -1,8 +1,12 @@
-use std::{borrow::Borrow, collections::HashMap};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
 
 use fontdue::{
     Font, FontSettings,
-    layout::{CoordinateSystem, GlyphRasterConfig, Layout, TextStyle},
+    layout::{CoordinateSystem, GlyphPosition, Layout, TextStyle},
 };
 use glam::{Mat4, Vec2, Vec3, vec2, vec3};
 use glium::{
@@ -12,16 +16,24 @@ use glium::{
     uniforms::MagnifySamplerFilter,
     vertex::BufferCreationError,
 };
-use image::ImageBuffer;
+use image::{ImageBuffer, RgbaImage};
 use meralus_engine::WindowDisplay;
-use meralus_shared::{Color, FromValue};
+use meralus_shared::{Color, FromValue, Point2D, Size2D};
+use owo_colors::OwoColorize;
 
 use super::Shader;
-use crate::{BLENDING, Point2D, Size2D, impl_vertex, loaders::TextureAtlas};
+use crate::{BLENDING, impl_vertex, loaders::TextureAtlas};
 
 pub const FONT: &[u8] = include_bytes!("../../resources/PixeloidSans.ttf");
 pub const FONT_BOLD: &[u8] = include_bytes!("../../resources/PixeloidSans-Bold.ttf");
 
+/// Size (in pixels) at which glyphs are rasterized into signed distance fields.
+/// Every other size is produced by scaling these in the shader.
+const SDF_SIZE: f32 = 48.0;
+/// Distance (in pixels of [`SDF_SIZE`]) covered by the distance field around
+/// glyph outlines.
+const SDF_SPREAD: usize = 6;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TextVertex {
     pub position: Vec3,
@@ -38,6 +50,7 @@ impl_vertex! {
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct TextDataVertex {
     pub screen_position: Point2D,
+    pub screen_size: Vec2,
     pub offset: Vec2,
     pub size: Vec2,
 }
@@ -45,21 +58,184 @@ pub struct TextDataVertex {
 impl_vertex! {
     TextDataVertex {
         screen_position: [f32; 2],
+        screen_size: [f32; 2],
         offset: [f32; 2],
         size: [f32; 2]
     }
 }
 
 impl TextDataVertex {
-    pub const fn from_vec(screen_position: Point2D, offset: Vec2, size: Vec2) -> Self {
+    pub const fn from_vec(
+        screen_position: Point2D,
+        screen_size: Vec2,
+        offset: Vec2,
+        size: Vec2,
+    ) -> Self {
         Self {
             screen_position,
+            screen_size,
             offset,
             size,
         }
     }
 }
 
+/// Additional pass drawn underneath text to improve its legibility.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextEffect {
+    /// Draws the text once more, shifted by the offset (in pixels).
+    Shadow { offset: Vec2, color: Color },
+    /// Draws the text eight more times around itself, `width` pixels away.
+    Outline { width: f32, color: Color },
+}
+
+impl TextEffect {
+    /// Returns the offsets at which the text is drawn, along with the color,
+    /// with the main pass being the last one.
+    fn passes(effect: Option<Self>, color: Color) -> Vec<(Vec2, Color)> {
+        let mut passes = match effect {
+            None => Vec::new(),
+            Some(Self::Shadow { offset, color }) => vec![(offset, color)],
+            Some(Self::Outline { width, color }) => (-1i8..=1)
+                .flat_map(|x| (-1i8..=1).map(move |y| vec2(f32::from(x), f32::from(y))))
+                .filter(|direction| *direction != Vec2::ZERO)
+                .map(|direction| (direction * width, color))
+                .collect(),
+        };
+
+        passes.push((Vec2::ZERO, color));
+
+        passes
+    }
+}
+
+/// Detailed metrics of laid out text, with vertical metrics taken from its
+/// first line. All values are in pixels, with `descent` being negative.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TextMetrics {
+    pub width: f32,
+    pub height: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    pub line_gap: f32,
+    /// Distance from the top of the text to the baseline of its first line.
+    pub baseline: f32,
+}
+
+impl TextMetrics {
+    fn from_layout(layout: &Layout) -> Self {
+        let width = layout.glyphs().iter().fold(0.0f32, |width, glyph| {
+            width.max(glyph.x + glyph.width as f32)
+        });
+
+        layout
+            .lines()
+            .and_then(|lines| lines.first())
+            .map_or_else(Self::default, |line| Self {
+                width,
+                height: layout.height(),
+                ascent: line.max_ascent,
+                descent: line.min_descent,
+                line_gap: line.max_line_gap,
+                baseline: line.baseline_y,
+            })
+    }
+}
+
+/// Returns the key under which the glyph is stored in a font atlas. Since
+/// glyphs are stored as distance fields, it does not depend on the font size.
+const fn atlas_key(glyph: &GlyphPosition) -> u16 {
+    glyph.key.glyph_index
+}
+
+/// Reorders the text from logical into visual order, so that right-to-left
+/// runs are laid out correctly by the (left-to-right only) layout.
+#[cfg(feature = "bidi")]
+fn visual_order(text: &str) -> Cow<'_, str> {
+    let info = unicode_bidi::BidiInfo::new(text, None);
+
+    if !info.has_rtl() {
+        return Cow::Borrowed(text);
+    }
+
+    let mut reordered = String::with_capacity(text.len());
+
+    for paragraph in &info.paragraphs {
+        reordered.push_str(&info.reorder_line(paragraph, paragraph.range.clone()));
+    }
+
+    Cow::Owned(reordered)
+}
+
+#[cfg(not(feature = "bidi"))]
+const fn visual_order(text: &str) -> Cow<'_, str> {
+    Cow::Borrowed(text)
+}
+
+/// Splits the text into runs of characters drawn with the same font, choosing
+/// for every character the first font of the chain that has a glyph for it.
+/// Characters missing from every font stay with the first one.
+fn font_runs<F: Fn(usize, char) -> bool>(
+    text: &str,
+    chain: &[usize],
+    has_glyph: F,
+) -> Vec<(usize, Range<usize>)> {
+    let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+
+    for (start, character) in text.char_indices() {
+        let font_index = chain
+            .iter()
+            .copied()
+            .find(|&font_index| has_glyph(font_index, character))
+            .unwrap_or(chain[0]);
+
+        let end = start + character.len_utf8();
+
+        match runs.last_mut() {
+            Some((last_index, range)) if *last_index == font_index => range.end = end,
+            _ => runs.push((font_index, start..end)),
+        }
+    }
+
+    runs
+}
+
+/// Converts a glyph coverage bitmap into a signed distance field, padded by
+/// [`SDF_SPREAD`] on every side. Distances are stored in the alpha channel,
+/// with `128` lying exactly on the outline.
+fn generate_sdf(width: usize, height: usize, bitmap: &[u8]) -> RgbaImage {
+    let [padded_width, padded_height] = [width + SDF_SPREAD * 2, height + SDF_SPREAD * 2];
+
+    let is_inside = |x: usize, y: usize| {
+        (SDF_SPREAD..width + SDF_SPREAD).contains(&x)
+            && (SDF_SPREAD..height + SDF_SPREAD).contains(&y)
+            && bitmap[(y - SDF_SPREAD) * width + (x - SDF_SPREAD)] >= 128
+    };
+
+    ImageBuffer::from_fn(padded_width as u32, padded_height as u32, |x, y| {
+        let [x, y] = [x as usize, y as usize];
+        let inside = is_inside(x, y);
+        let mut distance = SDF_SPREAD as f32;
+
+        for ny in y.saturating_sub(SDF_SPREAD)..(y + SDF_SPREAD + 1).min(padded_height) {
+            for nx in x.saturating_sub(SDF_SPREAD)..(x + SDF_SPREAD + 1).min(padded_width) {
+                if is_inside(nx, ny) != inside {
+                    distance = distance.min((nx.abs_diff(x) as f32).hypot(ny.abs_diff(y) as f32));
+                }
+            }
+        }
+
+        let signed = if inside { distance } else { -distance } / SDF_SPREAD as f32;
+
+        image::Rgba([
+            255,
+            255,
+            255,
+            (signed.mul_add(127.0, 128.0)).clamp(0.0, 255.0) as u8,
+        ])
+    })
+}
+
 struct TextShader;
 
 impl Shader for TextShader {
@@ -69,7 +245,62 @@ impl Shader for TextShader {
 
 pub struct FontInfo {
     pub font: Font,
-    pub atlas: TextureAtlas<GlyphRasterConfig>,
+    pub atlas: TextureAtlas<u16>,
+    /// Distance fields of glyphs in the atlas, which it's repacked from.
+    glyphs: HashMap<u16, RgbaImage>,
+    /// Glyphs rendered since the last [`TextRenderer::reset_used_glyphs`].
+    used_glyphs: HashSet<u16>,
+}
+
+impl FontInfo {
+    /// Rasterizes the glyph into the atlas unless it's there already. If the
+    /// atlas is full, glyphs that weren't rendered recently are dropped to
+    /// make space for it, and if that isn't enough, the glyph is skipped.
+    fn cache_glyph(&mut self, key: u16) {
+        self.used_glyphs.insert(key);
+
+        if self.atlas.contains(&key) {
+            return;
+        }
+
+        let (metrics, bitmap) = self.font.rasterize_indexed(key, SDF_SIZE);
+        let image = generate_sdf(metrics.width, metrics.height, &bitmap);
+
+        if self.atlas.try_append(key, &image).is_none() {
+            self.collect_unused_glyphs();
+
+            if self.atlas.try_append(key, &image).is_none() {
+                println!(
+                    "[{:18}] Glyph atlas is full, skipping glyph {}",
+                    "WARN/Rendering".bright_yellow(),
+                    key.bright_blue().bold()
+                );
+
+                return;
+            }
+        }
+
+        self.glyphs.insert(key, image);
+    }
+
+    /// Removes glyphs that weren't rendered since the last
+    /// [`TextRenderer::reset_used_glyphs`] from the atlas and repacks it, so
+    /// that glyphs no longer in use don't occupy atlas space forever.
+    fn collect_unused_glyphs(&mut self) {
+        let unused = self
+            .atlas
+            .keys()
+            .filter(|key| !self.used_glyphs.contains(*key))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for key in &unused {
+            self.atlas.remove(key);
+            self.glyphs.remove(key);
+        }
+
+        self.atlas.repack(&self.glyphs);
+    }
 }
 
 impl Borrow<Font> for FontInfo {
@@ -88,6 +319,11 @@ pub struct TextRenderer {
 }
 
 impl TextRenderer {
+    /// Creates a renderer drawing up to `character_limit` characters at once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if vertex buffers couldn't be created.
     pub fn new(
         display: &WindowDisplay,
         character_limit: usize,
@@ -114,7 +350,9 @@ impl TextRenderer {
         let character_offset = VertexBuffer::dynamic(
             display,
             &(0..character_limit)
-                .map(|_| TextDataVertex::from_vec(Point2D::ZERO, Vec2::ZERO, Vec2::ZERO))
+                .map(|_| {
+                    TextDataVertex::from_vec(Point2D::ZERO, Vec2::ZERO, Vec2::ZERO, Vec2::ZERO)
+                })
                 .collect::<Vec<_>>(),
         )?;
 
@@ -135,10 +373,30 @@ impl TextRenderer {
             self.fonts.push(FontInfo {
                 font,
                 atlas: TextureAtlas::new(display, 4096),
+                glyphs: HashMap::new(),
+                used_glyphs: HashSet::new(),
             });
         }
     }
 
+    /// Lays out the text using the given font, falling back to fonts registered
+    /// after it for characters it has no glyphs for.
+    fn layout_text(&mut self, font_index: usize, text: &str, size: f32) {
+        let text = visual_order(text);
+        let text = text.as_ref();
+        let chain = (font_index..self.fonts.len()).collect::<Vec<_>>();
+        let runs = font_runs(text, &chain, |font_index, character| {
+            self.fonts[font_index].font.lookup_glyph_index(character) != 0
+        });
+
+        self.layout.clear();
+
+        for (font_index, range) in runs {
+            self.layout
+                .append(&self.fonts, &TextStyle::new(&text[range], size, font_index));
+        }
+    }
+
     pub fn measure<F: AsRef<str>, T: AsRef<str>>(
         &mut self,
         font: F,
@@ -149,11 +407,7 @@ impl TextRenderer {
             .get(font.as_ref())
             .copied()
             .map(|font_index| {
-                let text = text.as_ref();
-
-                self.layout.clear();
-                self.layout
-                    .append(&self.fonts, &TextStyle::new(text, size, font_index));
+                self.layout_text(font_index, text.as_ref(), size);
 
                 self.layout
                     .glyphs()
@@ -167,6 +421,30 @@ impl TextRenderer {
             })
     }
 
+    pub fn measure_detailed<F: AsRef<str>, T: AsRef<str>>(
+        &mut self,
+        font: F,
+        text: T,
+        size: f32,
+    ) -> Option<TextMetrics> {
+        self.font_name_map
+            .get(font.as_ref())
+            .copied()
+            .map(|font_index| {
+                self.layout_text(font_index, text.as_ref(), size);
+
+                TextMetrics::from_layout(&self.layout)
+            })
+    }
+
+    /// Forgets which glyphs were rendered, so that ones not rendered after
+    /// this call can be dropped once their font atlas runs out of space.
+    pub fn reset_used_glyphs(&mut self) {
+        for font_info in &mut self.fonts {
+            font_info.used_glyphs.clear();
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn render<F: AsRef<str>, T: AsRef<str>>(
         &mut self,
@@ -177,86 +455,258 @@ impl TextRenderer {
         text: T,
         size: f32,
         color: Color,
+        effect: Option<TextEffect>,
         clip_area: Option<Rect>,
         draw_calls: &mut usize,
     ) {
         if let Some(font_index) = self.font_name_map.get(font.as_ref()).copied() {
-            let text = text.as_ref();
-
-            self.layout.clear();
-            self.layout
-                .append(&self.fonts, &TextStyle::new(text, size, font_index));
+            self.layout_text(font_index, text.as_ref(), size);
 
             let glyphs = self.layout.glyphs();
-            let font_info = &mut self.fonts[font_index];
+            let mut font_indices = Vec::new();
 
-            for (i, vertex) in self.character_offset.map().iter_mut().enumerate() {
-                if let Some(glyph) = glyphs.get(i) {
-                    if glyph.width == 0 || glyph.height == 0 {
+            for glyph in glyphs {
+                if !font_indices.contains(&glyph.font_index) {
+                    font_indices.push(glyph.font_index);
+                }
+            }
+
+            // every font has its own atlas, so glyphs are drawn font by font
+            for font_index in font_indices {
+                let font_info = &mut self.fonts[font_index];
+
+                // Glyphs are cached before any of them is placed, since making
+                // space for one moves those already in the atlas.
+                for glyph in glyphs {
+                    if glyph.font_index == font_index && glyph.width != 0 && glyph.height != 0 {
+                        font_info.cache_glyph(atlas_key(glyph));
+                    }
+                }
+
+                for (i, vertex) in self.character_offset.map().iter_mut().enumerate() {
+                    let Some((glyph, (offset, uv_size, _))) = glyphs
+                        .get(i)
+                        .filter(|glyph| {
+                            glyph.font_index == font_index && glyph.width != 0 && glyph.height != 0
+                        })
+                        .and_then(|glyph| {
+                            font_info
+                                .atlas
+                                .get_rect(&atlas_key(glyph))
+                                .map(|rect| (glyph, rect))
+                        })
+                    else {
                         vertex.screen_position = Point2D::ZERO;
+                        vertex.screen_size = Vec2::ZERO;
                         vertex.offset = Vec2::ZERO;
                         vertex.size = Vec2::ZERO;
 
                         continue;
-                    }
-
-                    let (offset, size, _) = if let Some(rect) = font_info.atlas.get_rect(&glyph.key)
-                    {
-                        rect
-                    } else {
-                        let (metrics, bitmap) = font_info.font.rasterize(glyph.parent, size);
+                    };
 
-                        let mut image =
-                            ImageBuffer::new(metrics.width as u32, metrics.height as u32);
+                    let scale = size / SDF_SIZE;
+                    let padding = SDF_SPREAD as f32 * scale;
 
-                        for (i, pixel) in image.pixels_mut().enumerate() {
-                            let alpha = bitmap[i];
+                    vertex.screen_position =
+                        position + Point2D::new(glyph.x - padding, glyph.y - padding).into();
+                    vertex.screen_size =
+                        uv_size * font_info.atlas.get_texture().width() as f32 * scale;
 
-                            *pixel = image::Rgba([255, 255, 255, alpha]);
-                        }
+                    vertex.offset = offset;
+                    vertex.size = uv_size;
+                }
 
-                        font_info.atlas.append(glyph.key, image)
+                for (offset, color) in TextEffect::passes(effect, color) {
+                    let matrix = *matrix * Mat4::from_translation(offset.extend(0.0));
+
+                    let uniforms = uniform! {
+                        matrix: matrix.to_cols_array_2d(),
+                        font: font_info
+                            .atlas
+                            .get_texture()
+                            .sampled()
+                            .magnify_filter(MagnifySamplerFilter::Linear),
+                        text_color: <[f32; 4]>::from_value(&color),
                     };
 
-                    vertex.screen_position = position + Point2D::new(glyph.x, glyph.y).into();
-
-                    vertex.offset = offset;
-                    vertex.size = size;
-                } else {
-                    vertex.screen_position = Point2D::ZERO;
-                    vertex.offset = Vec2::ZERO;
-                    vertex.size = Vec2::ZERO;
+                    frame
+                        .draw(
+                            (
+                                &self.character,
+                                self.character_offset.per_instance().unwrap(),
+                            ),
+                            NoIndices(PrimitiveType::TriangleStrip),
+                            &self.shader,
+                            &uniforms,
+                            &DrawParameters {
+                                blend: BLENDING,
+                                scissor: clip_area,
+                                ..Default::default()
+                            },
+                        )
+                        .expect("failed to draw!");
+
+                    *draw_calls += 1;
                 }
             }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use fontdue::{
+        Font, FontSettings,
+        layout::{CoordinateSystem, Layout, TextStyle},
+    };
+    use glam::Vec2;
+    use meralus_shared::Color;
+
+    use super::{
+        FONT, FONT_BOLD, SDF_SPREAD, TextEffect, TextMetrics, atlas_key, font_runs, generate_sdf,
+    };
+
+    #[test]
+    fn test_glyph_shared_between_sizes() {
+        let font = Font::from_bytes(FONT, FontSettings::default()).unwrap();
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+
+        layout.append(&[&font], &TextStyle::new("A", 18.0, 0));
+        layout.append(&[&font], &TextStyle::new("A", 64.0, 0));
+
+        let glyphs = layout.glyphs();
+
+        assert_eq!(glyphs.len(), 2);
+        assert_ne!(glyphs[0].key, glyphs[1].key);
+
+        let keys = glyphs.iter().map(atlas_key).collect::<HashSet<_>>();
+
+        assert_eq!(keys.len(), 1);
+    }
+
+    #[test]
+    fn test_generate_sdf() {
+        let sdf = generate_sdf(4, 4, &[255; 16]);
+
+        assert_eq!(
+            sdf.dimensions(),
+            (4 + SDF_SPREAD as u32 * 2, 4 + SDF_SPREAD as u32 * 2)
+        );
+        assert_eq!(sdf.get_pixel(0, 0).0[3], 1);
+        assert!(
+            sdf.get_pixel(SDF_SPREAD as u32 + 1, SDF_SPREAD as u32 + 1)
+                .0[3]
+                > 128
+        );
+        assert!(
+            sdf.get_pixel(SDF_SPREAD as u32 - 1, SDF_SPREAD as u32 + 1)
+                .0[3]
+                < 128
+        );
+    }
+
+    #[test]
+    fn test_effect_passes() {
+        assert_eq!(TextEffect::passes(None, Color::WHITE), [(
+            Vec2::ZERO,
+            Color::WHITE
+        )]);
+
+        let shadow = TextEffect::Shadow {
+            offset: Vec2::ONE,
+            color: Color::BLACK,
+        };
+
+        assert_eq!(TextEffect::passes(Some(shadow), Color::WHITE), [
+            (Vec2::ONE, Color::BLACK),
+            (Vec2::ZERO, Color::WHITE)
+        ]);
+
+        let outline = TextEffect::Outline {
+            width: 2.0,
+            color: Color::BLACK,
+        };
+        let passes = TextEffect::passes(Some(outline), Color::WHITE);
+
+        assert_eq!(passes.len(), 9);
+        assert_eq!(passes.last(), Some(&(Vec2::ZERO, Color::WHITE)));
+    }
+
+    #[test]
+    fn test_fallback_font_runs() {
+        let fonts = [
+            Font::from_bytes(FONT, FontSettings::default()).unwrap(),
+            Font::from_bytes(FONT_BOLD, FontSettings::default()).unwrap(),
+        ];
+
+        // pretend that the primary font has no glyph for `ж`
+        let has_glyph = |font_index: usize, character| {
+            (font_index != 0 || character != 'ж')
+                && fonts[font_index].lookup_glyph_index(character) != 0
+        };
 
-            let uniforms = uniform! {
-                matrix: matrix.to_cols_array_2d(),
-                font: font_info
-                    .atlas
-                    .get_texture()
-                    .sampled()
-                    .magnify_filter(MagnifySamplerFilter::Nearest),
-                text_color: <[f32; 4]>::from_value(&color),
-            };
-
-            frame
-                .draw(
-                    (
-                        &self.character,
-                        self.character_offset.per_instance().unwrap(),
-                    ),
-                    NoIndices(PrimitiveType::TriangleStrip),
-                    &self.shader,
-                    &uniforms,
-                    &DrawParameters {
-                        blend: BLENDING,
-                        scissor: clip_area,
-                        ..Default::default()
-                    },
-                )
-                .expect("failed to draw!");
-
-            *draw_calls += 1;
+        let text = "abжc";
+        let runs = font_runs(text, &[0, 1], has_glyph);
+
+        assert_eq!(runs, [(0, 0..2), (1, 2..4), (0, 4..5)]);
+
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+
+        for (font_index, range) in runs {
+            layout.append(&fonts, &TextStyle::new(&text[range], 18.0, font_index));
         }
+
+        let font_indices = layout
+            .glyphs()
+            .iter()
+            .map(|glyph| glyph.font_index)
+            .collect::<Vec<_>>();
+
+        assert_eq!(font_indices, [0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_text_metrics() {
+        let font = Font::from_bytes(FONT, FontSettings::default()).unwrap();
+        let line_metrics = font.horizontal_line_metrics(18.0).unwrap();
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+
+        layout.append(&[&font], &TextStyle::new("Inventory", 18.0, 0));
+
+        let metrics = TextMetrics::from_layout(&layout);
+
+        assert!(metrics.width > 0.0);
+        assert!((metrics.ascent - line_metrics.ascent).abs() < 1e-4);
+        assert!((metrics.descent - line_metrics.descent).abs() < 1e-4);
+        assert!(
+            (metrics.ascent - metrics.descent + metrics.line_gap - metrics.height).abs() < 1e-4
+        );
+        assert!(metrics.baseline > 0.0 && metrics.baseline <= metrics.height);
+    }
+
+    #[cfg(feature = "bidi")]
+    #[test]
+    fn test_rtl_reordering() {
+        let font = Font::from_bytes(FONT, FontSettings::default()).unwrap();
+        let text = "אבג";
+
+        assert_eq!(super::visual_order(text), "גבא");
+
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+        let mut leftmost = |text: &str| {
+            layout.clear();
+            layout.append(&[&font], &TextStyle::new(text, 18.0, 0));
+
+            layout
+                .glyphs()
+                .iter()
+                .min_by(|a, b| a.x.total_cmp(&b.x))
+                .map(|glyph| glyph.parent)
+        };
+
+        assert_eq!(leftmost(text), Some('א'));
+        assert_eq!(leftmost(&super::visual_order(text)), Some('ג'));
     }
 }
@@ -10,11 +10,11 @@ use glium::{
 };
 use meralus_engine::WindowDisplay;
 use meralus_shared::Color;
-use meralus_world::{CHUNK_SIZE_F32, Face, SUBCHUNK_COUNT_F32};
+use meralus_world::{CHUNK_SIZE_F32, Face, SUBCHUNK_COUNT_F32, coords};
 use owo_colors::OwoColorize;
 
 use super::Shader;
-use crate::{BLENDING, impl_vertex, player::FrustumCulling};
+use crate::{BLENDING, impl_vertex, loaders::FaceUV, player::FrustumCulling};
 
 struct VoxelShader;
 
@@ -58,11 +58,16 @@ pub struct Voxel {
 
     pub vertices: [Vec3; 4],
     pub aos: [f32; 4],
+    /// Texture coordinates in tiles, which exceed one on faces merged from
+    /// several blocks to repeat the texture.
     pub uvs: [Vec2; 4],
+    /// Area of the texture atlas the face texture occupies.
+    pub tile: FaceUV,
 
     pub face: Face,
     pub is_opaque: bool,
-    pub light: u8,
+    pub sun_light: u8,
+    pub block_light: u8,
     pub color: Color,
 }
 
@@ -71,8 +76,11 @@ pub struct Voxel {
 pub struct VoxelData {
     pub position: Vec3,
     pub uv: Vec2,
+    pub tile_offset: Vec2,
+    pub tile_scale: Vec2,
     pub color: Color,
-    pub light: u8,
+    pub sun_light: u8,
+    pub block_light: u8,
     pub visible: bool,
 }
 
@@ -80,8 +88,11 @@ impl_vertex! {
     VoxelData {
         position: [f32; 3],
         uv: [f32; 2],
+        tile_offset: [f32; 2],
+        tile_scale: [f32; 2],
         color: [u8; 4],
-        light: u8,
+        sun_light: u8,
+        block_light: u8,
         visible: i8
     }
 }
@@ -93,8 +104,13 @@ pub struct VoxelRenderer {
     world_mesh: HashMap<(IVec2, Face), [Vec</* (Vec3, [ */ VoxelData /* ; 6]) */>; 2]>,
     vertices: usize,
     draw_calls: usize,
-    sun_position: f32,
+    day_factor: f32,
     rendered_chunks: HashSet<(IVec2, Face)>,
+    /// Chunk the render distance is measured from.
+    center: IVec2,
+    /// Chunks farther than this from the center along either axis aren't
+    /// rendered.
+    render_distance: u16,
     display: WindowDisplay,
 }
 
@@ -113,8 +129,11 @@ impl VoxelRenderer {
                             for i in [0, 1, 2, 2, 3, 0] {
                                 voxels.push(VoxelData {
                                     position: voxel.position + voxel.vertices[i],
-                                    light: voxel.light,
+                                    sun_light: voxel.sun_light,
+                                    block_light: voxel.block_light,
                                     uv: voxel.uvs[i],
+                                    tile_offset: voxel.tile.offset,
+                                    tile_scale: voxel.tile.scale,
                                     color: voxel.color.multiply_rgb(voxel.aos[i]),
                                     visible: true,
                                 });
@@ -135,8 +154,10 @@ impl VoxelRenderer {
             world_mesh,
             vertices: 0,
             draw_calls: 0,
-            sun_position: 0.0,
+            day_factor: 1.0,
             rendered_chunks: HashSet::new(),
+            center: IVec2::ZERO,
+            render_distance: u16::MAX,
         };
 
         println!(
@@ -161,8 +182,11 @@ impl VoxelRenderer {
                     for i in [0, 1, 2, 2, 3, 0] {
                         voxels.push(VoxelData {
                             position: voxel.position + voxel.vertices[i],
-                            light: voxel.light,
+                            sun_light: voxel.sun_light,
+                            block_light: voxel.block_light,
                             uv: voxel.uvs[i],
+                            tile_offset: voxel.tile.offset,
+                            tile_scale: voxel.tile.scale,
                             color: voxel.color.multiply_rgb(voxel.aos[i]),
                             visible: true,
                         });
@@ -186,6 +210,15 @@ impl VoxelRenderer {
         }
     }
 
+    /// Recreates the shader with the given display and drops vertex buffers,
+    /// which are uploaded again from the world mesh once they become visible.
+    pub fn restore(&mut self, display: &WindowDisplay) {
+        self.display = display.clone();
+        self.shader = VoxelShader::program(display);
+        self.opaque_data.clear();
+        self.translucent_data.clear();
+    }
+
     fn setup_chunks(&mut self) {
         self.opaque_data
             .retain(|k, _| self.rendered_chunks.contains(k));
@@ -209,6 +242,19 @@ impl VoxelRenderer {
         }
     }
 
+    /// Drops the mesh and vertex buffers of the chunk with the given origin.
+    pub fn remove_chunk(&mut self, origin: IVec2) {
+        self.world_mesh.retain(|key, _| key.0 != origin);
+        self.opaque_data.retain(|key, _| key.0 != origin);
+        self.translucent_data.retain(|key, _| key.0 != origin);
+        self.rendered_chunks.retain(|key| key.0 != origin);
+    }
+
+    /// Returns origins of chunks rendered on the last frame.
+    pub fn visible_chunks(&self) -> impl Iterator<Item = IVec2> {
+        self.rendered_chunks.iter().map(|key| key.0)
+    }
+
     pub const fn get_debug_info(&self) -> (usize, usize) {
         (self.draw_calls, self.vertices)
     }
@@ -221,13 +267,30 @@ impl VoxelRenderer {
         self.world_mesh.len()
     }
 
-    pub const fn set_sun_position(&mut self, value: f32) {
-        self.sun_position = value;
+    /// Sets multiplier of the sky light, see [`Clock::get_day_factor`].
+    ///
+    /// [`Clock::get_day_factor`]: crate::clock::Clock::get_day_factor
+    pub const fn set_day_factor(&mut self, value: f32) {
+        self.day_factor = value;
+    }
+
+    /// Limits rendered chunks to the given distance from the center chunk.
+    pub const fn set_render_distance(&mut self, center: IVec2, distance: u16) {
+        self.center = center;
+        self.render_distance = distance;
+    }
+
+    /// Returns light intensity of a voxel the same way as the voxel shader
+    /// does: block light stays constant, while sky light is dimmed at night.
+    pub fn effective_light(sun_light: u8, block_light: u8, day_factor: f32) -> f32 {
+        let block_intensity = (f32::from(block_light) + 1.0) / 16.0;
+        let sun_intensity = (f32::from(sun_light) + 1.0) / 16.0;
+
+        block_intensity.max(sun_intensity * day_factor)
     }
 
     fn is_chunk_visible(frustum: &FrustumCulling, origin: IVec2) -> bool {
-        let origin = origin.as_vec2() * CHUNK_SIZE_F32;
-        let origin = Vec3::new(origin.x, 0.0, origin.y);
+        let origin = coords::chunk_to_world(origin).as_vec3();
         let chunk_size = CHUNK_SIZE_F32;
         let chunk_height = CHUNK_SIZE_F32 * SUBCHUNK_COUNT_F32;
 
@@ -245,8 +308,14 @@ impl VoxelRenderer {
         atlas: Sampler<'_, Texture2d>,
         params: &DrawParameters,
     ) {
+        let (center, render_distance) = (self.center, i32::from(self.render_distance));
+        let is_rendered = |origin: IVec2| {
+            (origin - center).abs().max_element() <= render_distance
+                && Self::is_chunk_visible(frustum, origin)
+        };
+
         for key in self.world_mesh.keys() {
-            if Self::is_chunk_visible(frustum, key.0) {
+            if is_rendered(key.0) {
                 self.rendered_chunks.insert(*key);
             } else if self.rendered_chunks.contains(key) {
                 self.rendered_chunks.remove(key);
@@ -257,7 +326,7 @@ impl VoxelRenderer {
 
         let uniforms = uniform! {
             // origin: origin.to_array(),
-            sun_position: [0.0, self.sun_position, 0.0],
+            day_factor: self.day_factor,
             matrix: matrix.to_cols_array_2d(),
             tex: atlas,
             with_tex: true,
@@ -266,7 +335,7 @@ impl VoxelRenderer {
         self.draw_calls = 0;
 
         for key in self.world_mesh.keys() {
-            if Self::is_chunk_visible(frustum, key.0) {
+            if is_rendered(key.0) {
                 if let Some(buffer) = self.opaque_data.get(key) {
                     frame
                         .draw(
@@ -333,3 +402,43 @@ impl VoxelRenderer {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::VoxelRenderer;
+    use crate::clock::Clock;
+
+    #[test]
+    fn test_effective_light() {
+        let noon = Clock::default().get_day_factor();
+        let midnight = Clock::new(Duration::ZERO).get_day_factor();
+
+        assert!((noon - 1.0).abs() < f32::EPSILON);
+        assert!((midnight - Clock::MIN_DAY_FACTOR).abs() < f32::EPSILON);
+
+        // Open sky is fully lit at noon and dark at midnight
+        assert!((VoxelRenderer::effective_light(15, 0, noon) - 1.0).abs() < f32::EPSILON);
+        assert!(
+            (VoxelRenderer::effective_light(15, 0, midnight) - Clock::MIN_DAY_FACTOR).abs()
+                < f32::EPSILON
+        );
+
+        // Torch light does not depend on time of day
+        for day_factor in [noon, midnight] {
+            assert!(
+                (VoxelRenderer::effective_light(0, 14, day_factor) - 15.0 / 16.0).abs()
+                    < f32::EPSILON
+            );
+        }
+
+        // Caves have no sky light, so they stay dark regardless of time
+        assert!(
+            (VoxelRenderer::effective_light(0, 0, noon)
+                - VoxelRenderer::effective_light(0, 0, midnight))
+            .abs()
+                < f32::EPSILON
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    thread::{self, JoinHandle},
+};
+
+use glam::{IVec2, Vec3};
+use meralus_world::{ChunkCache, ChunkManager};
+use serde::{Deserialize, Serialize};
+
+/// Directory inside of the world directory chunks are saved to.
+const CHUNKS_DIRECTORY: &str = "chunks";
+const PLAYER_FILE: &str = "player.json";
+
+/// Part of the player kept between sessions.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PlayerState {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// Saves chunks and the player state into the world directory.
+///
+/// # Errors
+///
+/// Returns an error if the directory or any of the files couldn't be written.
+pub fn save_world(
+    directory: &Path,
+    chunk_manager: &ChunkManager,
+    player: PlayerState,
+) -> io::Result<()> {
+    chunk_manager.save_to_dir(&directory.join(CHUNKS_DIRECTORY))?;
+
+    save_player(directory, player)
+}
+
+/// Saves chunks changed since the last save along with the player state on
+/// another thread, so that saving doesn't cause a hitch. The thread returns
+/// the amount of saved chunks.
+pub fn autosave(
+    directory: PathBuf,
+    chunk_manager: &mut ChunkManager,
+    player: PlayerState,
+) -> JoinHandle<io::Result<usize>> {
+    let chunks = chunk_manager
+        .take_dirty()
+        .into_iter()
+        .filter_map(|origin| chunk_manager.get_chunk(&origin).cloned())
+        .collect::<Vec<_>>();
+
+    thread::spawn(move || {
+        let chunks_directory = directory.join(CHUNKS_DIRECTORY);
+
+        fs::create_dir_all(&chunks_directory)?;
+
+        for chunk in &chunks {
+            ChunkManager::save_chunk(&chunks_directory, chunk)?;
+        }
+
+        save_player(&directory, player)?;
+
+        Ok(chunks.len())
+    })
+}
+
+/// Drops least recently seen chunks over the budget of the cache, saving
+/// changed ones into the world directory first, and returns their origins.
+///
+/// # Errors
+///
+/// Returns an error if a changed chunk couldn't be saved.
+pub fn evict_chunks(
+    directory: &Path,
+    cache: &mut ChunkCache,
+    chunk_manager: &mut ChunkManager,
+) -> io::Result<Vec<IVec2>> {
+    cache.evict(chunk_manager, &directory.join(CHUNKS_DIRECTORY))
+}
+
+/// Reads chunks saved into the world directory, returning `None` if no world
+/// was saved there.
+///
+/// # Errors
+///
+/// Returns an error if the chunks directory or a chunk in it couldn't be read.
+pub fn load_chunks(directory: &Path) -> io::Result<Option<ChunkManager>> {
+    let chunk_manager = ChunkManager::load_from_dir(&directory.join(CHUNKS_DIRECTORY))?;
+
+    Ok((!chunk_manager.is_empty()).then_some(chunk_manager))
+}
+
+/// Reads the player state saved into the world directory, returning `None`
+/// if it wasn't saved.
+///
+/// # Errors
+///
+/// Returns an error if the player file couldn't be read or parsed.
+pub fn load_player(directory: &Path) -> io::Result<Option<PlayerState>> {
+    match fs::read(directory.join(PLAYER_FILE)) {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Loads chunks dropped by [`evict_chunks`] back from the world directory once
+/// they are within the given distance from the center chunk, returning their
+/// origins.
+///
+/// # Errors
+///
+/// Returns an error if a chunk couldn't be read.
+pub fn reload_chunks(
+    directory: &Path,
+    cache: &mut ChunkCache,
+    chunk_manager: &mut ChunkManager,
+    center: IVec2,
+    distance: i32,
+) -> io::Result<Vec<IVec2>> {
+    cache.reload(
+        chunk_manager,
+        &directory.join(CHUNKS_DIRECTORY),
+        center,
+        distance,
+    )
+}
+
+fn save_player(directory: &Path, player: PlayerState) -> io::Result<()> {
+    fs::write(directory.join(PLAYER_FILE), serde_json::to_vec(&player)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use glam::Vec3;
+    use meralus_world::ChunkManager;
+    use proptest::prelude::*;
+
+    use super::{
+        CHUNKS_DIRECTORY, PLAYER_FILE, PlayerState, autosave, load_chunks, load_player, save_world,
+    };
+
+    #[test]
+    fn test_save_world() {
+        let directory = env::temp_dir().join(format!("meralus-save-{}", std::process::id()));
+        let mut chunk_manager = ChunkManager::from_range(0..2, &(0..1));
+
+        chunk_manager.set_block(Vec3::new(3.0, 64.0, 3.0), 1);
+
+        let player = PlayerState {
+            position: Vec3::new(2.0, 80.0, 2.0),
+            yaw: 1.5,
+            pitch: -0.25,
+        };
+
+        save_world(&directory, &chunk_manager, player).unwrap();
+
+        let chunks = directory.join(CHUNKS_DIRECTORY);
+
+        assert!(chunks.join("0_0.bin").is_file());
+        assert!(chunks.join("1_0.bin").is_file());
+
+        let saved = fs::read(directory.join(PLAYER_FILE)).unwrap();
+
+        assert_eq!(
+            serde_json::from_slice::<PlayerState>(&saved).unwrap(),
+            player
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_load_world() {
+        let directory = env::temp_dir().join(format!("meralus-load-{}", std::process::id()));
+        let mut chunk_manager = ChunkManager::from_range(0..2, &(0..1));
+        let player = PlayerState {
+            position: Vec3::new(2.0, 80.0, 2.0),
+            yaw: 1.5,
+            pitch: -0.25,
+        };
+
+        assert!(load_chunks(&directory).unwrap().is_none());
+        assert_eq!(load_player(&directory).unwrap(), None);
+
+        chunk_manager.set_block(Vec3::new(20.0, 64.0, 3.0), 1);
+
+        save_world(&directory, &chunk_manager, player).unwrap();
+
+        let loaded = load_chunks(&directory).unwrap().unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.get_block(Vec3::new(20.0, 64.0, 3.0)), Some(1));
+        assert_eq!(load_player(&directory).unwrap(), Some(player));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_autosave_writes_dirty_chunks() {
+        let directory = env::temp_dir().join(format!("meralus-autosave-{}", std::process::id()));
+        let chunks = directory.join(CHUNKS_DIRECTORY);
+        let mut chunk_manager = ChunkManager::from_range(0..2, &(0..1));
+        let player = PlayerState {
+            position: Vec3::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+        };
+
+        chunk_manager.set_block(Vec3::new(20.0, 64.0, 3.0), 1);
+
+        let saved = autosave(directory.clone(), &mut chunk_manager, player)
+            .join()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(saved, 1);
+        assert!(chunks.join("1_0.bin").is_file());
+        assert!(!chunks.join("0_0.bin").exists());
+        assert!(directory.join(PLAYER_FILE).is_file());
+
+        // nothing changed since the last save
+        let saved = autosave(directory.clone(), &mut chunk_manager, player)
+            .join()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(saved, 0);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    prop_compose! {
+        fn player_state()(
+            // JSON has no representation of infinities and NaN
+            position in prop::array::uniform3(prop::num::f32::NORMAL | prop::num::f32::ZERO),
+            yaw in prop::num::f32::NORMAL | prop::num::f32::ZERO,
+            pitch in prop::num::f32::NORMAL | prop::num::f32::ZERO,
+        ) -> PlayerState {
+            PlayerState {
+                position: Vec3::from_array(position),
+                yaw,
+                pitch,
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_player_state_round_trip(player in player_state()) {
+            let serialized = serde_json::to_vec(&player).unwrap();
+
+            prop_assert_eq!(serde_json::from_slice::<PlayerState>(&serialized).unwrap(), player);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::{collections::HashSet, fs, io, path::Path};
+
+use glam::{IVec2, UVec3, Vec3};
+use meralus_world::{AIR_BLOCK, ChunkManager};
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::Simulation;
+
+/// Blocks copied from a box in the world, which can be pasted elsewhere or
+/// saved to disk.
+///
+/// Blocks have no data besides their ids, so ids are all that is kept,
+/// including air, which clears the space the schematic is pasted to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schematic {
+    size: UVec3,
+    /// Blocks ordered by their x, z and then y coordinates.
+    blocks: Vec<u8>,
+}
+
+/// Returns offsets of every block in a box of the given size, in the order
+/// blocks of a schematic are stored in.
+fn offsets(size: UVec3) -> impl Iterator<Item = UVec3> {
+    (0..size.y).flat_map(move |y| {
+        (0..size.z).flat_map(move |z| (0..size.x).map(move |x| UVec3::new(x, y, z)))
+    })
+}
+
+impl Schematic {
+    /// Copies blocks of the box between two corners, both of which are
+    /// included.
+    pub fn copy(chunk_manager: &ChunkManager, first: Vec3, second: Vec3) -> Self {
+        let min = first.min(second).floor();
+        let size = (first.max(second).floor() - min).as_uvec3() + UVec3::ONE;
+
+        Self {
+            size,
+            blocks: offsets(size)
+                .map(|offset| {
+                    chunk_manager
+                        .get_block(min + offset.as_vec3())
+                        .unwrap_or(AIR_BLOCK)
+                })
+                .collect(),
+        }
+    }
+
+    pub const fn size(&self) -> UVec3 {
+        self.size
+    }
+
+    /// Sets blocks of the schematic with its minimum corner at the given
+    /// position and updates light, returning origins of chunks that have to
+    /// be remeshed.
+    pub fn paste(&self, simulation: &mut Simulation, at: Vec3) -> HashSet<IVec2> {
+        let mut chunks = HashSet::new();
+
+        for (offset, &block) in offsets(self.size).zip(&self.blocks) {
+            let position = at.floor() + offset.as_vec3();
+            let current = simulation
+                .chunk_manager()
+                .get_block(position)
+                .unwrap_or(AIR_BLOCK);
+
+            if current != block {
+                chunks.extend(simulation.edit_block(position, block));
+                chunks.extend(simulation.chunk_manager().chunks_affected_by(position));
+            }
+        }
+
+        chunks
+    }
+
+    /// Writes the schematic to the given file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file couldn't be written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, serde_json::to_vec(self)?)
+    }
+
+    /// Reads a schematic written by [`Schematic::save`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file couldn't be read or parsed.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use glam::{IVec2, UVec3, Vec3};
+    use proptest::{collection::vec, prelude::*};
+
+    use super::Schematic;
+    use crate::{BakedBlockModelLoader, simulation::Simulation};
+
+    #[test]
+    fn test_copy_paste_round_trip() {
+        let mut simulation = Simulation::new(BakedBlockModelLoader::default(), 0..2, 0..1);
+        let structure = [
+            (Vec3::new(2.0, 100.0, 2.0), 1),
+            (Vec3::new(3.0, 100.0, 2.0), 2),
+            (Vec3::new(2.0, 101.0, 3.0), 1),
+            (Vec3::new(4.0, 102.0, 3.0), 3),
+        ];
+
+        for (position, block) in structure {
+            simulation.edit_block(position, block);
+        }
+
+        // corners can be given in any order
+        let schematic = Schematic::copy(
+            simulation.chunk_manager(),
+            Vec3::new(4.0, 102.0, 2.0),
+            Vec3::new(2.0, 100.0, 3.0),
+        );
+
+        assert_eq!(schematic.size(), UVec3::new(3, 3, 2));
+
+        let path = env::temp_dir().join(format!("meralus-schematic-{}.json", std::process::id()));
+
+        schematic.save(&path).unwrap();
+
+        let loaded = Schematic::load(&path).unwrap();
+
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded, schematic);
+
+        // pasted across the chunk border
+        let offset = Vec3::new(12.0, 5.0, 8.0);
+        let chunks = loaded.paste(&mut simulation, Vec3::new(14.0, 105.0, 10.0));
+
+        assert!(chunks.contains(&IVec2::ZERO) && chunks.contains(&IVec2::X));
+
+        for y in 100..103 {
+            for z in 2..4 {
+                for x in 2..5 {
+                    let position = Vec3::new(x as f32, y as f32, z as f32);
+
+                    assert_eq!(
+                        simulation.chunk_manager().get_block(position + offset),
+                        simulation.chunk_manager().get_block(position),
+                        "{position}"
+                    );
+                }
+            }
+        }
+    }
+
+    fn schematic() -> impl Strategy<Value = Schematic> {
+        prop::array::uniform3(1..8u32).prop_flat_map(|size| {
+            let size = UVec3::from_array(size);
+
+            vec(any::<u8>(), size.element_product() as usize)
+                .prop_map(move |blocks| Schematic { size, blocks })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_schematic_round_trip(schematic in schematic()) {
+            let serialized = serde_json::to_vec(&schematic).unwrap();
+
+            prop_assert_eq!(serde_json::from_slice::<Schematic>(&serialized).unwrap(), schematic);
+        }
+    }
+}
@@ -0,0 +1,1005 @@
+use std::{collections::HashSet, ops::Range};
+
+use glam::{DVec3, I64Vec3, IVec2, U16Vec3, Vec3, ivec3, u16vec3};
+use meralus_world::{AIR_BLOCK, CHUNK_SIZE, ChunkManager, Face};
+
+use crate::{
+    Aabb, BakedBlockModelLoader,
+    loaders::BakedBlockModel,
+    raycast::{HitType, RayCastResult},
+};
+
+/// Height of terrain generated by [`WorldGenerator::Flat`].
+const FLAT_WORLD_HEIGHT: u16 = 200;
+/// Largest amount of blocks a single region edit may span, so that a typo in
+/// corners doesn't make the game relight the whole world block by block.
+pub const MAX_REGION_VOLUME: usize = 16 * 16 * 16;
+/// Largest amount of block boundaries a ray is traced across.
+const MAX_RAYCAST_STEPS: usize = 200;
+
+/// Way terrain of a new world is generated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldGenerator {
+    /// Hills made of noise with the given seed.
+    Surface { seed: u32 },
+    /// Plain of dirt covered with grass.
+    Flat,
+}
+
+/// World logic that doesn't depend on rendering: chunks, collision and
+/// opacity data of block models, lighting and raycasting.
+///
+/// Unlike [`Game`], it can be created without a display, so it's usable on a
+/// server or in tests.
+///
+/// [`Game`]: crate::Game
+pub struct Simulation {
+    models: BakedBlockModelLoader,
+    chunk_manager: ChunkManager,
+}
+
+struct LightNode(U16Vec3, IVec2);
+
+impl LightNode {
+    pub const fn get_position(&self) -> U16Vec3 {
+        self.0
+    }
+}
+
+struct BfsLight {
+    queue: Vec<LightNode>,
+    /// Nodes whose light was removed, along with their previous light level.
+    removal_queue: Vec<(LightNode, u8)>,
+    /// Origins of chunks whose light levels were changed.
+    changed: HashSet<IVec2>,
+}
+
+impl BfsLight {
+    fn new() -> Self {
+        Self {
+            queue: Vec::new(),
+            removal_queue: Vec::new(),
+            changed: HashSet::new(),
+        }
+    }
+
+    fn push(&mut self, node: LightNode) {
+        self.queue.push(node);
+    }
+
+    fn push_removal(&mut self, node: LightNode, light_level: u8) {
+        self.removal_queue.push((node, light_level));
+    }
+
+    fn add_source(
+        chunk_manager: &mut ChunkManager,
+        blocks: &BakedBlockModelLoader,
+        position: Vec3,
+        light_level: u8,
+    ) -> HashSet<IVec2> {
+        let mut bfs_light = Self::new();
+
+        if let Some(chunk) = chunk_manager.get_chunk_mut(&ChunkManager::to_local(position)) {
+            let position = chunk.to_local(position);
+
+            chunk.set_block_light(position, light_level);
+
+            bfs_light.changed.insert(chunk.origin);
+            bfs_light.push(LightNode(position, chunk.origin));
+        }
+
+        bfs_light.calculate(chunk_manager, blocks, false);
+
+        bfs_light.changed
+    }
+
+    fn remove_source(
+        chunk_manager: &mut ChunkManager,
+        blocks: &BakedBlockModelLoader,
+        position: Vec3,
+    ) -> HashSet<IVec2> {
+        let mut bfs_light = Self::new();
+
+        if let Some(chunk) = chunk_manager.get_chunk_mut(&ChunkManager::to_local(position))
+            && chunk.contains_position(position)
+        {
+            let position = chunk.to_local(position);
+            let light_level = chunk.get_block_light(position);
+
+            chunk.set_block_light(position, 0);
+
+            bfs_light.changed.insert(chunk.origin);
+            bfs_light.push_removal(LightNode(position, chunk.origin), light_level);
+        }
+
+        bfs_light.remove(chunk_manager, false);
+        bfs_light.calculate(chunk_manager, blocks, false);
+
+        bfs_light.changed
+    }
+
+    /// Clears sky light of the block and of the column below it that was lit
+    /// directly by the sky, then lets the surrounding light fill it back.
+    fn block_sky_light(
+        chunk_manager: &mut ChunkManager,
+        blocks: &BakedBlockModelLoader,
+        position: Vec3,
+    ) -> HashSet<IVec2> {
+        let mut bfs_light = Self::new();
+        let mut position = position.floor();
+        let top = position.y;
+
+        while let Some(chunk) = chunk_manager.get_chunk_mut(&ChunkManager::to_local(position))
+            && chunk.contains_position(position)
+        {
+            let local_position = chunk.to_local(position);
+            let light_level = chunk.get_sky_light(local_position);
+
+            if position.y < top
+                && (light_level != 15
+                    || chunk
+                        .get_block_unchecked(local_position)
+                        .is_some_and(|block| blocks.get(block.into()).unwrap().is_opaque()))
+            {
+                break;
+            }
+
+            chunk.set_sky_light(local_position, 0);
+
+            bfs_light.changed.insert(chunk.origin);
+            bfs_light.push_removal(LightNode(local_position, chunk.origin), light_level);
+
+            position.y -= 1.0;
+        }
+
+        bfs_light.remove(chunk_manager, true);
+        bfs_light.calculate(chunk_manager, blocks, true);
+
+        bfs_light.changed
+    }
+
+    /// Clears light that came from the removed nodes, queueing brighter
+    /// neighbours lit by other sources to propagate their light back with
+    /// [`BfsLight::calculate`].
+    fn remove(&mut self, chunk_manager: &mut ChunkManager, is_sky_light: bool) {
+        while let Some((node, light_level)) = self.removal_queue.pop() {
+            let Some(chunk) = chunk_manager.get_chunk(&node.1) else {
+                continue;
+            };
+
+            let world_position = chunk.to_world(node.get_position());
+
+            for face in Face::ALL {
+                let neighbour_position = (world_position + face.as_normal()).as_vec3();
+
+                if let Some(chunk) =
+                    chunk_manager.get_chunk_mut(&ChunkManager::to_local(neighbour_position))
+                {
+                    if !chunk.contains_position(neighbour_position) {
+                        continue;
+                    }
+
+                    let local_position = chunk.to_local(neighbour_position);
+                    let neighbour_light_level = chunk.get_light(local_position, is_sky_light);
+
+                    if neighbour_light_level != 0 && neighbour_light_level < light_level {
+                        chunk.set_light(local_position, is_sky_light, 0);
+
+                        self.changed.insert(chunk.origin);
+                        self.push_removal(
+                            LightNode(local_position, chunk.origin),
+                            neighbour_light_level,
+                        );
+                    } else if neighbour_light_level >= light_level {
+                        self.push(LightNode(local_position, chunk.origin));
+                    }
+                }
+            }
+        }
+    }
+
+    fn calculate(
+        &mut self,
+        chunk_manager: &mut ChunkManager,
+        blocks: &BakedBlockModelLoader,
+        is_sky_light: bool,
+    ) {
+        while let Some(node) = self.queue.pop() {
+            if let Some(chunk) = chunk_manager.get_chunk_mut(&node.1) {
+                let local_position = node.get_position();
+                let world_position = chunk.to_world(local_position);
+
+                let light_level = chunk.get_light(local_position, is_sky_light);
+
+                for face in Face::ALL {
+                    let neighbour_pos = world_position + face.as_normal();
+                    let neighbour_position = neighbour_pos.as_vec3();
+
+                    if let Some(chunk) =
+                        chunk_manager.get_chunk_mut(&ChunkManager::to_local(neighbour_position))
+                    {
+                        let local_position = chunk.to_local(neighbour_position);
+
+                        if !chunk.contains_local_position(local_position) {
+                            continue;
+                        }
+
+                        if chunk
+                            .get_block_unchecked(local_position)
+                            .is_none_or(|block| !blocks.get(block.into()).unwrap().is_opaque())
+                            && chunk.get_light(local_position, is_sky_light) + 2 <= light_level
+                        {
+                            chunk.set_light(
+                                local_position,
+                                is_sky_light,
+                                if is_sky_light && face == Face::Bottom && light_level == 15 {
+                                    light_level
+                                } else {
+                                    light_level - 1
+                                },
+                            );
+
+                            self.changed.insert(chunk.origin);
+                            self.queue.push(LightNode(local_position, chunk.origin));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Colliders {
+    pub top: Option<DVec3>,
+    pub bottom: Option<DVec3>,
+    pub left: Option<DVec3>,
+    pub right: Option<DVec3>,
+    pub front: Option<DVec3>,
+    pub back: Option<DVec3>,
+}
+
+impl Simulation {
+    #[must_use]
+    pub fn new(models: BakedBlockModelLoader, x_range: Range<i32>, z_range: Range<i32>) -> Self {
+        Self {
+            models,
+            chunk_manager: ChunkManager::from_range(x_range, &z_range),
+        }
+    }
+
+    pub const fn models(&self) -> &BakedBlockModelLoader {
+        &self.models
+    }
+
+    pub const fn models_mut(&mut self) -> &mut BakedBlockModelLoader {
+        &mut self.models
+    }
+
+    pub const fn chunk_manager(&self) -> &ChunkManager {
+        &self.chunk_manager
+    }
+
+    pub const fn chunk_manager_mut(&mut self) -> &mut ChunkManager {
+        &mut self.chunk_manager
+    }
+
+    pub fn generate_world(&mut self, generator: WorldGenerator) {
+        match generator {
+            WorldGenerator::Surface { seed } => {
+                self.chunk_manager.generate_surface(seed);
+            }
+            WorldGenerator::Flat => self.chunk_manager.generate_flat(FLAT_WORLD_HEIGHT),
+        }
+    }
+
+    /// Lets sky light spread into the position from its neighbours, returning
+    /// origins of chunks whose light levels were changed.
+    pub fn update_block_sky_light(&mut self, position: Vec3) -> HashSet<IVec2> {
+        let mut bfs_light = BfsLight::new();
+
+        for face in Face::ALL {
+            let position = position + face.as_normal().as_vec3();
+
+            if let Some(chunk) = self
+                .chunk_manager
+                .get_chunk(&ChunkManager::to_local(position))
+            {
+                let local = chunk.to_local(position);
+
+                if !chunk.contains_local_position(local) {
+                    continue;
+                }
+
+                if chunk.get_block_unchecked(local).is_none() {
+                    bfs_light.push(LightNode(local, chunk.origin));
+                }
+            }
+        }
+
+        bfs_light.calculate(&mut self.chunk_manager, &self.models, true);
+
+        bfs_light.changed
+    }
+
+    /// Sets the block and updates sky light around it, returning origins of
+    /// chunks whose light levels were changed.
+    pub fn edit_block(&mut self, position: Vec3, block: u8) -> HashSet<IVec2> {
+        if !self.chunk_manager.set_block(position, block) {
+            return HashSet::new();
+        }
+
+        if block == AIR_BLOCK {
+            // Nothing is above the topmost block to cover it from the sky.
+            if let Some(chunk) = self
+                .chunk_manager
+                .get_chunk(&ChunkManager::to_local(position))
+                && chunk.to_local(position).y + 1 >= chunk.height()
+            {
+                self.chunk_manager.set_sky_light(position, 15);
+            }
+
+            self.update_block_sky_light(position)
+        } else if self
+            .models
+            .get(block.into())
+            .is_some_and(BakedBlockModel::is_opaque)
+        {
+            self.block_sky_light(position)
+        } else {
+            HashSet::new()
+        }
+    }
+
+    /// Calls the function with each block in the box between two corners, both
+    /// of which are included, replacing the block with the returned one. Light
+    /// is updated after every change.
+    ///
+    /// Returns origins of chunks that have to be remeshed, or [`None`] if the
+    /// box is larger than [`MAX_REGION_VOLUME`], in which case nothing is
+    /// changed.
+    fn edit_region<F: Fn(u8) -> Option<u8>>(
+        &mut self,
+        first: Vec3,
+        second: Vec3,
+        edit: F,
+    ) -> Option<HashSet<IVec2>> {
+        let min = first.min(second).floor().as_ivec3();
+        let max = first.max(second).floor().as_ivec3();
+        // far apart corners overflow i32 extents and u32 volumes, so both are
+        // computed in 64 bits, with an overflowing volume being over the limit
+        let size = (max.as_i64vec3() - min.as_i64vec3() + I64Vec3::ONE).as_u64vec3();
+        let volume = size
+            .x
+            .checked_mul(size.y)
+            .and_then(|area| area.checked_mul(size.z));
+
+        if volume.is_none_or(|volume| volume > MAX_REGION_VOLUME as u64) {
+            return None;
+        }
+
+        let mut chunks = HashSet::new();
+
+        for y in min.y..=max.y {
+            for z in min.z..=max.z {
+                for x in min.x..=max.x {
+                    let position = ivec3(x, y, z).as_vec3();
+                    let current = self.chunk_manager.get_block(position).unwrap_or(AIR_BLOCK);
+
+                    if let Some(block) = edit(current)
+                        && block != current
+                    {
+                        chunks.extend(self.edit_block(position, block));
+                        chunks.extend(self.chunk_manager.chunks_affected_by(position));
+                    }
+                }
+            }
+        }
+
+        Some(chunks)
+    }
+
+    /// Sets every block in the box between two corners, see
+    /// [`Simulation::edit_region`].
+    pub fn fill_region(&mut self, first: Vec3, second: Vec3, block: u8) -> Option<HashSet<IVec2>> {
+        self.edit_region(first, second, |_| Some(block))
+    }
+
+    /// Replaces blocks of one kind in the box between two corners with
+    /// another one, see [`Simulation::edit_region`].
+    pub fn replace_region(
+        &mut self,
+        first: Vec3,
+        second: Vec3,
+        from: u8,
+        to: u8,
+    ) -> Option<HashSet<IVec2>> {
+        self.edit_region(first, second, |block| (block == from).then_some(to))
+    }
+
+    pub fn generate_lights(&mut self) {
+        let mut bfs_light = BfsLight::new();
+
+        for chunk in self.chunk_manager.chunks_mut() {
+            let top = chunk.height() - 1;
+
+            for z in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    let position = u16vec3(x as u16, top, z as u16);
+
+                    if chunk
+                        .get_block_unchecked(position)
+                        .is_none_or(|block| !self.models.get(block.into()).unwrap().is_opaque())
+                    {
+                        chunk.set_sky_light(position, 15);
+
+                        bfs_light.push(LightNode(position, chunk.origin));
+                    }
+                }
+            }
+        }
+
+        bfs_light.calculate(&mut self.chunk_manager, &self.models, true);
+    }
+
+    /// Makes the position a block light source, returning origins of chunks
+    /// whose light levels were changed.
+    pub fn set_block_light(&mut self, position: Vec3, light_level: u8) -> HashSet<IVec2> {
+        BfsLight::add_source(&mut self.chunk_manager, &self.models, position, light_level)
+    }
+
+    /// Removes sky light blocked by the opaque block placed at the position,
+    /// returning origins of chunks whose light levels were changed.
+    pub fn block_sky_light(&mut self, position: Vec3) -> HashSet<IVec2> {
+        BfsLight::block_sky_light(&mut self.chunk_manager, &self.models, position)
+    }
+
+    /// Removes the block light source at the position along with the light it
+    /// spread, returning origins of chunks whose light levels were changed.
+    pub fn remove_block_light(&mut self, position: Vec3) -> HashSet<IVec2> {
+        BfsLight::remove_source(&mut self.chunk_manager, &self.models, position)
+    }
+
+    pub fn collides(&self, aabb: Aabb) -> bool {
+        let min = aabb.min.floor().as_ivec3().to_array();
+        let max = aabb.max.ceil().as_ivec3().to_array();
+
+        for y in min[1]..max[1] {
+            for z in min[2]..max[2] {
+                for x in min[0]..max[0] {
+                    let position = ivec3(x, y, z).as_dvec3();
+
+                    if self.chunk_manager.contains_block(position.as_vec3()) {
+                        let block = Aabb::new(position, position + DVec3::ONE);
+
+                        if aabb.intersects_with_x(block)
+                            && aabb.intersects_with_y(block)
+                            && aabb.intersects_with_z(block)
+                        {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    pub fn get_colliders(&self, collider_position: DVec3, aabb: Aabb) -> Colliders {
+        let min = aabb.min.floor().as_ivec3().to_array();
+        let max = aabb.max.ceil().as_ivec3().to_array();
+
+        let mut colliders = Colliders::default();
+
+        for y in min[1]..max[1] {
+            for z in min[2]..max[2] {
+                for x in min[0]..max[0] {
+                    let position = ivec3(x, y, z).as_dvec3();
+
+                    if self.chunk_manager.contains_block(position.as_vec3()) {
+                        let block = Aabb::new(position, position + DVec3::ONE);
+
+                        if aabb.intersects_with_x(block)
+                            && aabb.intersects_with_y(block)
+                            && aabb.intersects_with_z(block)
+                        {
+                            let colliding_position = position - collider_position.floor();
+
+                            if colliding_position.x < 0.0 {
+                                colliders.left = Some(position);
+                            } else if colliding_position.x > 0.0 {
+                                colliders.right = Some(position);
+                            } else if colliding_position.y < 0.0 {
+                                colliders.bottom = Some(position);
+                            } else if colliding_position.y > 0.0 {
+                                colliders.top = Some(position);
+                            } else if colliding_position.z < 0.0 {
+                                colliders.back = Some(position);
+                            } else if colliding_position.z > 0.0 {
+                                colliders.front = Some(position);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        colliders
+    }
+
+    fn raycast_into(position: Vec3, start: DVec3, end: DVec3, aabb: Aabb) -> Option<RayCastResult> {
+        aabb.calculate_intercept(start - position.as_dvec3(), end - position.as_dvec3())
+            .map(|raytraceresult| {
+                RayCastResult::new3(
+                    raytraceresult.hit_vec + position.as_dvec3(),
+                    raytraceresult.hit_side,
+                    position,
+                )
+            })
+    }
+
+    /// Returns `true` if any block lies between the two points.
+    pub fn is_occluded(&self, from: Vec3, to: Vec3) -> bool {
+        self.raycast(from.as_dvec3(), to.as_dvec3(), false)
+            .is_some()
+    }
+
+    pub fn raycast(
+        &self,
+        mut origin: DVec3,
+        target: DVec3,
+        last_uncollidable_block: bool,
+    ) -> Option<RayCastResult> {
+        if origin.is_nan() || target.is_nan() {
+            None
+        } else {
+            let mut start = origin.floor();
+            let end = target.floor();
+
+            let mut position = start.as_vec3();
+
+            if let Some(result) = self.raycast_block(position, origin, target) {
+                return Some(result);
+            }
+
+            let mut result: Option<RayCastResult> = None;
+
+            for _ in 0..MAX_RAYCAST_STEPS {
+                if origin.is_nan() {
+                    return None;
+                }
+
+                if Self::is_same_block(start, end) {
+                    return if last_uncollidable_block {
+                        result
+                    } else {
+                        None
+                    };
+                }
+
+                let facing_at;
+
+                (origin, start, facing_at) = Self::raycast_step(origin, start, end, target);
+
+                position = start.as_vec3();
+
+                let block = self.get_model_for(position);
+
+                if let Some(block) = block {
+                    let result = Self::raycast_into(
+                        position,
+                        origin,
+                        target,
+                        Aabb::from(block.bounding_box),
+                    );
+
+                    if result.is_some() {
+                        return result;
+                    }
+                } else {
+                    result.replace(RayCastResult::new(
+                        HitType::None,
+                        origin,
+                        facing_at,
+                        position,
+                    ));
+                }
+            }
+
+            if last_uncollidable_block {
+                result
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Returns hits of every block the ray from `origin` to `target` passes
+    /// through, ordered from the origin outward. Like [`Simulation::raycast`],
+    /// the ray crosses at most [`MAX_RAYCAST_STEPS`] block boundaries.
+    pub fn raycast_all(&self, mut origin: DVec3, target: DVec3) -> Vec<RayCastResult> {
+        let mut hits = Vec::new();
+
+        if origin.is_nan() || target.is_nan() {
+            return hits;
+        }
+
+        let mut start = origin.floor();
+        let end = target.floor();
+
+        hits.extend(self.raycast_block(start.as_vec3(), origin, target));
+
+        for _ in 0..MAX_RAYCAST_STEPS {
+            if origin.is_nan() || Self::is_same_block(start, end) {
+                break;
+            }
+
+            (origin, start, _) = Self::raycast_step(origin, start, end, target);
+
+            hits.extend(self.raycast_block(start.as_vec3(), origin, target));
+        }
+
+        hits
+    }
+
+    /// Intersects the ray with the block at the position, if there is one.
+    fn raycast_block(&self, position: Vec3, origin: DVec3, target: DVec3) -> Option<RayCastResult> {
+        self.get_model_for(position).and_then(|block| {
+            Self::raycast_into(position, origin, target, Aabb::from(block.bounding_box))
+        })
+    }
+
+    fn is_same_block(start: DVec3, end: DVec3) -> bool {
+        (start.x - end.x).abs() < 0.0001
+            && (start.y - end.y).abs() < 0.0001
+            && (start.z - end.z).abs() < 0.0001
+    }
+
+    /// Moves the ray from `origin` in the block at `start` to the boundary of
+    /// the next block on the way to the block at `end`, returning the point on
+    /// the boundary, the next block and the face the ray enters it through.
+    #[allow(clippy::too_many_lines)]
+    fn raycast_step(
+        mut origin: DVec3,
+        start: DVec3,
+        end: DVec3,
+        target: DVec3,
+    ) -> (DVec3, DVec3, Face) {
+        let mut modify_d3 = true;
+        let mut modify_d4 = true;
+        let mut modify_d5 = true;
+
+        let mut d0 = 999.0f64;
+        let mut d1 = 999.0f64;
+        let mut d2 = 999.0f64;
+
+        if end.x > start.x {
+            d0 = start.x + 1.0;
+        } else if end.x < start.x {
+            d0 = start.x + 0.0;
+        } else {
+            modify_d3 = false;
+        }
+
+        if end.y > start.y {
+            d1 = start.y + 1.0;
+        } else if end.y < start.y {
+            d1 = start.y + 0.0;
+        } else {
+            modify_d4 = false;
+        }
+
+        if end.z > start.z {
+            d2 = start.z + 1.0;
+        } else if end.z < start.z {
+            d2 = start.z + 0.0;
+        } else {
+            modify_d5 = false;
+        }
+
+        let mut d3 = 999.0f64;
+        let mut d4 = 999.0f64;
+        let mut d5 = 999.0f64;
+
+        let d6 = target.x - origin.x;
+        let d7 = target.y - origin.y;
+        let d8 = target.z - origin.z;
+
+        if modify_d3 {
+            d3 = (d0 - origin.x) / d6;
+        }
+
+        if modify_d4 {
+            d4 = (d1 - origin.y) / d7;
+        }
+
+        if modify_d5 {
+            d5 = (d2 - origin.z) / d8;
+        }
+
+        if d3 == -0.0 {
+            d3 = -0.0001;
+        }
+
+        if d4 == -0.0 {
+            d4 = -0.0001;
+        }
+
+        if d5 == -0.0 {
+            d5 = -0.0001;
+        }
+
+        let facing_at = if d3 < d4 && d3 < d5 {
+            origin = DVec3::new(d0, d7.mul_add(d3, origin.y), d8.mul_add(d3, origin.z));
+
+            if end.x > start.x {
+                Face::Left
+            } else {
+                Face::Right
+            }
+        } else if d4 < d5 {
+            origin = DVec3::new(d6.mul_add(d4, origin.x), d1, d8.mul_add(d4, origin.z));
+
+            if end.y > start.y {
+                Face::Bottom
+            } else {
+                Face::Top
+            }
+        } else {
+            origin = DVec3::new(d6.mul_add(d5, origin.x), d7.mul_add(d5, origin.y), d2);
+
+            if end.z > start.z {
+                Face::Front
+            } else {
+                Face::Back
+            }
+        };
+
+        let start = origin.floor()
+            - match facing_at {
+                Face::Right => DVec3::X,
+                Face::Top => DVec3::Y,
+                Face::Back => DVec3::Z,
+                Face::Bottom | Face::Left | Face::Front => DVec3::ZERO,
+            };
+
+        (origin, start, facing_at)
+    }
+
+    /// Returns baked model of the block with the given id.
+    pub fn model_for_id(&self, id: usize) -> Option<&BakedBlockModel> {
+        self.models.get(id)
+    }
+
+    pub fn get_model_for(&self, position: Vec3) -> Option<&BakedBlockModel> {
+        self.chunk_manager
+            .get_block(position)
+            .and_then(|block| self.model_for_id(block.into()))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use glam::{DVec3, IVec2, Vec3};
+    use meralus_shared::Cube3D;
+    use meralus_world::{BlockManifest, ChunkManager, Face};
+
+    use super::{BfsLight, Simulation, WorldGenerator};
+    use crate::{
+        BakedBlockModelLoader,
+        loaders::{BakedBlockModel, BlockModelElement},
+        raycast::HitType,
+    };
+
+    #[test]
+    fn test_light_removal() {
+        let mut chunk_manager = ChunkManager::from_range(-1..1, &(-1..1));
+        let blocks = BakedBlockModelLoader::default();
+        let first = Vec3::new(-2.0, 100.0, 3.0);
+        let second = Vec3::new(6.0, 100.0, 3.0);
+
+        BfsLight::add_source(&mut chunk_manager, &blocks, first, 15);
+        BfsLight::add_source(&mut chunk_manager, &blocks, second, 15);
+
+        assert_eq!(chunk_manager.get_block_light(first + Vec3::X * 4.0), 11);
+
+        let changed = BfsLight::remove_source(&mut chunk_manager, &blocks, first);
+
+        assert!(changed.contains(&IVec2::new(-1, 0)));
+        assert_eq!(chunk_manager.get_block_light(first), 7);
+        assert_eq!(chunk_manager.get_block_light(first - Vec3::X * 4.0), 3);
+        assert_eq!(chunk_manager.get_block_light(first - Vec3::X * 8.0), 0);
+        assert_eq!(chunk_manager.get_block_light(second), 15);
+
+        BfsLight::remove_source(&mut chunk_manager, &blocks, second);
+
+        for offset in -10..10 {
+            let position = second + Vec3::new(offset as f32, 0.0, 0.0);
+
+            assert_eq!(chunk_manager.get_block_light(position), 0, "{position}");
+        }
+    }
+
+    /// Returns models of dirt, grass and water, which are enough for
+    /// collision and lighting without loading any resources.
+    pub fn solid_models() -> BakedBlockModelLoader {
+        let mut models = BakedBlockModelLoader::with_manifest(
+            BlockManifest::from_slice(br#"{ "1": "dirt", "2": "grass_block", "3": "water" }"#)
+                .unwrap(),
+        );
+
+        // models without faces still have everything needed for collision and
+        // lighting
+        for (id, name, transparent) in [
+            (1, "dirt", false),
+            (2, "grass_block", false),
+            (3, "water", true),
+        ] {
+            models.insert(id, BakedBlockModel {
+                name: String::from(name),
+                bounding_box: Cube3D::ONE,
+                ambient_occlusion: true,
+                transparent,
+                cull_same_block: transparent,
+                connected: None,
+                elements: vec![BlockModelElement {
+                    cube: Cube3D::ONE,
+                    rotation: None,
+                    faces: [const { None }; 6],
+                }],
+            });
+        }
+
+        models
+    }
+
+    #[test]
+    fn test_headless_simulation() {
+        let mut simulation = Simulation::new(solid_models(), -1..1, -1..1);
+
+        simulation.generate_world(WorldGenerator::Surface { seed: 12723 });
+        simulation.generate_lights();
+
+        let top = Vec3::new(0.5, 255.0, 0.5);
+
+        assert_eq!(simulation.chunk_manager().get_sky_light(top), 15);
+
+        let hit = simulation
+            .raycast(top.as_dvec3(), DVec3::new(0.5, 0.0, 0.5), false)
+            .unwrap();
+
+        assert_eq!(hit.hit_type, HitType::Block);
+        assert_eq!(hit.hit_side, Face::Top);
+        assert!(simulation.model_for_id(2).is_some());
+        assert!(
+            simulation
+                .chunk_manager()
+                .get_sky_light(hit.position - Vec3::Y * 2.0)
+                < 15
+        );
+    }
+
+    #[test]
+    fn test_fill_region() {
+        let mut simulation = Simulation::new(solid_models(), 0..2, 0..1);
+
+        simulation.generate_lights();
+
+        let below = Vec3::new(15.0, 99.0, 5.0);
+
+        assert_eq!(simulation.chunk_manager().get_sky_light(below), 15);
+
+        // spans the border between two chunks
+        let chunks = simulation
+            .fill_region(Vec3::new(17.0, 102.0, 7.0), Vec3::new(14.0, 100.0, 4.0), 1)
+            .unwrap();
+
+        assert!(chunks.contains(&IVec2::ZERO) && chunks.contains(&IVec2::X));
+
+        for y in 100..=102 {
+            for z in 4..=7 {
+                for x in 14..=17 {
+                    let position = Vec3::new(x as f32, y as f32, z as f32);
+
+                    assert_eq!(
+                        simulation.chunk_manager().get_block(position),
+                        Some(1),
+                        "{position}"
+                    );
+                }
+            }
+        }
+
+        // the box casts a shadow
+        assert!(simulation.chunk_manager().get_sky_light(below) < 15);
+
+        simulation
+            .replace_region(
+                Vec3::new(14.0, 102.0, 4.0),
+                Vec3::new(20.0, 110.0, 4.0),
+                1,
+                2,
+            )
+            .unwrap();
+
+        assert_eq!(
+            simulation
+                .chunk_manager()
+                .get_block(Vec3::new(15.0, 102.0, 4.0)),
+            Some(2)
+        );
+        assert_eq!(
+            simulation
+                .chunk_manager()
+                .get_block(Vec3::new(15.0, 102.0, 5.0)),
+            Some(1)
+        );
+        // air isn't replaced
+        assert_eq!(
+            simulation
+                .chunk_manager()
+                .get_block(Vec3::new(19.0, 102.0, 4.0)),
+            None
+        );
+
+        assert!(
+            simulation
+                .fill_region(Vec3::ZERO, Vec3::splat(16.0), 1)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_huge_region() {
+        let mut simulation = Simulation::new(solid_models(), 0..1, 0..1);
+
+        // 65536 x 65536 x 1 blocks, whose volume wraps to zero in 32 bits
+        assert!(
+            simulation
+                .fill_region(Vec3::ZERO, Vec3::new(65535.0, 0.0, 65535.0), 1)
+                .is_none()
+        );
+        // corners at the opposite ends of the coordinate range
+        assert!(
+            simulation
+                .fill_region(Vec3::splat(f32::MIN), Vec3::splat(f32::MAX), 1)
+                .is_none()
+        );
+        assert_eq!(simulation.chunk_manager().get_block(Vec3::ZERO), None);
+    }
+
+    #[test]
+    fn test_raycast_all() {
+        let mut simulation = Simulation::new(solid_models(), 0..1, 0..1);
+
+        for x in [2, 3, 4] {
+            simulation.edit_block(Vec3::new(x as f32, 100.0, 5.0), 1);
+        }
+
+        let origin = DVec3::new(0.5, 100.5, 5.5);
+        let hits = simulation.raycast_all(origin, DVec3::new(10.5, 100.5, 5.5));
+
+        assert_eq!(hits.len(), 3);
+
+        for (hit, x) in hits.iter().zip([2.0, 3.0, 4.0]) {
+            assert_eq!(hit.hit_type, HitType::Block);
+            assert_eq!(hit.position, Vec3::new(x, 100.0, 5.0));
+        }
+
+        assert!(
+            hits.windows(2)
+                .all(|hits| hits[0].hit_vec.distance(origin) < hits[1].hit_vec.distance(origin))
+        );
+        // the first hit is the one a single raycast stops at
+        assert_eq!(
+            simulation.raycast(origin, DVec3::new(10.5, 100.5, 5.5), false),
+            Some(hits[0])
+        );
+        assert!(
+            simulation
+                .raycast_all(origin, DVec3::new(0.5, 100.5, 15.5))
+                .is_empty()
+        );
+    }
+}
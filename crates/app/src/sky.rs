@@ -0,0 +1,98 @@
+use meralus_shared::{Color, Lerp};
+
+const MIDNIGHT_COLOR: Color = Color::from_hsl(220.0, 0.35, 0.25);
+const DAWN_COLOR: Color = Color::from_hsl(25.0, 0.6, 0.6);
+const NOON_COLOR: Color = Color::from_hsl(220.0, 0.5, 0.75);
+const DUSK_COLOR: Color = Color::from_hsl(10.0, 0.55, 0.5);
+
+/// Sky color over the course of a day, described by keyframes placed at
+/// [`Clock`] progress (where `0.0` is midnight and `0.5` is noon).
+///
+/// Colors between keyframes are blended linearly, wrapping around midnight.
+///
+/// [`Clock`]: crate::clock::Clock
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkyGradient {
+    keys: Vec<(f32, Color)>,
+}
+
+impl Default for SkyGradient {
+    fn default() -> Self {
+        Self::new([
+            (0.0, MIDNIGHT_COLOR),
+            (0.25, DAWN_COLOR),
+            (0.5, NOON_COLOR),
+            (0.75, DUSK_COLOR),
+        ])
+    }
+}
+
+impl SkyGradient {
+    /// # Panics
+    ///
+    /// Panics if there are no keyframes.
+    pub fn new<I: IntoIterator<Item = (f32, Color)>>(keys: I) -> Self {
+        let mut keys = keys
+            .into_iter()
+            .map(|(time, color)| (time.rem_euclid(1.0), color))
+            .collect::<Vec<_>>();
+
+        assert!(
+            !keys.is_empty(),
+            "sky gradient requires at least one keyframe"
+        );
+
+        keys.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        Self { keys }
+    }
+
+    pub fn sample(&self, progress: f32) -> Color {
+        let progress = progress.rem_euclid(1.0);
+        let next = self.keys.partition_point(|(time, _)| *time <= progress);
+
+        let (start_time, start) = next.checked_sub(1).map_or_else(
+            || {
+                let (time, color) = self.keys[self.keys.len() - 1];
+
+                (time - 1.0, color)
+            },
+            |index| self.keys[index],
+        );
+
+        let (end_time, end) = self
+            .keys
+            .get(next)
+            .copied()
+            .unwrap_or_else(|| (self.keys[0].0 + 1.0, self.keys[0].1));
+
+        let span = end_time - start_time;
+
+        if span > 0.0 {
+            start.lerp(&end, (progress - start_time) / span)
+        } else {
+            start
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use meralus_shared::{Color, Lerp};
+
+    use super::SkyGradient;
+
+    #[test]
+    fn test_sky_gradient_sampling() {
+        let sky = SkyGradient::new([(0.5, Color::WHITE), (0.0, Color::BLACK), (0.75, Color::RED)]);
+
+        assert_eq!(sky.sample(0.0), Color::BLACK);
+        assert_eq!(sky.sample(0.5), Color::WHITE);
+        assert_eq!(sky.sample(0.75), Color::RED);
+        assert_eq!(sky.sample(1.0), Color::BLACK);
+
+        assert_eq!(sky.sample(0.25), Color::BLACK.lerp(&Color::WHITE, 0.5));
+        assert_eq!(sky.sample(0.625), Color::WHITE.lerp(&Color::RED, 0.5));
+        assert_eq!(sky.sample(0.875), Color::RED.lerp(&Color::BLACK, 0.5));
+    }
+}
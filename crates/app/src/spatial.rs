@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use glam::{DVec3, IVec3};
+
+use crate::Aabb;
+
+/// Uniform grid of cells bucketing values by their bounding boxes, so only
+/// values in cells overlapping a query have to be checked.
+#[derive(Debug, Clone)]
+pub struct SpatialHash {
+    cell_size: f64,
+    cells: HashMap<IVec3, Vec<(usize, Aabb)>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f64) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, point: DVec3) -> IVec3 {
+        (point / self.cell_size).floor().as_ivec3()
+    }
+
+    /// Returns every cell overlapped by the bounding box.
+    fn cells_of(&self, aabb: Aabb) -> impl Iterator<Item = IVec3> + use<> {
+        let min = self.cell(aabb.min);
+        let max = self.cell(aabb.max);
+
+        (min.y..=max.y).flat_map(move |y| {
+            (min.z..=max.z).flat_map(move |z| (min.x..=max.x).map(move |x| IVec3::new(x, y, z)))
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Inserts index of a value into every cell its bounding box overlaps.
+    pub fn insert(&mut self, index: usize, aabb: Aabb) {
+        for cell in self.cells_of(aabb) {
+            self.cells.entry(cell).or_default().push((index, aabb));
+        }
+    }
+
+    /// Returns sorted indices of values matching the predicate in cells
+    /// overlapped by the bounding box.
+    fn query<F: Fn(Aabb) -> bool>(&self, area: Aabb, predicate: F) -> Vec<usize> {
+        let mut indices = self
+            .cells_of(area)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(|(_, aabb)| predicate(*aabb))
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+
+        // Values spanning several cells are found once per cell.
+        indices.sort_unstable();
+        indices.dedup();
+
+        indices
+    }
+
+    /// Returns indices of values whose bounding boxes intersect with the given
+    /// one.
+    pub fn query_aabb(&self, aabb: Aabb) -> Vec<usize> {
+        self.query(aabb, |other| aabb.intersects(other))
+    }
+
+    /// Returns indices of values whose bounding boxes are at most `radius`
+    /// away from the center.
+    pub fn query_radius(&self, center: DVec3, radius: f64) -> Vec<usize> {
+        let area = Aabb::new(center - radius, center + radius);
+
+        self.query(area, |aabb| {
+            center.clamp(aabb.min, aabb.max).distance_squared(center) <= radius * radius
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::DVec3;
+
+    use super::SpatialHash;
+    use crate::Aabb;
+
+    #[test]
+    fn test_query_radius() {
+        let mut grid = SpatialHash::new(4.0);
+        let unit = |x, z| Aabb::new(DVec3::new(x, 0.0, z), DVec3::new(x + 1.0, 1.0, z + 1.0));
+
+        grid.insert(0, unit(0.0, 0.0));
+        grid.insert(1, unit(3.5, 0.0));
+        grid.insert(2, unit(-6.0, 2.0));
+        grid.insert(3, unit(40.0, 40.0));
+        // spans several cells, but must be returned once
+        grid.insert(
+            4,
+            Aabb::new(DVec3::new(-5.0, 0.0, -5.0), DVec3::new(5.0, 2.0, 5.0)),
+        );
+
+        assert_eq!(grid.query_radius(DVec3::new(0.5, 0.5, 0.5), 4.0), [0, 1, 4]);
+        assert_eq!(grid.query_radius(DVec3::new(0.5, 0.5, 0.5), 6.0), [
+            0, 1, 2, 4
+        ]);
+        assert_eq!(grid.query_radius(DVec3::new(40.5, 0.5, 40.5), 1.0), [3]);
+        assert!(
+            grid.query_radius(DVec3::new(20.0, 0.0, 20.0), 2.0)
+                .is_empty()
+        );
+
+        assert_eq!(grid.query_aabb(unit(0.5, 0.5)), [0, 4]);
+
+        grid.clear();
+
+        assert!(grid.query_radius(DVec3::ZERO, 100.0).is_empty());
+    }
+}
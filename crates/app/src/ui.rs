@@ -1,9 +1,11 @@
-use glam::Mat4;
+use glam::{Mat4, Vec3};
 use glium::{Frame, Rect};
+use meralus_animation::Curve;
+use meralus_app::renderers::{Rectangle, TextEffect, TextMetrics};
 use meralus_engine::WindowDisplay;
 use meralus_shared::{Color, Point2D, Rect2D, Size2D};
 
-use crate::{GameLoop, renderers::Rectangle};
+use crate::GameLoop;
 
 struct Text {
     position: Point2D,
@@ -11,20 +13,96 @@ struct Text {
     data: String,
     size: f32,
     color: Color,
+    effect: Option<TextEffect>,
     clip: Option<Rect2D>,
-    matrix: Option<Mat4>,
+    matrix: Mat4,
+}
+
+/// Stack of nested clip areas, where every area is already intersected with
+/// its parents.
+#[derive(Debug, Default)]
+struct ClipStack {
+    areas: Vec<Rect2D>,
+}
+
+impl ClipStack {
+    /// Returns the overlapping part of two rectangles, which is empty if they
+    /// don't overlap.
+    fn intersect(a: Rect2D, b: Rect2D) -> Rect2D {
+        let left = a.origin.x.max(b.origin.x);
+        let top = a.origin.y.max(b.origin.y);
+        let right = (a.origin.x + a.size.width).min(b.origin.x + b.size.width);
+        let bottom = (a.origin.y + a.size.height).min(b.origin.y + b.size.height);
+
+        Rect2D::new(
+            Point2D::new(left, top),
+            Size2D::new((right - left).max(0.0), (bottom - top).max(0.0)),
+        )
+    }
+
+    fn push(&mut self, area: Rect2D) {
+        let area = self
+            .current()
+            .map_or(area, |parent| Self::intersect(parent, area));
+
+        self.areas.push(area);
+    }
+
+    fn pop(&mut self) {
+        self.areas.pop();
+    }
+
+    fn current(&self) -> Option<Rect2D> {
+        self.areas.last().copied()
+    }
+}
+
+/// Stack of nested transforms, where every matrix is already multiplied by
+/// its parents and the root transform.
+#[derive(Debug, Default)]
+struct TransformStack {
+    /// Transform applied to the whole UI, scaling it by the UI scale.
+    root: Mat4,
+    matrices: Vec<Mat4>,
+}
+
+impl TransformStack {
+    fn set_scale(&mut self, scale: f32) {
+        let root = Mat4::from_scale(Vec3::new(scale, scale, 1.0));
+        let inverse = self.root.inverse();
+
+        for matrix in &mut self.matrices {
+            *matrix = root * inverse * *matrix;
+        }
+
+        self.root = root;
+    }
+
+    fn push(&mut self, transform: Mat4) {
+        self.matrices.push(self.current() * transform);
+    }
+
+    fn pop(&mut self) {
+        self.matrices.pop();
+    }
+
+    fn current(&self) -> Mat4 {
+        self.matrices.last().copied().unwrap_or(self.root)
+    }
 }
 
 pub struct UiContext<'a> {
     window_size: Size2D,
+    scale: f32,
     bounds: Rect2D,
     pub game_loop: &'a mut GameLoop,
     display: &'a WindowDisplay,
     frame: &'a mut Frame,
-    rectangles: Vec<Rectangle>,
+    /// Rectangles grouped by clip area, in drawing order.
+    rectangles: Vec<(Option<Rect2D>, Vec<Rectangle>)>,
     texts: Vec<Text>,
-    clip: Option<Rect2D>,
-    matrix: Option<Mat4>,
+    clip: ClipStack,
+    matrix: TransformStack,
 }
 
 impl<'a> UiContext<'a> {
@@ -37,17 +115,32 @@ impl<'a> UiContext<'a> {
 
         Self {
             window_size: Size2D::new(width as f32, height as f32),
+            scale: 1.0,
             bounds: Rect2D::new(Point2D::ZERO, Size2D::new(width as f32, height as f32)),
             game_loop,
             display,
             frame,
             rectangles: Vec::new(),
             texts: Vec::new(),
-            clip: None,
-            matrix: None,
+            clip: ClipStack::default(),
+            matrix: TransformStack::default(),
         }
     }
 
+    /// Sets the factor by which sizes, positions and font sizes of the UI are
+    /// multiplied, making UI bounds that much smaller in UI units.
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        self.scale = scale;
+        self.bounds = Rect2D::new(
+            Point2D::ZERO,
+            Size2D::new(
+                self.window_size.width / scale,
+                self.window_size.height / scale,
+            ),
+        );
+        self.matrix.set_scale(scale);
+    }
+
     pub fn measure_text<F: AsRef<str>, T: AsRef<str>>(
         &mut self,
         font: F,
@@ -57,6 +150,17 @@ impl<'a> UiContext<'a> {
         self.game_loop.text_renderer.measure(font, text, size)
     }
 
+    pub fn measure_text_detailed<F: AsRef<str>, T: AsRef<str>>(
+        &mut self,
+        font: F,
+        text: T,
+        size: f32,
+    ) -> Option<TextMetrics> {
+        self.game_loop
+            .text_renderer
+            .measure_detailed(font, text, size)
+    }
+
     pub fn draw_text<F: Into<String>, T: Into<String>>(
         &mut self,
         position: Point2D,
@@ -64,6 +168,7 @@ impl<'a> UiContext<'a> {
         text: T,
         size: f32,
         color: Color,
+        effect: Option<TextEffect>,
     ) {
         self.texts.push(Text {
             position,
@@ -71,51 +176,93 @@ impl<'a> UiContext<'a> {
             data: text.into(),
             size,
             color,
-            clip: self.clip,
-            matrix: self.matrix,
+            effect,
+            clip: self.clip.current(),
+            matrix: self.matrix.current(),
         });
     }
 
-    pub const fn add_transform(&mut self, transform: Mat4) {
-        self.matrix.replace(transform);
+    /// Applies the transform to everything drawn until the matching
+    /// [`UiContext::remove_transform`], on top of the enclosing transforms.
+    pub fn add_transform(&mut self, transform: Mat4) {
+        self.matrix.push(transform);
     }
 
-    pub const fn remove_transform(&mut self) {
-        self.matrix.take();
+    pub fn remove_transform(&mut self) {
+        self.matrix.pop();
     }
 
     pub fn draw_rect(&mut self, position: Point2D, size: Size2D, color: Color) {
-        self.rectangles.push(
-            Rectangle::new(position.x, position.y, size.width, size.height, color)
-                .with_matrix(self.matrix),
-        );
+        let rect = Rectangle::new(position.x, position.y, size.width, size.height, color)
+            .with_matrix(Some(self.matrix.current()));
+
+        let current_clip = self.clip.current();
+
+        match self.rectangles.last_mut() {
+            Some((clip, rects)) if *clip == current_clip => rects.push(rect),
+            _ => self.rectangles.push((current_clip, vec![rect])),
+        }
+    }
+
+    /// Converts clip area in window coordinates to scissor rectangle in
+    /// framebuffer space, whose origin is at the bottom left corner.
+    fn to_scissor(window_size: Size2D, area: Rect2D) -> Rect {
+        Rect {
+            left: area.origin.x.floor() as u32,
+            bottom: (window_size.height - area.origin.y - area.size.height).floor() as u32,
+            width: area.size.width.floor() as u32,
+            height: area.size.height.floor() as u32,
+        }
+    }
+
+    /// Plots the given curve inside of the area described by `position` and
+    /// `size`, with `t` going left to right and the eased value bottom to top.
+    pub fn draw_curve(
+        &mut self,
+        position: Point2D,
+        size: Size2D,
+        curve: Curve,
+        thickness: f32,
+        color: Color,
+    ) {
+        let steps = size.width.max(1.0) as usize;
+
+        for (t, eased) in curve.sample(steps) {
+            self.draw_rect(
+                Point2D::new(
+                    t.mul_add(size.width, position.x),
+                    (1.0 - eased).mul_add(size.height, position.y),
+                ) - Point2D::ONE.to_vector() * (thickness / 2.0),
+                Size2D::ONE * thickness,
+                color,
+            );
+        }
     }
 
     pub fn finish(self) {
-        self.game_loop.shape_renderer.draw_rects(
-            self.frame,
-            self.display,
-            &self.rectangles,
-            &mut self.game_loop.debugging.draw_calls,
-            &mut self.game_loop.debugging.vertices,
-        );
+        for (clip, rects) in &self.rectangles {
+            self.game_loop.shape_renderer.draw_rects(
+                self.frame,
+                self.display,
+                rects,
+                clip.map(|area| Self::to_scissor(self.window_size, area)),
+                &mut self.game_loop.debugging.draw_calls,
+                &mut self.game_loop.debugging.vertices,
+            );
+        }
 
         for text in self.texts {
             self.game_loop.text_renderer.render(
                 self.frame,
-                &(self.game_loop.window_matrix * text.matrix.unwrap_or_default()),
+                &(self.game_loop.window_matrix * text.matrix),
                 text.position,
                 text.font,
                 text.data,
                 text.size,
                 text.color,
-                text.clip.map(|area| Rect {
-                    left: area.origin.x.floor() as u32,
-                    bottom: (self.window_size.height - area.origin.y - area.size.height).floor()
-                        as u32,
-                    width: area.size.width.floor() as u32,
-                    height: area.size.height.floor() as u32,
-                }),
+                text.effect,
+                text.clip
+                    .map(|area| Self::to_scissor(self.window_size, area)),
                 &mut self.game_loop.debugging.draw_calls,
             );
         }
@@ -130,11 +277,14 @@ impl<'a> UiContext<'a> {
     }
 
     pub fn clipped<F: FnOnce(&mut UiContext, Rect2D)>(&mut self, bounds: Rect2D, func: F) {
-        self.clip.replace(bounds);
+        self.clip.push(Rect2D::new(
+            Point2D::new(bounds.origin.x * self.scale, bounds.origin.y * self.scale),
+            bounds.size * self.scale,
+        ));
 
         func(self, self.bounds);
 
-        self.clip.take();
+        self.clip.pop();
     }
 
     pub fn bounds<F: FnOnce(&mut UiContext, Rect2D)>(&mut self, bounds: Rect2D, func: F) {
@@ -158,3 +308,135 @@ impl<'a> UiContext<'a> {
         self.bounds.size += Size2D::ONE * value * 2.0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::{Mat4, Vec3};
+    use glium::Rect;
+    use meralus_app::renderers::Rectangle;
+    use meralus_shared::{Color, Point2D, Rect2D, Size2D};
+
+    use super::{ClipStack, TransformStack, UiContext};
+
+    #[test]
+    fn test_clip_to_scissor() {
+        let window_size = Size2D::new(800.0, 600.0);
+
+        assert_eq!(
+            UiContext::to_scissor(
+                window_size,
+                Rect2D::new(Point2D::new(100.0, 50.0), Size2D::new(200.5, 120.0))
+            ),
+            Rect {
+                left: 100,
+                bottom: 430,
+                width: 200,
+                height: 120,
+            }
+        );
+        assert_eq!(
+            UiContext::to_scissor(window_size, Rect2D::new(Point2D::ZERO, window_size)),
+            Rect {
+                left: 0,
+                bottom: 0,
+                width: 800,
+                height: 600,
+            }
+        );
+    }
+
+    #[test]
+    fn test_nested_clips() {
+        let mut clip = ClipStack::default();
+
+        assert_eq!(clip.current(), None);
+
+        clip.push(Rect2D::new(
+            Point2D::new(0.0, 0.0),
+            Size2D::new(100.0, 100.0),
+        ));
+        clip.push(Rect2D::new(
+            Point2D::new(50.0, 25.0),
+            Size2D::new(100.0, 50.0),
+        ));
+
+        assert_eq!(
+            clip.current(),
+            Some(Rect2D::new(
+                Point2D::new(50.0, 25.0),
+                Size2D::new(50.0, 50.0)
+            ))
+        );
+
+        clip.push(Rect2D::new(
+            Point2D::new(200.0, 0.0),
+            Size2D::new(10.0, 10.0),
+        ));
+
+        assert_eq!(
+            clip.current().map(|area| area.size),
+            Some(Size2D::new(0.0, 0.0))
+        );
+
+        clip.pop();
+        clip.pop();
+
+        assert_eq!(
+            clip.current(),
+            Some(Rect2D::new(
+                Point2D::new(0.0, 0.0),
+                Size2D::new(100.0, 100.0)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_nested_transforms() {
+        let mut matrix = TransformStack::default();
+        let scale = Mat4::from_scale(Vec3::splat(2.0));
+        let translation = Mat4::from_translation(Vec3::new(10.0, 5.0, 0.0));
+
+        assert_eq!(matrix.current(), Mat4::IDENTITY);
+
+        matrix.push(scale);
+        matrix.push(translation);
+
+        assert_eq!(matrix.current(), scale * translation);
+        assert_eq!(
+            matrix.current().transform_point3(Vec3::ZERO),
+            Vec3::new(20.0, 10.0, 0.0)
+        );
+
+        matrix.pop();
+
+        assert_eq!(matrix.current(), scale);
+
+        matrix.pop();
+
+        assert_eq!(matrix.current(), Mat4::IDENTITY);
+    }
+
+    #[test]
+    fn test_ui_scale() {
+        let mut matrix = TransformStack::default();
+        let rect = Rectangle::new(10.0, 20.0, 48.0, 48.0, Color::WHITE);
+
+        matrix.set_scale(2.0);
+
+        let [top_left, .., bottom_right] = rect
+            .with_matrix(Some(matrix.current()))
+            .as_instance()
+            .corners();
+
+        assert_eq!(top_left, Vec3::new(20.0, 40.0, 0.0));
+        assert_eq!(bottom_right - top_left, Vec3::new(96.0, 96.0, 0.0));
+
+        matrix.push(Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)));
+        matrix.set_scale(1.0);
+
+        assert_eq!(
+            matrix.current(),
+            Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0))
+        );
+    }
+}
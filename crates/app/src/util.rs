@@ -5,7 +5,7 @@ use meralus_engine::KeyCode;
 use meralus_shared::{Color, Cube3D};
 use meralus_world::Face;
 
-use crate::{Camera, KeyboardController, renderers::Line};
+use crate::{camera::Camera, keyboard::KeyboardController, renderers::Line};
 
 const AMBIENT_OCCLUSION_VALUES: [f32; 4] = [0.4, 0.55, 0.75, 1.0];
 
@@ -185,7 +185,7 @@ impl VecExt<f64> for DVec3 {
         if d2 * d2 < 1.0000000116860974E-7 {
             None
         } else {
-            let d3 = (z - self.x) / d2;
+            let d3 = (z - self.z) / d2;
 
             if (0.0..=1.0).contains(&d3) {
                 Some(Self::new(
@@ -216,7 +216,7 @@ pub fn format_bytes(bytes: usize) -> String {
     format!("{value:.2}GB")
 }
 
-pub fn cube_outline(Cube3D { origin, size }: Cube3D) -> [Line; 12] {
+pub fn cube_outline(Cube3D { origin, size }: Cube3D, color: Color) -> [Line; 12] {
     [
         [[0.0, 0.0, 0.0], [0.0, size.height, 0.0]],
         [[size.width, 0.0, 0.0], [size.width, size.height, 0.0]],
@@ -247,7 +247,51 @@ pub fn cube_outline(Cube3D { origin, size }: Cube3D) -> [Line; 12] {
         Line::new(
             origin.to_raw() + Vec3::from_array(start),
             origin.to_raw() + Vec3::from_array(end),
-            Color::BLUE,
+            color,
         )
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use glam::DVec3;
+    use meralus_shared::{Color, Cube3D, Point3D, Size3D};
+
+    use super::{VecExt, cube_outline};
+
+    #[test]
+    fn test_cube_outline_color() {
+        let cube = Cube3D::new(Point3D::new(1.0, 2.0, 3.0), Size3D::new(1.0, 1.0, 1.0));
+
+        for color in [Color::WHITE, Color::RED, Color::BLUE] {
+            assert!(
+                cube_outline(cube, color)
+                    .iter()
+                    .all(|line| line.color == color)
+            );
+        }
+    }
+
+    #[test]
+    fn test_intermediate_values() {
+        let start = DVec3::new(1.0, 2.0, 3.0);
+        let end = DVec3::new(5.0, 10.0, 11.0);
+
+        assert_eq!(
+            start.get_intermediate_with_x_value(end, 3.0),
+            Some(DVec3::new(3.0, 6.0, 7.0))
+        );
+        assert_eq!(
+            start.get_intermediate_with_y_value(end, 4.0),
+            Some(DVec3::new(2.0, 4.0, 5.0))
+        );
+        assert_eq!(
+            start.get_intermediate_with_z_value(end, 9.0),
+            Some(DVec3::new(4.0, 8.0, 9.0))
+        );
+
+        assert_eq!(start.get_intermediate_with_x_value(end, 6.0), None);
+        assert_eq!(start.get_intermediate_with_y_value(end, 1.0), None);
+        assert_eq!(start.get_intermediate_with_z_value(end, 12.0), None);
+    }
+}
@@ -0,0 +1,211 @@
+use glam::Vec3;
+use meralus_shared::{Color, Lerp};
+
+use crate::particles::{Particle, ParticleSystem};
+
+/// Time it takes to fully switch from one weather to another, in seconds.
+const TRANSITION_DURATION: f32 = 5.0;
+/// Half of the side of the area around the player precipitation falls in.
+const PRECIPITATION_RADIUS: f32 = 16.0;
+/// Height above the player precipitation particles are emitted at.
+const PRECIPITATION_HEIGHT: f32 = 20.0;
+/// Color the sky is tinted with during precipitation.
+const FOG_COLOR: Color = Color::from_hsl(220.0, 0.08, 0.55);
+
+// Fractional parts of the plastic number powers, giving an evenly spread
+// sequence of positions without any random number generator.
+const SPREAD_X: f32 = 0.754_877_7;
+const SPREAD_Z: f32 = 0.569_840_3;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+}
+
+impl Weather {
+    /// Returns the weather following this one, cycling through all of them.
+    #[must_use]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Clear => Self::Rain,
+            Self::Rain => Self::Snow,
+            Self::Snow => Self::Clear,
+        }
+    }
+
+    /// Sky light multiplier at full intensity of the weather.
+    const fn light_factor(self) -> f32 {
+        match self {
+            Self::Clear => 1.0,
+            Self::Rain => 0.7,
+            Self::Snow => 0.85,
+        }
+    }
+
+    /// How much the sky is tinted with fog at full intensity of the weather.
+    const fn fog(self) -> f32 {
+        match self {
+            Self::Clear => 0.0,
+            Self::Rain => 0.6,
+            Self::Snow => 0.4,
+        }
+    }
+
+    /// Precipitation particles emitted every second.
+    const fn precipitation_rate(self) -> f32 {
+        match self {
+            Self::Clear => 0.0,
+            Self::Rain => 200.0,
+            Self::Snow => 60.0,
+        }
+    }
+
+    const fn particle(self, position: Vec3) -> Particle {
+        match self {
+            Self::Clear | Self::Rain => Particle {
+                position,
+                velocity: Vec3::new(0.0, -12.0, 0.0),
+                gravity: 1.0,
+                life: 2.0,
+                size: 0.05,
+                color: Color::from_hsl(210.0, 0.6, 0.7),
+            },
+            // Snowflakes fall with a constant speed instead of accelerating.
+            Self::Snow => Particle {
+                position,
+                velocity: Vec3::new(0.0, -2.0, 0.0),
+                gravity: 0.0,
+                life: 10.0,
+                size: 0.08,
+                color: Color::WHITE,
+            },
+        }
+    }
+}
+
+/// Current weather along with the transition from the previous one.
+///
+/// Snow falls everywhere for now, as there are no biomes to limit it to cold
+/// ones.
+#[derive(Debug, Default)]
+pub struct WeatherState {
+    previous: Weather,
+    current: Weather,
+    /// Transition progress from the previous weather in the range `0.0..=1.0`.
+    transition: f32,
+    /// Fraction of a particle carried over to the next update.
+    pending: f32,
+    emitted: u32,
+}
+
+impl WeatherState {
+    pub const fn current(&self) -> Weather {
+        self.current
+    }
+
+    /// Starts a smooth transition to the given weather.
+    pub fn set(&mut self, weather: Weather) {
+        if weather != self.current {
+            self.previous = self.current;
+            self.current = weather;
+            self.transition = 0.0;
+        }
+    }
+
+    /// Sky light multiplier, which is `1.0` for clear weather.
+    pub fn light_factor(&self) -> f32 {
+        self.previous
+            .light_factor()
+            .lerp(&self.current.light_factor(), self.transition)
+    }
+
+    /// Tints sky color with fog according to how strong precipitation is.
+    pub fn tint_sky(&self, color: Color) -> Color {
+        let fog = self
+            .previous
+            .fog()
+            .lerp(&self.current.fog(), self.transition);
+
+        color.lerp(&FOG_COLOR, fog)
+    }
+
+    /// Advances the transition and emits precipitation particles above the
+    /// given position.
+    #[allow(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss
+    )]
+    pub fn update(&mut self, delta: f32, particles: &mut ParticleSystem, center: Vec3) {
+        self.transition = (self.transition + delta / TRANSITION_DURATION).min(1.0);
+
+        let (weather, intensity) = if self.current.precipitation_rate() > 0.0 {
+            (self.current, self.transition)
+        } else {
+            (self.previous, 1.0 - self.transition)
+        };
+
+        self.pending += weather.precipitation_rate() * intensity * delta;
+
+        let count = self.pending.floor();
+
+        self.pending -= count;
+
+        for _ in 0..count as u32 {
+            self.emitted = self.emitted.wrapping_add(1);
+
+            let index = self.emitted as f32;
+            let offset = Vec3::new(
+                (index * SPREAD_X).fract().mul_add(2.0, -1.0),
+                0.0,
+                (index * SPREAD_Z).fract().mul_add(2.0, -1.0),
+            ) * PRECIPITATION_RADIUS;
+
+            if !particles.emit(weather.particle(center + offset + Vec3::Y * PRECIPITATION_HEIGHT)) {
+                self.pending = 0.0;
+
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+
+    use super::{Weather, WeatherState};
+    use crate::{particles::ParticleSystem, renderers::VoxelRenderer};
+
+    #[test]
+    fn test_rain() {
+        let mut weather = WeatherState::default();
+        let mut particles = ParticleSystem::new(1024);
+
+        weather.update(1.0, &mut particles, Vec3::ZERO);
+
+        assert!(particles.particles().is_empty());
+        assert!((weather.light_factor() - 1.0).abs() < f32::EPSILON);
+
+        weather.set(Weather::Rain);
+
+        for _ in 0..60 {
+            weather.update(0.1, &mut particles, Vec3::ZERO);
+        }
+
+        assert!(
+            VoxelRenderer::effective_light(15, 0, weather.light_factor())
+                < VoxelRenderer::effective_light(15, 0, 1.0)
+        );
+        assert!(!particles.particles().is_empty());
+        assert!(
+            particles
+                .particles()
+                .iter()
+                .all(|particle| particle.velocity.y < 0.0 && particle.position.y > 0.0)
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    f32::consts::FRAC_PI_4,
+    ffi::OsStr,
+    fmt, fs, io,
+    path::Path,
+    sync::Arc,
+};
+
+use glam::Vec3;
+
+/// Sound effects keyed by their ids, which are names of the files they were
+/// loaded from without an extension (e.g. `sounds/footstep.ogg` is
+/// `footstep`).
+#[derive(Default)]
+pub struct SoundRegistry {
+    sounds: HashMap<String, Arc<[u8]>>,
+}
+
+impl fmt::Debug for SoundRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.sounds.keys()).finish()
+    }
+}
+
+impl SoundRegistry {
+    /// Extensions of files that are treated as sounds.
+    pub const EXTENSIONS: &[&str] = &["ogg", "wav", "flac"];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every sound file directly inside of the directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or any of the sounds can't be read.
+    pub fn load<P: AsRef<Path>>(directory: P) -> io::Result<Self> {
+        let mut registry = Self::new();
+
+        for entry in fs::read_dir(directory)? {
+            registry.load_file(entry?.path())?;
+        }
+
+        Ok(registry)
+    }
+
+    /// Loads the file if it's a sound, replacing a sound with the same id.
+    /// Returns whether the file was loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sound can't be read.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> io::Result<bool> {
+        let path = path.as_ref();
+        let is_sound = path
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|extension| Self::EXTENSIONS.contains(&extension));
+
+        match path.file_stem() {
+            Some(id) if is_sound && path.is_file() => {
+                self.insert(id.to_string_lossy(), fs::read(path)?);
+
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    pub fn insert<I: Into<String>, D: Into<Arc<[u8]>>>(&mut self, id: I, data: D) {
+        self.sounds.insert(id.into(), data.into());
+    }
+
+    /// Returns encoded data of the sound with the given id.
+    pub fn get(&self, id: &str) -> Option<&Arc<[u8]>> {
+        self.sounds.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sounds.is_empty()
+    }
+}
+
+/// Position and orientation sounds placed in the world are heard from,
+/// usually matching the camera.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listener {
+    pub position: Vec3,
+    pub front: Vec3,
+    pub up: Vec3,
+}
+
+impl Default for Listener {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            front: Vec3::NEG_Z,
+            up: Vec3::Y,
+        }
+    }
+}
+
+impl Listener {
+    /// Distance at which sounds fade out completely.
+    pub const HEARING_DISTANCE: f32 = 32.0;
+
+    /// Returns volumes of the left and right channels for a sound at the
+    /// given position, fading it out linearly with distance.
+    pub fn channel_volumes(&self, position: Vec3) -> [f32; 2] {
+        let offset = position - self.position;
+        let volume = (1.0 - offset.length() / Self::HEARING_DISTANCE).clamp(0.0, 1.0);
+        let right = self.front.cross(self.up).normalize_or_zero();
+        let pan = offset.normalize_or_zero().dot(right);
+        // Equal-power panning keeps the loudness constant as the sound moves
+        // from one side to another.
+        let angle = (pan + 1.0) * FRAC_PI_4;
+
+        [volume * angle.cos(), volume * angle.sin()]
+    }
+}
+
+/// Plays sounds from the registry on the default output device.
+///
+/// Without the `audio` feature sounds are only looked up, but never played.
+pub struct Audio {
+    sounds: RefCell<SoundRegistry>,
+    listener: Cell<Listener>,
+    #[cfg(feature = "audio")]
+    output: Option<(rodio::OutputStream, rodio::OutputStreamHandle)>,
+}
+
+impl fmt::Debug for Audio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Audio")
+            .field("sounds", &self.sounds)
+            .field("listener", &self.listener)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Audio {
+    pub fn new() -> Self {
+        Self {
+            sounds: RefCell::new(SoundRegistry::new()),
+            listener: Cell::new(Listener::default()),
+            // Missing output device shouldn't prevent the game from running.
+            #[cfg(feature = "audio")]
+            output: rodio::OutputStream::try_default().ok(),
+        }
+    }
+
+    pub fn set_sounds(&self, sounds: SoundRegistry) {
+        self.sounds.replace(sounds);
+    }
+
+    pub fn set_listener(&self, listener: Listener) {
+        self.listener.set(listener);
+    }
+
+    /// Starts playing the sound without waiting for it to finish. Returns
+    /// whether the sound has started playing.
+    pub fn play(&self, id: &str) -> bool {
+        self.sounds
+            .borrow()
+            .get(id)
+            .is_some_and(|data| self.play_data(data, None))
+    }
+
+    /// Same as [`Audio::play`], but attenuates and pans the sound according
+    /// to its position relative to the listener.
+    pub fn play_at(&self, id: &str, position: Vec3) -> bool {
+        let volumes = self.listener.get().channel_volumes(position);
+
+        // Sounds out of hearing distance aren't worth decoding.
+        volumes.iter().any(|volume| *volume > 0.0)
+            && self
+                .sounds
+                .borrow()
+                .get(id)
+                .is_some_and(|data| self.play_data(data, Some(volumes)))
+    }
+
+    #[cfg(feature = "audio")]
+    fn play_data(&self, data: &Arc<[u8]>, volumes: Option<[f32; 2]>) -> bool {
+        use rodio::{Source, source::ChannelVolume};
+
+        let Some((_, handle)) = &self.output else {
+            return false;
+        };
+
+        let Ok(source) = rodio::Decoder::new(io::Cursor::new(Arc::clone(data))) else {
+            return false;
+        };
+
+        match volumes {
+            Some(volumes) => handle
+                .play_raw(ChannelVolume::new(
+                    source.convert_samples::<f32>(),
+                    volumes.to_vec(),
+                ))
+                .is_ok(),
+            None => handle.play_raw(source.convert_samples()).is_ok(),
+        }
+    }
+
+    #[cfg(not(feature = "audio"))]
+    #[allow(clippy::unused_self)]
+    const fn play_data(&self, _: &Arc<[u8]>, _: Option<[f32; 2]>) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use glam::Vec3;
+
+    use super::{Audio, Listener, SoundRegistry};
+
+    #[test]
+    fn test_sound_registry() {
+        let directory = env::temp_dir().join(format!("meralus-sounds-{}", std::process::id()));
+
+        fs::create_dir_all(&directory).unwrap();
+
+        for (file, data) in [
+            ("footstep.ogg", b"step".as_slice()),
+            ("block_break.wav", b"break"),
+            ("credits.txt", b"text"),
+        ] {
+            fs::write(directory.join(file), data).unwrap();
+        }
+
+        let registry = SoundRegistry::load(&directory).unwrap();
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(
+            registry.get("footstep").map(|data| &data[..]),
+            Some(b"step".as_slice())
+        );
+        assert_eq!(
+            registry.get("block_break").map(|data| &data[..]),
+            Some(b"break".as_slice())
+        );
+        assert!(registry.get("credits").is_none());
+
+        let audio = Audio::new();
+
+        audio.set_sounds(registry);
+
+        // Sounds that aren't registered are never played.
+        assert!(!audio.play("block_place"));
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_listener_channel_volumes() {
+        let listener = Listener::default();
+
+        let [left, right] = listener.channel_volumes(Vec3::new(-4.0, 0.0, 0.0));
+
+        assert!(left > right);
+
+        let [left, right] = listener.channel_volumes(Vec3::new(0.0, 0.0, -2.0));
+
+        assert!((left - right).abs() < 1e-6);
+
+        let near = listener.channel_volumes(Vec3::new(0.0, 0.0, -2.0));
+        let far = listener.channel_volumes(Vec3::new(0.0, 0.0, -20.0));
+
+        assert!(far[0] < near[0] && far[1] < near[1]);
+        assert!(
+            listener
+                .channel_volumes(Vec3::new(0.0, 0.0, -Listener::HEARING_DISTANCE))
+                .iter()
+                .all(|volume| volume.abs() < f32::EPSILON)
+        );
+
+        // turning around swaps the sides
+        let listener = Listener {
+            front: Vec3::Z,
+            ..listener
+        };
+
+        let [left, right] = listener.channel_volumes(Vec3::new(-4.0, 0.0, 0.0));
+
+        assert!(left < right);
+    }
+}
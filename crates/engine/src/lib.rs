@@ -1,20 +1,24 @@
 #![allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
 
+mod audio;
+mod recording;
+
 use std::{
     num::NonZeroU32,
     time::{Duration, Instant},
 };
 
-use glam::{UVec2, Vec2, uvec2, vec2};
+use glam::{UVec2, Vec2, Vec3, uvec2, vec2};
 use glium::Display;
 use glutin::{
-    config::ConfigTemplateBuilder,
-    context::{ContextApi, ContextAttributesBuilder},
+    config::{Config, ConfigTemplateBuilder},
+    context::{ContextApi, ContextAttributesBuilder, Robustness},
     display::GetGlDisplay,
     prelude::{GlDisplay, NotCurrentGlContext},
     surface::{SurfaceAttributesBuilder, WindowSurface},
 };
 use glutin_winit::DisplayBuilder;
+use serde::{Deserialize, Serialize};
 use winit::{
     application::ApplicationHandler,
     error::EventLoopError,
@@ -26,17 +30,28 @@ use winit::{
 };
 pub use winit::{event::MouseButton, keyboard::KeyCode, window::CursorGrabMode};
 
+use self::audio::Audio;
+pub use self::{
+    audio::{Listener, SoundRegistry},
+    recording::{InputEvent, InputRecording},
+};
+
 pub type WindowDisplay = Display<WindowSurface>;
 
 #[derive(Debug, Clone, Copy)]
 pub struct WindowContext<'a> {
     event_loop: &'a ActiveEventLoop,
     window: &'a Window,
+    audio: &'a Audio,
 }
 
 impl<'a> WindowContext<'a> {
-    const fn new(event_loop: &'a ActiveEventLoop, window: &'a Window) -> Self {
-        Self { event_loop, window }
+    const fn new(event_loop: &'a ActiveEventLoop, window: &'a Window, audio: &'a Audio) -> Self {
+        Self {
+            event_loop,
+            window,
+            audio,
+        }
     }
 
     #[allow(clippy::missing_panics_doc)]
@@ -51,9 +66,31 @@ impl<'a> WindowContext<'a> {
     pub fn close_window(&self) {
         self.event_loop.exit();
     }
+
+    /// Replaces sounds available for playing.
+    pub fn set_sounds(&self, sounds: SoundRegistry) {
+        self.audio.set_sounds(sounds);
+    }
+
+    /// Plays the sound with the given id, returning whether it has started
+    /// playing. Sounds are never played without the `audio` feature.
+    pub fn play_sound(&self, id: &str) -> bool {
+        self.audio.play(id)
+    }
+
+    /// Sets where sounds placed in the world are heard from.
+    pub fn set_listener(&self, listener: Listener) {
+        self.audio.set_listener(listener);
+    }
+
+    /// Plays the sound attenuated and panned according to its position
+    /// relative to the listener, returning whether it has started playing.
+    pub fn play_sound_at(&self, id: &str, position: Vec3) -> bool {
+        self.audio.play_at(id, position)
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[allow(clippy::struct_excessive_bools)]
 pub struct KeyboardModifiers {
     alt_key: bool,
@@ -80,18 +117,162 @@ pub trait State {
     // &WindowDisplay, delta: f32) {}
     fn update(&mut self, context: WindowContext, display: &WindowDisplay, delta: Duration) {}
     fn render(&mut self, display: &WindowDisplay, delta: Duration);
+
+    /// Called when the GL context is lost, after which every GPU resource
+    /// created with the old display is unusable.
+    fn on_context_lost(&mut self) {}
+    /// Called with the newly created display after the GL context was lost,
+    /// so that GPU resources can be created again.
+    fn on_context_restored(&mut self, display: &WindowDisplay) {}
+    /// Called once before the application exits, which is the last chance
+    /// to persist anything.
+    fn on_exit(&mut self) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ContextEvent {
+    Lost,
+    Restored,
+}
+
+/// Replaces the display with a new one if its context was lost, notifying
+/// about the loss before and about the restoration after that.
+fn recover_context<D, F: FnMut(ContextEvent, &D)>(
+    display: &mut D,
+    is_lost: bool,
+    recreate: impl FnOnce() -> D,
+    mut notify: F,
+) -> bool {
+    if !is_lost {
+        return false;
+    }
+
+    notify(ContextEvent::Lost, display);
+
+    *display = recreate();
+
+    notify(ContextEvent::Restored, display);
+
+    true
+}
+
+/// Creates GL context and window surface, preferring a context that reports
+/// GPU resets as context loss.
+fn create_display(window: &Window, gl_config: &Config) -> WindowDisplay {
+    let window_handle = window.window_handle().expect("failed to get window handle");
+    let robust_context_attrs = ContextAttributesBuilder::new()
+        .with_robustness(Robustness::RobustLoseContextOnReset)
+        .build(Some(window_handle.into()));
+    let context_attrs = ContextAttributesBuilder::new().build(Some(window_handle.into()));
+    let fallback_context_attrs = ContextAttributesBuilder::new()
+        .with_context_api(ContextApi::Gles(None))
+        .build(Some(window_handle.into()));
+
+    let gl_context = unsafe {
+        gl_config
+            .display()
+            .create_context(gl_config, &robust_context_attrs)
+            .or_else(|_| {
+                gl_config
+                    .display()
+                    .create_context(gl_config, &context_attrs)
+            })
+            .unwrap_or_else(|_| {
+                gl_config
+                    .display()
+                    .create_context(gl_config, &fallback_context_attrs)
+                    .expect("failed to create context")
+            })
+    };
+
+    let (width, height): (u32, u32) = window.inner_size().into();
+    // Surface can't have zero size, so it gets resized once the window
+    // is restored.
+    let (width, height) = surface_size(width, height).unwrap_or((NonZeroU32::MIN, NonZeroU32::MIN));
+    let attrs =
+        SurfaceAttributesBuilder::<WindowSurface>::new().build(window_handle.into(), width, height);
+
+    let surface = unsafe {
+        gl_config
+            .display()
+            .create_window_surface(gl_config, &attrs)
+            .expect("failed to create surface")
+    };
+
+    let current_context = gl_context
+        .make_current(&surface)
+        .expect("failed to obtain opengl context");
+
+    Display::from_context_surface(current_context, surface)
+        .expect("failed to create display from context and surface")
+}
+
+/// Returns size of the window surface, or `None` if the window has no area,
+/// which happens when it's minimized on some platforms.
+fn surface_size(width: u32, height: u32) -> Option<(NonZeroU32, NonZeroU32)> {
+    NonZeroU32::new(width).zip(NonZeroU32::new(height))
+}
+
+/// Resizes the window surface with the given size unless the window has no
+/// area, returning whether it's minimized.
+fn handle_resize<F: FnOnce(NonZeroU32, NonZeroU32)>(width: u32, height: u32, resize: F) -> bool {
+    let Some((width, height)) = surface_size(width, height) else {
+        return true;
+    };
+
+    resize(width, height);
+
+    false
+}
+
+/// Runs the exit hook on the window, dropping it right away so that the hook
+/// can't run twice.
+fn exit_window<W, F: FnOnce(&mut W)>(window: &mut Option<W>, on_exit: F) {
+    if let Some(mut window) = window.take() {
+        on_exit(&mut window);
+    }
 }
 
 pub struct ApplicationWindow<T: State> {
     state: T,
     window: Window,
     display: WindowDisplay,
+    gl_config: Config,
+    audio: Audio,
+    /// Whether the window has no area, in which case nothing is rendered.
+    minimized: bool,
     last_time: Option<Instant>,
     delta: Duration,
 }
 
+/// Where input handled by the application comes from.
+enum InputMode {
+    Live,
+    /// Input is handled as usual, but also recorded, with frames using the
+    /// fixed delta of the recording.
+    Recording(InputRecording),
+    /// Live input is ignored and recorded frames are dispatched instead,
+    /// until they run out.
+    Replaying {
+        recording: InputRecording,
+        frame: usize,
+    },
+}
+
+impl InputMode {
+    const fn fixed_delta(&self) -> Option<Duration> {
+        match self {
+            Self::Live => None,
+            Self::Recording(recording) | Self::Replaying { recording, .. } => {
+                Some(recording.delta())
+            }
+        }
+    }
+}
+
 pub struct Application<T: State> {
     window: Option<ApplicationWindow<T>>,
+    input: InputMode,
 }
 
 impl<T: State> Application<T> {
@@ -106,11 +287,54 @@ impl<T: State> Application<T> {
 
         Ok(())
     }
+
+    /// Creates application recording its input, with every frame taking
+    /// exactly `delta` so that the session can be replayed deterministically.
+    pub const fn recording(delta: Duration) -> Self {
+        Self {
+            window: None,
+            input: InputMode::Recording(InputRecording::new(delta)),
+        }
+    }
+
+    /// Creates application that ignores live input and replays the recorded
+    /// one instead.
+    pub const fn replaying(recording: InputRecording) -> Self {
+        Self {
+            window: None,
+            input: InputMode::Replaying {
+                recording,
+                frame: 0,
+            },
+        }
+    }
+
+    /// Returns input recorded so far, if the application is recording it.
+    pub const fn recorded_input(&self) -> Option<&InputRecording> {
+        match &self.input {
+            InputMode::Recording(recording) => Some(recording),
+            _ => None,
+        }
+    }
+
+    fn handle_input(&mut self, event: InputEvent) {
+        match &mut self.input {
+            InputMode::Live => {}
+            InputMode::Recording(recording) => recording.push(event),
+            InputMode::Replaying { .. } => return,
+        }
+
+        self.window
+            .inspect_mut(|window| event.dispatch(&mut window.state));
+    }
 }
 
 impl<T: State> Default for Application<T> {
     fn default() -> Self {
-        Self { window: None }
+        Self {
+            window: None,
+            input: InputMode::Live,
+        }
     }
 }
 
@@ -130,56 +354,44 @@ impl<T: State> ApplicationWindow<T> {
             .expect("failed to build display");
 
         let window = window.expect("failed to get window");
-
-        let window_handle = window.window_handle().expect("failed to get window handle");
-        let context_attrs = ContextAttributesBuilder::new().build(Some(window_handle.into()));
-        let fallback_context_attrs = ContextAttributesBuilder::new()
-            .with_context_api(ContextApi::Gles(None))
-            .build(Some(window_handle.into()));
-
-        let gl_context = unsafe {
-            gl_config
-                .display()
-                .create_context(&gl_config, &context_attrs)
-                .unwrap_or_else(|_| {
-                    gl_config
-                        .display()
-                        .create_context(&gl_config, &fallback_context_attrs)
-                        .expect("failed to create context")
-                })
-        };
-
         let (width, height): (u32, u32) = window.inner_size().into();
-        let attrs = SurfaceAttributesBuilder::<WindowSurface>::new().build(
-            window_handle.into(),
-            NonZeroU32::new(width).expect("failed to create window width"),
-            NonZeroU32::new(height).expect("failed to create window height"),
-        );
-
-        let surface = unsafe {
-            gl_config
-                .display()
-                .create_window_surface(&gl_config, &attrs)
-                .expect("failed to create surface")
-        };
-
-        let current_context = gl_context
-            .make_current(&surface)
-            .expect("failed to obtain opengl context");
-
-        let display = Display::from_context_surface(current_context, surface)
-            .expect("failed to create display from context and surface");
+        let display = create_display(&window, &gl_config);
+        let audio = Audio::new();
 
         Self {
-            state: T::new(WindowContext::new(event_loop, &window), &display),
+            state: T::new(WindowContext::new(event_loop, &window, &audio), &display),
             window,
             display,
+            gl_config,
+            audio,
+            minimized: surface_size(width, height).is_none(),
             last_time: None,
             // tick_acceleration: Duration::ZERO,
             // fixed_acceleration: Duration::ZERO,
             delta: Duration::ZERO,
         }
     }
+
+    fn recover_context_loss(&mut self) {
+        let is_lost = self.display.is_context_lost();
+        let Self {
+            state,
+            window,
+            display,
+            gl_config,
+            ..
+        } = self;
+
+        recover_context(
+            display,
+            is_lost,
+            || create_display(window, gl_config),
+            |event, display| match event {
+                ContextEvent::Lost => state.on_context_lost(),
+                ContextEvent::Restored => state.on_context_restored(display),
+            },
+        );
+    }
 }
 
 trait InspectMut<T> {
@@ -203,36 +415,42 @@ impl<T: State> ApplicationHandler for Application<T> {
         self.window.take();
     }
 
+    fn exiting(&mut self, _: &ActiveEventLoop) {
+        exit_window(&mut self.window, |window| window.state.on_exit());
+    }
+
     fn window_event(&mut self, _: &ActiveEventLoop, _: WindowId, event: WindowEvent) {
         match event {
             WindowEvent::Resized(physical_size) => self.window.inspect_mut(move |window| {
-                window.display.resize(physical_size.into());
-
-                window.state.handle_window_resize(
-                    uvec2(physical_size.width, physical_size.height),
-                    window.window.scale_factor(),
+                window.minimized = handle_resize(
+                    physical_size.width,
+                    physical_size.height,
+                    |width, height| {
+                        window.display.resize((width.get(), height.get()));
+
+                        window.state.handle_window_resize(
+                            uvec2(width.get(), height.get()),
+                            window.window.scale_factor(),
+                        );
+                    },
                 );
             }),
             WindowEvent::ModifiersChanged(modifiers) => {
                 let state = modifiers.state();
 
-                self.window.inspect_mut(move |window| {
-                    window.state.handle_keyboard_modifiers(KeyboardModifiers {
-                        alt_key: state.alt_key(),
-                        control_key: state.control_key(),
-                        shift_key: state.shift_key(),
-                        super_key: state.super_key(),
-                    });
-                });
+                self.handle_input(InputEvent::Modifiers(KeyboardModifiers {
+                    alt_key: state.alt_key(),
+                    control_key: state.control_key(),
+                    shift_key: state.shift_key(),
+                    super_key: state.super_key(),
+                }));
             }
             WindowEvent::KeyboardInput { event, .. } => {
-                if let PhysicalKey::Code(code) = event.physical_key {
-                    self.window.inspect_mut(|window| {
-                        window.state.handle_keyboard_input(
-                            code,
-                            event.state.is_pressed(),
-                            event.repeat,
-                        );
+                if let PhysicalKey::Code(key) = event.physical_key {
+                    self.handle_input(InputEvent::Key {
+                        key,
+                        is_pressed: event.state.is_pressed(),
+                        repeat: event.repeat,
                     });
                 }
             }
@@ -242,13 +460,12 @@ impl<T: State> ApplicationHandler for Application<T> {
                     MouseScrollDelta::PixelDelta(delta) => vec2(delta.x as f32, delta.y as f32),
                 };
 
-                self.window.inspect_mut(|window| {
-                    window.state.handle_mouse_wheel(delta);
-                });
+                self.handle_input(InputEvent::MouseWheel(delta));
             }
             WindowEvent::MouseInput { state, button, .. } => {
-                self.window.inspect_mut(|window| {
-                    window.state.handle_mouse_button(button, state.is_pressed());
+                self.handle_input(InputEvent::MouseButton {
+                    button,
+                    is_pressed: state.is_pressed(),
                 });
             }
             _ => {}
@@ -257,23 +474,48 @@ impl<T: State> ApplicationHandler for Application<T> {
 
     fn device_event(&mut self, _: &ActiveEventLoop, _: DeviceId, event: DeviceEvent) {
         if let DeviceEvent::MouseMotion { delta } = event {
-            self.window.inspect_mut(|window| {
-                window
-                    .state
-                    .handle_mouse_motion(vec2(delta.0 as f32, delta.1 as f32));
-            });
+            self.handle_input(InputEvent::MouseMotion(vec2(
+                delta.0 as f32,
+                delta.1 as f32,
+            )));
         }
     }
 
     fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
-        self.window.inspect_mut(|window| {
+        let Self { window, input } = self;
+
+        if let InputMode::Replaying { recording, frame } = input {
+            match recording.frame(*frame) {
+                Some(events) => {
+                    window.inspect_mut(|window| {
+                        for event in events {
+                            event.dispatch(&mut window.state);
+                        }
+                    });
+
+                    *frame += 1;
+                }
+                None => *input = InputMode::Live,
+            }
+        }
+
+        let fixed_delta = input.fixed_delta();
+
+        window.inspect_mut(|window| {
+            if let Some(delta) = fixed_delta {
+                window.delta = delta;
+            }
+
             window.state.update(
-                WindowContext::new(event_loop, &window.window),
+                WindowContext::new(event_loop, &window.window, &window.audio),
                 &window.display,
                 window.delta,
             );
 
-            window.state.render(&window.display, window.delta);
+            if !window.minimized {
+                window.recover_context_loss();
+                window.state.render(&window.display, window.delta);
+            }
 
             window.delta = window
                 .last_time
@@ -281,5 +523,108 @@ impl<T: State> ApplicationHandler for Application<T> {
 
             window.last_time.replace(Instant::now());
         });
+
+        if let InputMode::Recording(recording) = input {
+            recording.end_frame();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, num::NonZeroU32, rc::Rc, time::Duration};
+
+    use super::{
+        ContextEvent, State, WindowContext, WindowDisplay, exit_window, handle_resize,
+        recover_context, surface_size,
+    };
+
+    struct ExitCounter(Rc<Cell<usize>>);
+
+    impl State for ExitCounter {
+        fn new(_: WindowContext, _: &WindowDisplay) -> Self {
+            unreachable!()
+        }
+
+        fn render(&mut self, _: &WindowDisplay, _: Duration) {}
+
+        fn on_exit(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_zero_surface_size() {
+        assert_eq!(surface_size(0, 0), None);
+        assert_eq!(surface_size(0, 600), None);
+        assert_eq!(surface_size(800, 0), None);
+        assert_eq!(
+            surface_size(800, 600),
+            NonZeroU32::new(800).zip(NonZeroU32::new(600))
+        );
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut sizes = Vec::new();
+
+        for (width, height) in [(0, 0), (0, 600), (800, 0)] {
+            assert!(handle_resize(width, height, |width, height| {
+                sizes.push((width.get(), height.get()));
+            }));
+        }
+
+        assert!(sizes.is_empty());
+
+        assert!(!handle_resize(800, 600, |width, height| {
+            sizes.push((width.get(), height.get()));
+        }));
+        assert_eq!(sizes, [(800, 600)]);
+    }
+
+    #[test]
+    fn test_exit_hook() {
+        let exits = Rc::new(Cell::new(0));
+        let mut window = Some(ExitCounter(exits.clone()));
+
+        exit_window(&mut window, State::on_exit);
+
+        assert_eq!(exits.get(), 1);
+        assert!(window.is_none());
+
+        // the window is gone, so exiting again doesn't run the hook
+        exit_window(&mut window, State::on_exit);
+
+        assert_eq!(exits.get(), 1);
+    }
+
+    #[test]
+    fn test_context_recovery() {
+        let mut display = 1;
+        let mut events = Vec::new();
+
+        assert!(!recover_context(
+            &mut display,
+            false,
+            || 2,
+            |event, &display| {
+                events.push((event, display));
+            }
+        ));
+        assert!(events.is_empty());
+
+        assert!(recover_context(
+            &mut display,
+            true,
+            || 2,
+            |event, &display| {
+                events.push((event, display));
+            }
+        ));
+        assert_eq!(events, [
+            (ContextEvent::Lost, 1),
+            (ContextEvent::Restored, 2)
+        ]);
+        assert_eq!(display, 2);
     }
 }
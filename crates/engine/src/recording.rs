@@ -0,0 +1,203 @@
+use std::{mem, time::Duration};
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::{KeyCode, KeyboardModifiers, MouseButton, State};
+
+/// Input event passed to one of the [`State`] handlers.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum InputEvent {
+    Modifiers(KeyboardModifiers),
+    Key {
+        key: KeyCode,
+        is_pressed: bool,
+        repeat: bool,
+    },
+    MouseMotion(Vec2),
+    MouseWheel(Vec2),
+    MouseButton {
+        button: MouseButton,
+        is_pressed: bool,
+    },
+}
+
+impl InputEvent {
+    /// Passes the event to the corresponding handler of the state.
+    pub fn dispatch<T: State>(self, state: &mut T) {
+        match self {
+            Self::Modifiers(modifiers) => state.handle_keyboard_modifiers(modifiers),
+            Self::Key {
+                key,
+                is_pressed,
+                repeat,
+            } => state.handle_keyboard_input(key, is_pressed, repeat),
+            Self::MouseMotion(position) => state.handle_mouse_motion(position),
+            Self::MouseWheel(delta) => state.handle_mouse_wheel(delta),
+            Self::MouseButton { button, is_pressed } => {
+                state.handle_mouse_button(button, is_pressed);
+            }
+        }
+    }
+}
+
+/// Input events grouped by frames they happened in.
+///
+/// Frames are both recorded and replayed with the same fixed delta, which
+/// makes a replay reach exactly the same state as the recorded session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    delta: Duration,
+    frames: Vec<Vec<InputEvent>>,
+    /// Events of the frame that is currently being recorded.
+    #[serde(skip)]
+    pending: Vec<InputEvent>,
+}
+
+impl InputRecording {
+    pub const fn new(delta: Duration) -> Self {
+        Self {
+            delta,
+            frames: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the data is not a valid recording.
+    pub fn from_slice(data: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(data)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the recording can't be serialized.
+    pub fn to_vec(&self) -> Result<Vec<u8>, serde_json::Error> {
+        serde_json::to_vec(self)
+    }
+
+    /// Time passing between frames.
+    pub const fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Returns events of the frame with the given index.
+    pub fn frame(&self, index: usize) -> Option<&[InputEvent]> {
+        self.frames.get(index).map(Vec::as_slice)
+    }
+
+    pub const fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Adds event to the frame that is currently being recorded.
+    pub fn push(&mut self, event: InputEvent) {
+        self.pending.push(event);
+    }
+
+    /// Finishes recording of the current frame and starts the next one.
+    pub fn end_frame(&mut self) {
+        self.frames.push(mem::take(&mut self.pending));
+    }
+
+    /// Feeds every recorded frame into the state, calling `frame` with the
+    /// fixed delta after events of each frame were dispatched.
+    pub fn replay<T: State, F: FnMut(&mut T, Duration)>(&self, state: &mut T, mut frame: F) {
+        for events in &self.frames {
+            for event in events {
+                event.dispatch(state);
+            }
+
+            frame(state, self.delta);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use glam::{Vec2, vec2};
+
+    use super::{InputEvent, InputRecording};
+    use crate::{KeyCode, State, WindowContext, WindowDisplay};
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Walker {
+        position: Vec2,
+        direction: Vec2,
+    }
+
+    impl Walker {
+        fn step(&mut self, delta: Duration) {
+            self.position += self.direction * delta.as_secs_f32();
+        }
+    }
+
+    impl State for Walker {
+        fn new(_: WindowContext, _: &WindowDisplay) -> Self {
+            unreachable!()
+        }
+
+        fn handle_keyboard_input(&mut self, key: KeyCode, is_pressed: bool, _: bool) {
+            let direction = match key {
+                KeyCode::KeyW => vec2(0.0, 1.0),
+                KeyCode::KeyD => vec2(1.0, 0.0),
+                _ => return,
+            };
+
+            if is_pressed {
+                self.direction += direction;
+            } else {
+                self.direction -= direction;
+            }
+        }
+
+        fn render(&mut self, _: &WindowDisplay, _: Duration) {}
+    }
+
+    #[test]
+    fn test_replay_reaches_recorded_state() {
+        let delta = Duration::from_millis(16);
+        let key = |key, is_pressed| InputEvent::Key {
+            key,
+            is_pressed,
+            repeat: false,
+        };
+
+        let mut recording = InputRecording::new(delta);
+        let mut walker = Walker::default();
+
+        for events in [
+            vec![key(KeyCode::KeyW, true)],
+            vec![],
+            vec![key(KeyCode::KeyD, true)],
+            vec![key(KeyCode::KeyW, false)],
+            vec![],
+            vec![key(KeyCode::KeyD, false)],
+        ] {
+            for event in events {
+                recording.push(event);
+                event.dispatch(&mut walker);
+            }
+
+            walker.step(delta);
+            recording.end_frame();
+        }
+
+        assert_eq!(recording.len(), 6);
+        assert!(walker.position.x > 0.0 && walker.position.y > 0.0);
+
+        let recording = InputRecording::from_slice(&recording.to_vec().unwrap()).unwrap();
+        let mut replayed = Walker::default();
+
+        recording.replay(&mut replayed, Walker::step);
+
+        assert_eq!(replayed, walker);
+    }
+}
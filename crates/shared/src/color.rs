@@ -1,3 +1,5 @@
+use std::{error, fmt};
+
 use glam::{Vec3, Vec4};
 
 use crate::AsValue;
@@ -7,6 +9,81 @@ use crate::AsValue;
 /// Color type represented as RGBA
 pub struct Color([u8; 4]);
 
+/// Error returned when a string isn't a valid hex color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HexColorError {
+    /// String doesn't start with `#`.
+    MissingHash,
+    /// Number of digits is neither 3, 6 nor 8.
+    InvalidLength(usize),
+    InvalidDigit(char),
+}
+
+impl fmt::Display for HexColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHash => f.write_str("hex color must start with `#`"),
+            Self::InvalidLength(length) => {
+                write!(f, "hex color must have 3, 6 or 8 digits, got {length}")
+            }
+            Self::InvalidDigit(digit) => write!(f, "`{digit}` is not a hexadecimal digit"),
+        }
+    }
+}
+
+impl error::Error for HexColorError {}
+
+/// Serialized as a `#rrggbbaa` string, but can also be deserialized from an
+/// array of RGBA channels.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Color {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let [red, green, blue, alpha] = self.0;
+
+        serializer.collect_str(&format_args!("#{red:02x}{green:02x}{blue:02x}{alpha:02x}"))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Color {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ColorVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ColorVisitor {
+            type Value = Color;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a hex color string or an array of RGBA channels")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Color::try_from_hex_str(value).map_err(E::custom)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut channels = [0; 4];
+
+                for (index, channel) in channels.iter_mut().enumerate() {
+                    *channel = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(index, &self))?;
+                }
+
+                if seq.next_element::<u8>()?.is_some() {
+                    return Err(serde::de::Error::invalid_length(5, &self));
+                }
+
+                Ok(Color(channels))
+            }
+        }
+
+        deserializer.deserialize_any(ColorVisitor)
+    }
+}
+
 impl AsValue<[f32; 4]> for Color {
     fn as_value(&self) -> [f32; 4] {
         [
@@ -72,6 +149,16 @@ const fn color_to_linear(color: u8) -> f32 {
     COLOR_TO_LINEAR[color as usize]
 }
 
+fn linear_to_color(value: f32) -> u8 {
+    let value = if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055f32.mul_add(value.powf(1.0 / 2.4), -0.055)
+    };
+
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
 const COLOR_TO_LINEAR: [f32; 256] = [
     0.000_000_00,
     0.000_303_53,
@@ -336,6 +423,7 @@ impl Color {
     pub const BLUE: Self = Self([0, 0, 255, 255]);
     pub const BROWN: Self = Self([165, 42, 42, 255]);
     pub const GREEN: Self = Self([0, 255, 0, 255]);
+    pub const LIGHT_GREEN: Self = Self::from_hex(0x7A_FA_81_FF);
     pub const PURPLE: Self = Self([128, 0, 128, 255]);
     pub const RED: Self = Self([255, 0, 0, 255]);
     pub const WHITE: Self = Self([255, 255, 255, 255]);
@@ -361,6 +449,58 @@ impl Color {
         Self([red, green, blue, alpha])
     }
 
+    /// Creates color from a number in `0xRRGGBBAA` format.
+    pub const fn from_hex(value: u32) -> Self {
+        Self(value.to_be_bytes())
+    }
+
+    /// Parses color in `#rgb`, `#rrggbb` or `#rrggbbaa` format, where colors
+    /// without alpha are opaque.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not in one of the formats above.
+    pub fn try_from_hex_str(value: &str) -> Result<Self, HexColorError> {
+        let digits = value.strip_prefix('#').ok_or(HexColorError::MissingHash)?;
+        let length = digits.chars().count();
+
+        if !matches!(length, 3 | 6 | 8) {
+            return Err(HexColorError::InvalidLength(length));
+        }
+
+        let mut value = 0u32;
+
+        for digit in digits.chars() {
+            value = (value << 4)
+                | digit
+                    .to_digit(16)
+                    .ok_or(HexColorError::InvalidDigit(digit))?;
+        }
+
+        Ok(match length {
+            // every digit is repeated, so `f` becomes `ff`
+            3 => Self::new(
+                ((value >> 8) & 0xF) as u8 * 0x11,
+                ((value >> 4) & 0xF) as u8 * 0x11,
+                (value & 0xF) as u8 * 0x11,
+                255,
+            ),
+            6 => Self::from_hex((value << 8) | 0xFF),
+            _ => Self::from_hex(value),
+        })
+    }
+
+    /// Formats color as `#rrggbb`, or `#rrggbbaa` if it's not opaque.
+    pub fn to_hex_string(&self) -> String {
+        let [red, green, blue, alpha] = self.0;
+
+        if alpha == 255 {
+            format!("#{red:02x}{green:02x}{blue:02x}")
+        } else {
+            format!("#{red:02x}{green:02x}{blue:02x}{alpha:02x}")
+        }
+    }
+
     pub const fn new_f32(red: f32, green: f32, blue: f32, alpha: f32) -> Self {
         Self([
             (255.0 * red) as u8,
@@ -416,6 +556,117 @@ impl Color {
         Self::new_f32(red, green, blue, 1.0)
     }
 
+    /// Creates color from hue in degrees, saturation and value in the range
+    /// `0.0..=1.0`.
+    pub const fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let chroma = value * saturation;
+        let mut sector = (hue / 60.0) % 6.0;
+
+        if sector < 0.0 {
+            sector += 6.0;
+        }
+
+        let x = chroma * (1.0 - ((sector % 2.0) - 1.0).abs());
+        let [red, green, blue] = match sector as u8 {
+            0 => [chroma, x, 0.0],
+            1 => [x, chroma, 0.0],
+            2 => [0.0, chroma, x],
+            3 => [0.0, x, chroma],
+            4 => [x, 0.0, chroma],
+            _ => [chroma, 0.0, x],
+        };
+
+        let lightest = value - chroma;
+
+        Self::new_f32(red + lightest, green + lightest, blue + lightest, 1.0)
+    }
+
+    /// Returns the largest and the smallest of RGB channels.
+    fn channel_range(self) -> (u8, u8) {
+        let [red, green, blue, _] = self.0;
+
+        (red.max(green).max(blue), red.min(green).min(blue))
+    }
+
+    /// Returns hue in degrees, which is `0.0` for greys.
+    fn hue(self) -> f32 {
+        let (max, min) = self.channel_range();
+        let delta = f32::from(max - min) / 255.0;
+        let [red, green, blue, _] = self.0.map(|channel| f32::from(channel) / 255.0);
+
+        if delta == 0.0 {
+            0.0
+        } else if max == self.0[0] {
+            60.0 * ((green - blue) / delta).rem_euclid(6.0)
+        } else if max == self.0[1] {
+            60.0 * ((blue - red) / delta + 2.0)
+        } else {
+            60.0 * ((red - green) / delta + 4.0)
+        }
+    }
+
+    /// Returns hue in degrees, saturation and value of the color, where hue
+    /// of greys is `0.0`.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (max, min) = self.channel_range();
+        let value = f32::from(max) / 255.0;
+        let saturation = if max == 0 {
+            0.0
+        } else {
+            f32::from(max - min) / f32::from(max)
+        };
+
+        (self.hue(), saturation, value)
+    }
+
+    /// Returns hue in degrees, saturation and lightness of the color, which
+    /// is the inverse of [`Color::from_hsl`].
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (max, min) = self.channel_range();
+        let lightness = f32::from(u16::from(max) + u16::from(min)) / 510.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            f32::from(max - min) / 255.0 / (1.0 - 2.0f32.mul_add(lightness, -1.0).abs())
+        };
+
+        (self.hue(), saturation, lightness)
+    }
+
+    /// Converts the color to HSL, changes it and converts it back, keeping
+    /// alpha as is.
+    fn map_hsl<F: FnOnce(f32, f32) -> (f32, f32)>(self, map: F) -> Self {
+        let (hue, saturation, lightness) = self.to_hsl();
+        let (saturation, lightness) = map(saturation, lightness);
+        let Self([red, green, blue, _]) =
+            Self::from_hsl(hue, saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+
+        Self([red, green, blue, self.0[3]])
+    }
+
+    /// Decreases lightness of the color by the amount in the range
+    /// `0.0..=1.0`, making it black at `1.0`.
+    #[must_use]
+    pub fn darken(&self, amount: f32) -> Self {
+        self.map_hsl(|saturation, lightness| (saturation, lightness - amount))
+    }
+
+    /// Increases lightness of the color by the amount in the range
+    /// `0.0..=1.0`, making it white at `1.0`.
+    #[must_use]
+    pub fn lighten(&self, amount: f32) -> Self {
+        self.map_hsl(|saturation, lightness| (saturation, lightness + amount))
+    }
+
+    /// Increases saturation of the color by the amount, desaturating it if
+    /// the amount is negative.
+    #[must_use]
+    pub fn saturate(&self, amount: f32) -> Self {
+        self.map_hsl(|saturation, lightness| (saturation + amount, lightness))
+    }
+
+    /// Converts RGB channels from sRGB to linear space, the same way shaders
+    /// do.
     pub const fn to_linear(&self) -> [f32; 3] {
         [
             color_to_linear(self.0[0]),
@@ -424,10 +675,227 @@ impl Color {
         ]
     }
 
+    /// Creates opaque color from RGB channels in linear space, which is the
+    /// inverse of [`Color::to_linear`].
+    pub fn from_linear([red, green, blue]: [f32; 3]) -> Self {
+        Self([
+            linear_to_color(red),
+            linear_to_color(green),
+            linear_to_color(blue),
+            255,
+        ])
+    }
+
     #[must_use]
     pub fn multiply_rgb(self, factor: f32) -> Self {
         let value: Vec3 = self.as_value();
 
         (value * factor).into()
     }
+
+    /// Composites the color over the background using the source-over
+    /// operator, both being straight (not premultiplied) colors.
+    #[must_use]
+    pub fn blend_over(&self, background: Self) -> Self {
+        let source: Vec4 = self.as_value();
+        let background: Vec4 = background.as_value();
+        let alpha = background.w.mul_add(1.0 - source.w, source.w);
+
+        if alpha <= 0.0 {
+            return Self([0; 4]);
+        }
+
+        let rgb = (source.truncate() * source.w
+            + background.truncate() * background.w * (1.0 - source.w))
+            / alpha;
+
+        Self(
+            rgb.extend(alpha)
+                .to_array()
+                .map(|channel| (channel * 255.0).round() as u8),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Color, HexColorError};
+
+    #[test]
+    fn test_hex_round_trip() {
+        let color = Color::try_from_hex_str("#7afa81").unwrap();
+
+        assert_eq!(color, Color::new(0x7A, 0xFA, 0x81, 255));
+        assert_eq!(color.to_hex_string(), "#7afa81");
+        assert_eq!(
+            Color::try_from_hex_str(&Color::LIGHT_GREEN.to_hex_string()),
+            Ok(Color::LIGHT_GREEN)
+        );
+
+        let translucent = Color::from_hex(0x12_34_56_78);
+
+        assert_eq!(translucent.to_hex_string(), "#12345678");
+        assert_eq!(Color::try_from_hex_str("#12345678"), Ok(translucent));
+        assert_eq!(Color::try_from_hex_str("#FfF"), Ok(Color::WHITE));
+    }
+
+    #[test]
+    fn test_linear_conversion() {
+        assert!(
+            Color::BLACK
+                .to_linear()
+                .iter()
+                .all(|value| value.abs() < 1e-6)
+        );
+        assert!(
+            Color::WHITE
+                .to_linear()
+                .iter()
+                .all(|value| (value - 1.0).abs() < 1e-6)
+        );
+        assert_eq!(Color::from_linear([0.0; 3]), Color::BLACK);
+        assert_eq!(Color::from_linear([1.0; 3]), Color::WHITE);
+
+        // channels on both sides of the 0.04045 boundary between the linear
+        // segment and the power curve
+        let [below, above, _] = Color::new(10, 11, 0, 255).to_linear();
+
+        assert!((below - 10.0 / 255.0 / 12.92).abs() < 1e-6);
+        assert!((above - ((11.0 / 255.0 + 0.055) / 1.055f32).powf(2.4)).abs() < 1e-6);
+        assert_eq!(
+            Color::from_linear([0.040_45 / 12.92; 3]),
+            Color::new(10, 10, 10, 255)
+        );
+
+        for value in 0..=255 {
+            let color = Color::new(value, value, value, 255);
+
+            assert_eq!(Color::from_linear(color.to_linear()), color);
+        }
+    }
+
+    #[test]
+    fn test_hsv() {
+        let hue = |color: Color| color.to_hsv().0;
+
+        assert!(hue(Color::RED).abs() < 1e-4);
+        assert!((hue(Color::GREEN) - 120.0).abs() < 1e-4);
+        assert!((hue(Color::BLUE) - 240.0).abs() < 1e-4);
+        assert_eq!(Color::from_hsv(0.0, 1.0, 1.0), Color::RED);
+        assert_eq!(Color::from_hsv(120.0, 1.0, 1.0), Color::GREEN);
+        assert_eq!(Color::from_hsv(240.0, 1.0, 1.0), Color::BLUE);
+        assert_eq!(Color::from_hsv(-120.0, 1.0, 1.0), Color::BLUE);
+
+        let (hue, saturation, value) = Color::new(128, 128, 128, 255).to_hsv();
+
+        assert!(hue.abs() < f32::EPSILON && saturation.abs() < f32::EPSILON);
+        assert!((value - 128.0 / 255.0).abs() < 1e-6);
+
+        for color in [
+            Color::YELLOW,
+            Color::PURPLE,
+            Color::BROWN,
+            Color::LIGHT_GREEN,
+            Color::new(12, 200, 180, 255),
+        ] {
+            let (hue, saturation, value) = color.to_hsv();
+            let converted = Color::from_hsv(hue, saturation, value);
+
+            for (a, b) in [
+                (color.get_red(), converted.get_red()),
+                (color.get_green(), converted.get_green()),
+                (color.get_blue(), converted.get_blue()),
+            ] {
+                assert!(a.abs_diff(b) <= 1, "{color:?} became {converted:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_hsl_helpers() {
+        assert_eq!(Color::RED.lighten(1.0), Color::WHITE);
+        assert_eq!(Color::RED.darken(1.0), Color::BLACK);
+        assert_eq!(Color::RED.lighten(0.0), Color::RED);
+        assert_eq!(Color::RED.with_alpha(0.5).darken(1.0).get_alpha(), 127);
+
+        let lighter = Color::BROWN.lighten(0.2);
+        let darker = Color::BROWN.darken(0.2);
+
+        assert!(lighter.get_red() > Color::BROWN.get_red());
+        assert!(darker.get_red() < Color::BROWN.get_red());
+
+        let grey = Color::new(128, 64, 64, 255).saturate(-1.0);
+
+        assert!(grey.get_red() == grey.get_green() && grey.get_green() == grey.get_blue());
+        assert_eq!(Color::new(128, 64, 64, 255).saturate(1.0).get_green(), 0);
+
+        let (hue, saturation, lightness) = Color::RED.to_hsl();
+
+        assert!(hue.abs() < 1e-4 && (saturation - 1.0).abs() < 1e-4);
+        assert!((lightness - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde_round_trip() {
+        let color = Color::new(18, 52, 86, 120);
+        let json = serde_json::to_string(&color).unwrap();
+
+        assert_eq!(json, r##""#12345678""##);
+        assert_eq!(serde_json::from_str::<Color>(&json).unwrap(), color);
+        assert_eq!(
+            serde_json::from_str::<Color>("[18, 52, 86, 120]").unwrap(),
+            color
+        );
+        assert_eq!(
+            serde_json::from_str::<Color>(r##""#f00""##).unwrap(),
+            Color::RED
+        );
+        assert!(serde_json::from_str::<Color>("[18, 52, 86]").is_err());
+        assert!(serde_json::from_str::<Color>("[18, 52, 86, 120, 0]").is_err());
+        assert!(serde_json::from_str::<Color>(r#""red""#).is_err());
+    }
+
+    #[test]
+    fn test_blend_over() {
+        let background = Color::new(20, 40, 60, 200);
+
+        assert_eq!(Color::RED.blend_over(background), Color::RED);
+        assert_eq!(
+            Color::RED.with_alpha(0.0).blend_over(background),
+            background
+        );
+        assert_eq!(
+            Color::new(255, 0, 0, 0).blend_over(Color::new(0, 0, 0, 0)),
+            Color::new(0, 0, 0, 0)
+        );
+
+        let overlay = Color::new(255, 0, 0, 128).blend_over(Color::WHITE);
+
+        assert_eq!(overlay, Color::new(255, 127, 127, 255));
+
+        // translucent layers stay translucent, with straight color channels
+        let overlay = Color::new(255, 0, 0, 128).blend_over(Color::new(0, 0, 255, 128));
+
+        assert_eq!(overlay.get_alpha(), 192);
+        assert_eq!(overlay.get_green(), 0);
+        assert!(overlay.get_red().abs_diff(170) <= 1);
+        assert!(overlay.get_blue().abs_diff(85) <= 1);
+    }
+
+    #[test]
+    fn test_invalid_hex() {
+        assert_eq!(
+            Color::try_from_hex_str("7afa81"),
+            Err(HexColorError::MissingHash)
+        );
+        assert_eq!(
+            Color::try_from_hex_str("#7afa8"),
+            Err(HexColorError::InvalidLength(5))
+        );
+        assert_eq!(
+            Color::try_from_hex_str("#7afg81"),
+            Err(HexColorError::InvalidDigit('g'))
+        );
+    }
 }
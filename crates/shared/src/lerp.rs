@@ -1,3 +1,5 @@
+use glam::{Vec2, Vec3};
+
 use crate::Color;
 
 pub trait Lerp {
@@ -21,3 +23,38 @@ impl Lerp for Color {
         )
     }
 }
+
+impl Lerp for Vec2 {
+    fn lerp(&self, end: &Self, x: f32) -> Self {
+        Self::lerp(*self, *end, x)
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(&self, end: &Self, x: f32) -> Self {
+        Self::lerp(*self, *end, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec3, vec2, vec3};
+
+    use super::Lerp;
+
+    #[test]
+    fn test_vector_lerp() {
+        let (start, end) = (vec2(-2.0, 4.0), vec2(6.0, 0.0));
+
+        assert_eq!(Lerp::lerp(&start, &end, 0.0), start);
+        assert_eq!(Lerp::lerp(&start, &end, 1.0), end);
+        assert_eq!(Lerp::lerp(&start, &end, 0.5), vec2(2.0, 2.0));
+
+        let (start, end) = (Vec3::ZERO, vec3(10.0, -4.0, 1.0));
+
+        assert_eq!(Lerp::lerp(&start, &end, 0.0), start);
+        assert_eq!(Lerp::lerp(&start, &end, 1.0), end);
+        assert_eq!(Lerp::lerp(&start, &end, 0.5), vec3(5.0, -2.0, 0.5));
+        assert_eq!(Lerp::lerp(&Vec2::ONE, &Vec2::ONE, 0.3), Vec2::ONE);
+    }
+}
@@ -7,12 +7,17 @@
 mod color;
 mod lerp;
 #[cfg(feature = "network")] mod network;
+mod random;
 
 use std::{fmt, ops::Add};
 
 #[cfg(feature = "network")]
 pub use self::network::{Client, IncomingPacket, OutgoingPacket, Player, ServerConnection};
-pub use self::{color::Color, lerp::Lerp};
+pub use self::{
+    color::{Color, HexColorError},
+    lerp::Lerp,
+    random::Random,
+};
 
 pub type Size2D = glamour::Size2;
 pub type Size3D = glamour::Size3;
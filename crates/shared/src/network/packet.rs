@@ -7,7 +7,7 @@ pub struct Player {
     pub position: Vec3,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum IncomingPacket {
     GetPlayers,
@@ -15,7 +15,7 @@ pub enum IncomingPacket {
     PlayerMoved { position: Vec3 },
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum OutgoingPacket {
     PlayerConnected { name: String },
@@ -73,3 +73,68 @@ pub fn wrap_stream<I, O>(stream: TcpStream) -> (InStream<I>, OutSink<O>) {
         OutSink::new(sink, Bson::default()),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::pin::Pin;
+
+    use glam::Vec3;
+    use proptest::{collection::vec, prelude::*};
+    use serde::{Deserialize, Serialize};
+    use tokio_serde::{Deserializer, Serializer};
+    use tokio_util::bytes::BytesMut;
+
+    use super::Bson;
+    use crate::{IncomingPacket, OutgoingPacket, Player};
+
+    fn position() -> impl Strategy<Value = Vec3> {
+        // BSON stores floats as doubles, which hold every finite float
+        any::<[f32; 3]>()
+            .prop_filter("finite", |position| {
+                position.iter().all(|value| value.is_finite())
+            })
+            .prop_map(Vec3::from_array)
+    }
+
+    fn incoming_packet() -> impl Strategy<Value = IncomingPacket> {
+        prop_oneof![
+            Just(IncomingPacket::GetPlayers),
+            any::<String>().prop_map(|name| IncomingPacket::PlayerConnected { name }),
+            position().prop_map(|position| IncomingPacket::PlayerMoved { position }),
+        ]
+    }
+
+    fn outgoing_packet() -> impl Strategy<Value = OutgoingPacket> {
+        let player = (any::<String>(), position())
+            .prop_map(|(nickname, position)| Player { nickname, position });
+
+        prop_oneof![
+            any::<String>().prop_map(|name| OutgoingPacket::PlayerConnected { name }),
+            (any::<String>(), position())
+                .prop_map(|(name, position)| OutgoingPacket::PlayerMoved { name, position }),
+            vec(player, 0..8).prop_map(|players| OutgoingPacket::PlayersList { players }),
+        ]
+    }
+
+    /// Passes the packet through the codec both ends of a connection use.
+    fn round_trip<T: Serialize + for<'a> Deserialize<'a> + Unpin>(packet: &T) -> T {
+        let mut codec = Bson::<T, T>::default();
+        let bytes = Pin::new(&mut codec).serialize(packet).unwrap();
+
+        Pin::new(&mut codec)
+            .deserialize(&BytesMut::from(&bytes[..]))
+            .unwrap()
+    }
+
+    proptest! {
+        #[test]
+        fn test_incoming_packet_round_trip(packet in incoming_packet()) {
+            prop_assert_eq!(round_trip(&packet), packet);
+        }
+
+        #[test]
+        fn test_outgoing_packet_round_trip(packet in outgoing_packet()) {
+            prop_assert_eq!(round_trip(&packet), packet);
+        }
+    }
+}
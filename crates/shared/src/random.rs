@@ -0,0 +1,60 @@
+/// Deterministic pseudo-random number generator (`SplitMix64`), producing the
+/// same sequence for the same seed on every platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub const fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub const fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut value = self.state;
+
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+
+        value ^ (value >> 31)
+    }
+
+    /// Returns a number in the range `0.0..1.0`.
+    #[allow(clippy::cast_precision_loss)]
+    pub const fn next_f32(&mut self) -> f32 {
+        // upper 24 bits fit into the mantissa without rounding
+        (self.next_u64() >> 40) as f32 / (1u32 << 24) as f32
+    }
+
+    /// Returns `true` with the given probability in the range `0.0..=1.0`.
+    pub const fn chance(&mut self, probability: f32) -> bool {
+        self.next_f32() < probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Random;
+
+    #[test]
+    fn test_random_is_deterministic() {
+        let mut first = Random::new(12723);
+        let mut second = Random::new(12723);
+
+        for _ in 0..64 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+
+        assert_ne!(Random::new(1).next_u64(), Random::new(2).next_u64());
+
+        let mut random = Random::new(0);
+
+        for _ in 0..1024 {
+            assert!((0.0..1.0).contains(&random.next_f32()));
+            assert!(random.chance(1.0));
+            assert!(!random.chance(0.0));
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::hint::black_box;
+
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use glam::IVec2;
+use meralus_world::{CAVE_THRESHOLD, Chunk};
+
+const SEED: u32 = 12723;
+
+fn generation(c: &mut Criterion) {
+    c.bench_function("generate_surface", |b| {
+        b.iter(|| {
+            let mut chunk = Chunk::new(black_box(IVec2::new(3, -2)));
+
+            chunk.generate_surface(black_box(SEED));
+
+            chunk
+        });
+    });
+
+    c.bench_function("carve_caves", |b| {
+        let mut chunk = Chunk::new(IVec2::new(3, -2));
+
+        chunk.generate_surface(SEED);
+
+        b.iter_batched_ref(
+            || chunk.clone(),
+            |chunk| chunk.carve_caves(black_box(SEED), CAVE_THRESHOLD),
+            BatchSize::LargeInput,
+        );
+    });
+}
+
+fn serialize(c: &mut Criterion) {
+    let mut chunk = Chunk::new(IVec2::ZERO);
+
+    chunk.generate_surface(SEED);
+    chunk.carve_caves(SEED, CAVE_THRESHOLD);
+
+    let data = chunk.serialize();
+
+    c.bench_function("serialize", |b| b.iter(|| black_box(&chunk).serialize()));
+    c.bench_function("deserialize", |b| {
+        b.iter(|| Chunk::deserialize(black_box(&data)).unwrap());
+    });
+}
+
+criterion_group!(benches, generation, serialize);
+criterion_main!(benches);
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize, de::Error};
+
+use crate::AIR_BLOCK;
+
+/// Declares the id every block is stored with in chunks, so that ids don't
+/// depend on the order block models happen to be loaded in.
+///
+/// Written as an object of form id-name (e.g. `{ "1": "dirt", "2": "stone" }`).
+/// Id `0` is reserved for air (see [`AIR_BLOCK`]) and can't be declared.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct BlockManifest {
+    blocks: BTreeMap<u8, String>,
+}
+
+impl BlockManifest {
+    pub fn from_slice(data: &[u8]) -> Result<Self, serde_json::Error> {
+        let manifest: Self = serde_json::from_slice(data)?;
+
+        if let Some(name) = manifest.blocks.get(&AIR_BLOCK) {
+            return Err(Error::custom(format!(
+                "id {AIR_BLOCK} is reserved for air, but it's assigned to `{name}`"
+            )));
+        }
+
+        Ok(manifest)
+    }
+
+    /// Returns id assigned to the block with the given name.
+    pub fn id<T: AsRef<str>>(&self, name: T) -> Option<u8> {
+        self.blocks
+            .iter()
+            .find_map(|(id, block)| (block == name.as_ref()).then_some(*id))
+    }
+
+    /// Returns name of the block with the given id.
+    pub fn name(&self, id: u8) -> Option<&str> {
+        self.blocks.get(&id).map(String::as_str)
+    }
+
+    /// Returns names of all declared blocks, ordered by their ids.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.blocks.values().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockManifest;
+
+    #[test]
+    fn test_manifest_ids() {
+        let manifest =
+            BlockManifest::from_slice(br#"{ "2": "grass_block", "3": "stone", "1": "dirt" }"#)
+                .unwrap();
+
+        assert_eq!(manifest.id("dirt"), Some(1));
+        assert_eq!(manifest.name(2), Some("grass_block"));
+        assert_eq!(manifest.id("air"), None);
+        assert_eq!(manifest.names().collect::<Vec<_>>(), [
+            "dirt",
+            "grass_block",
+            "stone"
+        ]);
+        assert!(BlockManifest::from_slice(br#"{ "0": "stone" }"#).is_err());
+    }
+}
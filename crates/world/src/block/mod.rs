@@ -1,13 +1,15 @@
 mod face;
+mod manifest;
 mod state;
 
-use std::{collections::HashMap, path::PathBuf};
+use std::{collections::HashMap, fmt, path::PathBuf};
 
 pub use face::{Axis, Corner, Face};
 use glam::{Vec2, Vec3};
+pub use manifest::BlockManifest;
 use serde::{
-    Deserialize, Serialize,
-    de::{Error, Visitor},
+    Deserialize, Serialize, Serializer,
+    de::{Error, IntoDeserializer, Visitor, value::StrDeserializer},
 };
 pub use state::{BlockCondition, BlockState, BlockStates, ConditionValue, Property, PropertyValue};
 
@@ -19,8 +21,13 @@ pub struct BlockFace {
     /// At this moment it should always be a reference to an id from
     /// [`BlockModel::textures`] (in `#your-texture-id` format).
     pub texture: String,
-    /// Optional UV coordinates in the range `0.0..1.0` on both axes (where
-    /// `0.0, 0.0` is bottom-left and `1.0, 1.0` is top-right).
+    /// Optional start and size of the used part of the texture in the range
+    /// `0.0..1.0` on both axes (where `0.0, 0.0` is bottom-left and `1.0, 1.0`
+    /// is top-right).
+    ///
+    /// Written either as `[x1, y1, x2, y2]` corners or as
+    /// `{ "start": [x, y], "size": [width, height] }`.
+    #[serde(serialize_with = "FaceUv::serialize_start_size")]
     pub uv: Option<[Vec2; 2]>,
     /// Specifies whether to apply color of current biome to the texture.
     pub tint: bool,
@@ -29,6 +36,39 @@ pub struct BlockFace {
     pub cull_face: Option<Face>,
 }
 
+/// UV area of a [`BlockFace`] in one of the supported forms.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum FaceUv {
+    Corners([f32; 4]),
+    StartSize { start: Vec2, size: Vec2 },
+}
+
+impl FaceUv {
+    fn into_start_size(self) -> [Vec2; 2] {
+        match self {
+            Self::Corners([x1, y1, x2, y2]) => [Vec2::new(x1, y1), Vec2::new(x2 - x1, y2 - y1)],
+            Self::StartSize { start, size } => [start, size],
+        }
+    }
+
+    #[allow(clippy::ref_option)] // signature required by `serialize_with`
+    fn serialize_start_size<S: Serializer>(
+        uv: &Option<[Vec2; 2]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        uv.map(|[start, size]| Self::StartSize { start, size })
+            .serialize(serializer)
+    }
+}
+
+impl BlockFace {
+    /// Names of faces accepted as `cull_face` value.
+    const FACES: &[&str] = &["bottom", "top", "left", "right", "front", "back"];
+    /// Fields accepted when the face is described with a map.
+    const FIELDS: &[&str] = &["texture", "uv", "tint", "cull_face"];
+}
+
 impl<'de> Deserialize<'de> for BlockFace {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -66,9 +106,27 @@ impl<'de> Deserialize<'de> for BlockFace {
                             texture.replace(map.next_value()?);
                         }
                         "tint" => value.tint = map.next_value()?,
-                        "uv" => value.uv = Some(map.next_value()?),
-                        "cull_face" => value.cull_face = Some(map.next_value()?),
-                        field => Err(Error::unknown_field(field, &["texture", "uv", "cull_face"]))?,
+                        "uv" => {
+                            let uv = map.next_value::<FaceUv>().map_err(|_| {
+                                Error::custom(
+                                    "invalid `uv`, expected `[x1, y1, x2, y2]` or `{ \"start\": \
+                                     [x, y], \"size\": [width, height] }`",
+                                )
+                            })?;
+
+                            value.uv = Some(uv.into_start_size());
+                        }
+                        "cull_face" => {
+                            let face = map.next_value::<String>()?;
+                            let deserializer: StrDeserializer<A::Error> =
+                                face.as_str().into_deserializer();
+
+                            value.cull_face =
+                                Some(Face::deserialize(deserializer).map_err(|_| {
+                                    Error::unknown_variant(&face, BlockFace::FACES)
+                                })?);
+                        }
+                        field => Err(Error::unknown_field(field, BlockFace::FIELDS))?,
                     }
                 }
 
@@ -198,6 +256,24 @@ pub struct BlockModel {
     /// are not a full cuboid (e.g. levers).
     #[serde(default = "default_ao")]
     pub ambient_occlusion: bool,
+    /// Marks model as see-through (e.g. leaves or glass), so it never hides
+    /// faces of neighbouring blocks, even if it's a full cuboid.
+    #[serde(default)]
+    pub transparent: bool,
+    /// Hides faces between neighbouring blocks of this model (e.g. so that
+    /// water doesn't show faces inside of it), unlike other transparent
+    /// blocks like glass, which keep them.
+    #[serde(default)]
+    pub cull_same_block: bool,
+    /// Optional reference to an id from [`textures`] (in `#your-texture-id`
+    /// format) of a sheet with 16 variants of the texture, which replaces
+    /// textures of faces depending on which neighbours in the plane of a face
+    /// are the same block (e.g. so that glass has borders only on exposed
+    /// edges).
+    ///
+    /// [`textures`]: BlockModel::textures
+    #[serde(default)]
+    pub connected_texture: Option<String>,
     /// List of [`BlockElement`] describing the individual parts of the model.
     #[serde(default)]
     pub elements: Vec<BlockElement>,
@@ -213,13 +289,116 @@ impl BlockModel {
     }
 
     pub fn is_transparent(&self) -> bool {
-        self.textures.is_empty() && self.elements.is_empty()
+        self.transparent || (self.textures.is_empty() && self.elements.is_empty())
     }
 
     pub fn is_opaque(&self) -> bool {
-        self.elements
-            .iter()
-            .any(|element| element.start == Vec3::ZERO && element.end == Vec3::ONE)
+        !self.transparent
+            && self
+                .elements
+                .iter()
+                .any(|element| element.start == Vec3::ZERO && element.end == Vec3::ONE)
+    }
+
+    /// Checks the model for structural mistakes that would otherwise surface
+    /// only while baking it: inverted elements, face textures missing from
+    /// [`textures`] and invalid rotation angles.
+    ///
+    /// Should be called on a model already merged with its parents, as
+    /// textures are usually defined by the child model.
+    ///
+    /// [`textures`]: BlockModel::textures
+    pub fn validate(&self) -> Result<(), Vec<ModelProblem>> {
+        let mut problems = Vec::new();
+
+        for (index, element) in self.elements.iter().enumerate() {
+            let path = format!("elements[{index}]");
+
+            for (axis, (start, end)) in ["x", "y", "z"].into_iter().zip(
+                element
+                    .start
+                    .to_array()
+                    .into_iter()
+                    .zip(element.end.to_array()),
+            ) {
+                if start > end {
+                    problems.push(ModelProblem::new(
+                        format!("{path}.start"),
+                        format!("`start.{axis}` ({start}) is greater than `end.{axis}` ({end})"),
+                    ));
+                }
+            }
+
+            let faces: Vec<(String, &BlockFace)> = match &element.faces {
+                Faces::All(face) => vec![(format!("{path}.all"), face)],
+                Faces::Unique(faces) => Face::ALL
+                    .into_iter()
+                    .filter_map(|face| {
+                        faces.get(&face).map(|data| {
+                            (
+                                format!("{path}.faces.{}", BlockFace::FACES[face.normal_index()]),
+                                data,
+                            )
+                        })
+                    })
+                    .collect(),
+            };
+
+            for (path, face) in faces {
+                if !self.textures.contains_key(&face.texture) {
+                    problems.push(ModelProblem::new(
+                        format!("{path}.texture"),
+                        format!("texture `{}` is not defined in `textures`", face.texture),
+                    ));
+                }
+            }
+
+            if let Some(rotation) = element.rotation
+                && !(-180.0..=180.0).contains(&rotation.angle)
+            {
+                problems.push(ModelProblem::new(
+                    format!("{path}.rotation.angle"),
+                    format!("angle {} is not in range -180..=180", rotation.angle),
+                ));
+            }
+        }
+
+        if let Some(texture) = &self.connected_texture
+            && !self.textures.contains_key(texture)
+        {
+            problems.push(ModelProblem::new(
+                String::from("connected_texture"),
+                format!("texture `{texture}` is not defined in `textures`"),
+            ));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+}
+
+/// Problem found in a [`BlockModel`] during [validation].
+///
+/// [validation]: BlockModel::validate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelProblem {
+    /// Path to the invalid field (e.g. `elements[0].faces.top.texture`).
+    pub path: String,
+    pub message: String,
+}
+
+impl ModelProblem {
+    const fn new(path: String, message: String) -> Self {
+        Self { path, message }
+    }
+}
+
+impl fmt::Display for ModelProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
     }
 }
 
@@ -267,12 +446,153 @@ pub enum Faces {
 
 #[cfg(test)]
 mod tests {
-    use crate::block::BlockModel;
+    use glam::Vec2;
+
+    use crate::block::{BlockFace, BlockModel, Face, ModelProblem};
 
     #[test]
     fn test_block_model_parsing() {
         let data = include_bytes!("../../../../crates/app/resources/models/grass_block.json");
 
         assert!(serde_json::from_slice::<BlockModel>(data).is_ok());
+
+        let model = BlockModel::from_slice(
+            br##"{ "transparent": true, "textures": { "#all": "game:leaves" }, "elements": [{ "start": [0, 0, 0], "end": [1, 1, 1], "all": { "texture": "#all" } }] }"##,
+        )
+        .unwrap();
+
+        assert!(model.transparent && model.is_transparent() && !model.is_opaque());
+        assert!(!model.cull_same_block);
+
+        let model = BlockModel::from_slice(include_bytes!(
+            "../../../../crates/app/resources/models/water.json"
+        ))
+        .unwrap();
+
+        assert!(model.transparent && model.cull_same_block);
+    }
+
+    #[test]
+    fn test_block_face_parsing() {
+        let face: BlockFace = serde_json::from_str(
+            r##"{ "texture": "#side", "uv": [0.0, 0.5, 1.0, 1.0], "tint": true, "cull_face": "top" }"##,
+        )
+        .unwrap();
+
+        assert_eq!(face.texture, "#side");
+        assert_eq!(face.uv, Some([Vec2::new(0.0, 0.5), Vec2::new(1.0, 0.5)]));
+        assert!(face.tint);
+        assert_eq!(
+            serde_json::from_value::<BlockFace>(serde_json::to_value(&face).unwrap())
+                .unwrap()
+                .uv,
+            face.uv
+        );
+        assert_eq!(face.cull_face, Some(Face::Top));
+    }
+
+    #[test]
+    fn test_block_face_uv_forms() {
+        for data in [
+            r##"{ "texture": "#side", "uv": [0.25, 0.0, 0.75, 0.5] }"##,
+            r##"{ "texture": "#side", "uv": { "start": [0.25, 0.0], "size": [0.5, 0.5] } }"##,
+        ] {
+            let face: BlockFace = serde_json::from_str(data).unwrap();
+
+            assert_eq!(
+                face.uv,
+                Some([Vec2::new(0.25, 0.0), Vec2::new(0.5, 0.5)]),
+                "{data}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_block_face_errors() {
+        let error = serde_json::from_str::<BlockFace>(r##"{ "texture": "#side", "color": 1 }"##)
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("unknown field `color`"), "{error}");
+        assert!(
+            BlockFace::FIELDS.iter().all(|field| error.contains(field)),
+            "{error}"
+        );
+
+        let error = serde_json::from_str::<BlockFace>(r#"{ "tint": true }"#)
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("missing field `texture`"), "{error}");
+
+        let error =
+            serde_json::from_str::<BlockFace>(r##"{ "texture": "#side", "uv": [0.0, 1.0] }"##)
+                .unwrap_err()
+                .to_string();
+
+        assert!(error.contains("invalid `uv`"), "{error}");
+
+        let error =
+            serde_json::from_str::<BlockFace>(r##"{ "texture": "#side", "cull_face": "up" }"##)
+                .unwrap_err()
+                .to_string();
+
+        assert!(error.contains("unknown variant `up`"), "{error}");
+        assert!(error.contains("bottom"), "{error}");
+    }
+
+    #[test]
+    fn test_block_model_validation() {
+        for data in [
+            &include_bytes!("../../../../crates/app/resources/models/grass_block.json")[..],
+            br##"{ "textures": { "#all": "game:dirt" }, "elements": [{ "start": [0, 0, 0], "end": [1, 0.5, 1], "all": "#all", "rotation": { "origin": [0.5, 0.5, 0.5], "axis": "y", "angle": 45 } }] }"##,
+        ] {
+            assert_eq!(BlockModel::from_slice(data).unwrap().validate(), Ok(()));
+        }
+
+        let inverted = BlockModel::from_slice(
+            br##"{ "textures": { "#all": "game:dirt" }, "elements": [{ "start": [0, 1, 0], "end": [1, 0, 0.5], "all": "#all" }] }"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            inverted.validate(),
+            Err(vec![ModelProblem {
+                path: String::from("elements[0].start"),
+                message: String::from("`start.y` (1) is greater than `end.y` (0)"),
+            }])
+        );
+
+        let dangling = BlockModel::from_slice(
+            br##"{ "textures": { "#side": "game:dirt" }, "elements": [{ "start": [0, 0, 0], "end": [1, 1, 1], "faces": { "top": "#top", "left": "#side" }, "rotation": { "origin": [0, 0, 0], "axis": "x", "angle": 720 } }] }"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            dangling.validate(),
+            Err(vec![
+                ModelProblem {
+                    path: String::from("elements[0].faces.top.texture"),
+                    message: String::from("texture `#top` is not defined in `textures`"),
+                },
+                ModelProblem {
+                    path: String::from("elements[0].rotation.angle"),
+                    message: String::from("angle 720 is not in range -180..=180"),
+                },
+            ])
+        );
+
+        let connected = BlockModel::from_slice(
+            br##"{ "textures": { "#all": "game:glass" }, "connected_texture": "#connected", "elements": [{ "start": [0, 0, 0], "end": [1, 1, 1], "all": "#all" }] }"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            connected.validate(),
+            Err(vec![ModelProblem {
+                path: String::from("connected_texture"),
+                message: String::from("texture `#connected` is not defined in `textures`"),
+            }])
+        );
     }
 }
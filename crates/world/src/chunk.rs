@@ -1,25 +1,97 @@
-use std::io::{self, Read};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Read},
+    iter,
+};
 
-use glam::{IVec2, IVec3, U16Vec3, Vec3, vec3};
-use noise::{Fbm, NoiseFn, Perlin};
+use glam::{IVec2, IVec3, U16Vec3, Vec3};
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin, RidgedMulti};
+use serde::{Deserialize, Serialize};
 
-pub const CHUNK_SIZE: usize = 16;
-pub const CHUNK_SIZE_U16: u16 = 16;
-pub const CHUNK_SIZE_I32: i32 = 16;
-pub const CHUNK_SIZE_F32: f32 = 16.0;
-pub const CHUNK_SIZE_F64: f64 = 16.0;
+use crate::coords;
 
+/// Width and depth of a chunk, as well as height of a subchunk, in blocks.
+pub const CHUNK_SIZE: usize = 16;
+/// [`CHUNK_SIZE`] as [`u16`], used for chunk-local positions.
+pub const CHUNK_SIZE_U16: u16 = CHUNK_SIZE as u16;
+/// [`CHUNK_SIZE`] as [`i32`], used for world block positions.
+pub const CHUNK_SIZE_I32: i32 = CHUNK_SIZE as i32;
+/// [`CHUNK_SIZE`] as [`f32`], used for world positions.
+pub const CHUNK_SIZE_F32: f32 = CHUNK_SIZE as f32;
+/// [`CHUNK_SIZE`] as [`f64`], used for noise sampling.
+pub const CHUNK_SIZE_F64: f64 = CHUNK_SIZE as f64;
+
+/// Default amount of subchunks stacked in a chunk.
 pub const SUBCHUNK_COUNT: usize = 16;
-pub const SUBCHUNK_COUNT_I32: i32 = 16;
-pub const SUBCHUNK_COUNT_U16: u16 = 16;
-pub const SUBCHUNK_COUNT_F32: f32 = 16.0;
-pub const SUBCHUNK_COUNT_F64: f64 = 16.0;
-
+/// [`SUBCHUNK_COUNT`] as [`i32`].
+pub const SUBCHUNK_COUNT_I32: i32 = SUBCHUNK_COUNT as i32;
+/// [`SUBCHUNK_COUNT`] as [`u16`].
+pub const SUBCHUNK_COUNT_U16: u16 = SUBCHUNK_COUNT as u16;
+/// [`SUBCHUNK_COUNT`] as [`f32`].
+pub const SUBCHUNK_COUNT_F32: f32 = SUBCHUNK_COUNT as f32;
+/// [`SUBCHUNK_COUNT`] as [`f64`].
+pub const SUBCHUNK_COUNT_F64: f64 = SUBCHUNK_COUNT as f64;
+
+/// Default chunk height in blocks.
 pub const CHUNK_HEIGHT: usize = CHUNK_SIZE * SUBCHUNK_COUNT;
+/// [`CHUNK_HEIGHT`] as [`i32`].
 pub const CHUNK_HEIGHT_I32: i32 = CHUNK_SIZE_I32 * SUBCHUNK_COUNT_I32;
+/// [`CHUNK_HEIGHT`] as [`u16`].
 pub const CHUNK_HEIGHT_U16: u16 = CHUNK_SIZE_U16 * SUBCHUNK_COUNT_U16;
+/// [`CHUNK_HEIGHT`] as [`f32`].
 pub const CHUNK_HEIGHT_F32: f32 = CHUNK_SIZE_F32 * SUBCHUNK_COUNT_F32;
+/// [`CHUNK_HEIGHT`] as [`f64`].
 pub const CHUNK_HEIGHT_F64: f64 = CHUNK_SIZE_F64 * SUBCHUNK_COUNT_F64;
+/// Block id always reserved for air, which has no model and is never meshed.
+pub const AIR_BLOCK: u8 = 0;
+/// Default density caves are carved above, see [`Chunk::carve_caves`].
+pub const CAVE_THRESHOLD: f64 = 0.65;
+/// Id of the block filling air below the sea level during generation.
+pub const WATER_BLOCK: u8 = 3;
+/// Height below which generated terrain is flooded with water.
+pub const SEA_LEVEL: u16 = 64;
+/// Size of cave noise features in blocks.
+const CAVE_SCALE: f64 = 48.0;
+
+/// Parameters of the noise surface terrain is generated from, see
+/// [`Chunk::generate_surface_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerrainSettings {
+    pub seed: u32,
+    /// Amount of noise layers, each adding finer details.
+    pub octaves: usize,
+    /// Horizontal frequency of the noise per block, so that lower values
+    /// make hills wider.
+    pub frequency: f64,
+    /// Frequency multiplier between successive octaves.
+    pub lacunarity: f64,
+    /// Amplitude multiplier between successive octaves, so that lower values
+    /// make terrain smoother.
+    pub persistence: f64,
+    /// Vertical size of terrain features in blocks, so that higher values
+    /// raise taller mountains.
+    pub height_scale: f64,
+    /// Height at which the noise starts, shifting terrain features up or down.
+    pub base_height: f64,
+    pub sea_level: u16,
+}
+
+impl Default for TerrainSettings {
+    fn default() -> Self {
+        Self {
+            seed: Fbm::<Perlin>::DEFAULT_SEED,
+            octaves: Fbm::<Perlin>::DEFAULT_OCTAVE_COUNT,
+            frequency: 1.0 / CHUNK_SIZE_F64,
+            lacunarity: Fbm::<Perlin>::DEFAULT_LACUNARITY,
+            persistence: Fbm::<Perlin>::DEFAULT_PERSISTENCE,
+            height_scale: CHUNK_HEIGHT_F64,
+            base_height: 0.0,
+            sea_level: SEA_LEVEL,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Cube whose size is specified by [`CHUNK_SIZE`] constant.
@@ -37,29 +109,97 @@ impl SubChunk {
     };
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-/// Part of the world consisting of subchunks, number of which is specified by
-/// [`SUBCHUNK_COUNT`] constant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Part of the world consisting of subchunks, number of which is
+/// [`SUBCHUNK_COUNT`] by default.
 pub struct Chunk {
     /// Chunk location on a 2D grid
     pub origin: IVec2,
-    /// Array of chunk vertical sections
-    pub subchunks: [SubChunk; SUBCHUNK_COUNT],
+    /// Chunk vertical sections, from bottom to top
+    pub subchunks: Vec<SubChunk>,
 }
 
-impl Chunk {
-    #[allow(clippy::large_stack_arrays)]
-    pub const EMPTY: Self = Self {
-        origin: IVec2::ZERO,
-        subchunks: [SubChunk::EMPTY; SUBCHUNK_COUNT],
-    };
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+/// Statistics about blocks and light levels of a chunk, used for debugging.
+pub struct ChunkSummary {
+    /// Amount of blocks of every ID, including air (`0`).
+    pub block_counts: BTreeMap<u8, usize>,
+    /// Amount of blocks with every sky light level.
+    pub sky_light: [usize; 16],
+    /// Amount of blocks with every block light level.
+    pub block_light: [usize; 16],
+}
 
-    pub fn deserialize<T: AsRef<[u8]>>(data: T) -> io::Result<Self> {
-        let mut value = Self::EMPTY;
+impl ChunkSummary {
+    fn average(levels: &[usize; 16]) -> f32 {
+        let (sum, count) = levels
+            .iter()
+            .enumerate()
+            .fold((0, 0), |(sum, count), (level, amount)| {
+                (sum + level * amount, count + amount)
+            });
+
+        if count == 0 {
+            0.0
+        } else {
+            sum as f32 / count as f32
+        }
+    }
+}
+
+impl fmt::Display for ChunkSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "blocks:")?;
+
+        for (id, count) in &self.block_counts {
+            write!(f, " #{id} x{count}")?;
+        }
+
+        write!(
+            f,
+            ", average sky light: {:.2}, average block light: {:.2}",
+            Self::average(&self.sky_light),
+            Self::average(&self.block_light)
+        )
+    }
+}
 
+impl Chunk {
+    /// Version of the format written by [`Chunk::serialize`].
+    pub const FORMAT_VERSION: u8 = 2;
+    /// Size of format version, origin and subchunk count in bytes.
+    const HEADER_SIZE: usize = 13;
+    /// Largest amount of subchunks a chunk may have, so that its height in
+    /// blocks still fits into [`u16`].
+    const MAX_SUBCHUNK_COUNT: usize = u16::MAX as usize / CHUNK_SIZE;
+    /// Version of the format storing block ID and light level of every block
+    /// as is, which can still be read by [`Chunk::deserialize`].
+    pub const RAW_FORMAT_VERSION: u8 = 1;
+
+    /// Reads chunk written by [`Chunk::serialize`], or in the older
+    /// [raw format](Chunk::RAW_FORMAT_VERSION).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error with [`io::ErrorKind::InvalidData`] kind if the data
+    /// has unknown format version, more subchunks than a chunk may have or
+    /// the rest of the data can hold, or invalid runs, or
+    /// [`io::ErrorKind::UnexpectedEof`] if it's truncated.
+    pub fn deserialize<T: AsRef<[u8]>>(data: T) -> io::Result<Self> {
         let mut data = data.as_ref();
 
-        value.origin = {
+        let mut version = [0; 1];
+
+        data.read_exact(&mut version)?;
+
+        if version[0] != Self::FORMAT_VERSION && version[0] != Self::RAW_FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown chunk format version: {}", version[0]),
+            ));
+        }
+
+        let origin = {
             let mut x = [0; 4];
             let mut z = [0; 4];
 
@@ -72,73 +212,184 @@ impl Chunk {
             IVec2::new(x, z)
         };
 
-        // for y in 0..CHUNK_HEIGHT {
-        //     for z in 0..CHUNK_SIZE {
-        //         for x in 0..CHUNK_SIZE {
-        //             let mut buf = [0; 2];
+        let subchunk_count = {
+            let mut count = [0; 4];
 
-        //             data.read_exact(&mut buf)?;
+            data.read_exact(&mut count)?;
 
-        //             value.blocks[y][z][x] = buf[0];
-        //             value.light_levels[y][z][x] = buf[1];
-        //         }
-        //     }
-        // }
+            u32::from_be_bytes(count) as usize
+        };
+
+        // Checked before anything is allocated, so that corrupted data can't
+        // make the chunk huge. Raw blocks take two bytes each, while a run
+        // covers up to `u16::MAX` blocks in four bytes.
+        let total = subchunk_count * CHUNK_SIZE.pow(3);
+        let min_len = if version[0] == Self::RAW_FORMAT_VERSION {
+            total * 2
+        } else {
+            total.div_ceil(usize::from(u16::MAX)) * 4
+        };
+
+        if subchunk_count > Self::MAX_SUBCHUNK_COUNT || data.len() < min_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "invalid subchunk count {subchunk_count} for {} bytes of blocks",
+                    data.len()
+                ),
+            ));
+        }
+
+        let mut value = Self::with_subchunk_count(origin, subchunk_count);
+
+        if version[0] == Self::RAW_FORMAT_VERSION {
+            for subchunk in &mut value.subchunks {
+                for y in 0..CHUNK_SIZE {
+                    for z in 0..CHUNK_SIZE {
+                        for x in 0..CHUNK_SIZE {
+                            let mut buf = [0; 2];
+
+                            data.read_exact(&mut buf)?;
+
+                            subchunk.blocks[y][z][x] = buf[0];
+                            subchunk.light_levels[y][z][x] = buf[1];
+                        }
+                    }
+                }
+            }
+
+            return Ok(value);
+        }
+
+        let mut cells = value.subchunks.iter_mut().flat_map(|subchunk| {
+            let SubChunk {
+                blocks,
+                light_levels,
+            } = subchunk;
+
+            blocks
+                .as_flattened_mut()
+                .as_flattened_mut()
+                .iter_mut()
+                .zip(light_levels.as_flattened_mut().as_flattened_mut())
+        });
+        let mut filled = 0;
+
+        while filled < total {
+            let mut run = [0; 4];
+
+            data.read_exact(&mut run)?;
+
+            let count = usize::from(u16::from_be_bytes([run[0], run[1]]));
+
+            if count == 0 || filled + count > total {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid run of {count} blocks after {filled} of {total} blocks"),
+                ));
+            }
+
+            for (block, light_level) in cells.by_ref().take(count) {
+                *block = run[2];
+                *light_level = run[3];
+            }
+
+            filled += count;
+        }
 
         Ok(value)
     }
 
+    /// Returns runs of blocks with the same block ID and light level, going
+    /// through subchunks from the bottom to the top and through blocks of
+    /// every subchunk in `y`, `z`, `x` order.
+    fn runs(&self) -> impl Iterator<Item = (u16, u8, u8)> + '_ {
+        let mut cells = self
+            .subchunks
+            .iter()
+            .flat_map(|subchunk| {
+                subchunk
+                    .blocks
+                    .as_flattened()
+                    .as_flattened()
+                    .iter()
+                    .copied()
+                    .zip(
+                        subchunk
+                            .light_levels
+                            .as_flattened()
+                            .as_flattened()
+                            .iter()
+                            .copied(),
+                    )
+            })
+            .peekable();
+
+        iter::from_fn(move || {
+            let cell = cells.next()?;
+            let mut count = 1;
+
+            while count < u16::MAX && cells.next_if_eq(&cell).is_some() {
+                count += 1;
+            }
+
+            Some((count, cell.0, cell.1))
+        })
+    }
+
+    /// Returns length of the data written by [`Chunk::serialize`] without
+    /// writing it.
+    pub fn serialized_len(&self) -> usize {
+        Self::HEADER_SIZE + self.runs().count() * 4
+    }
+
+    /// Writes format version, origin, subchunk count and then run-length
+    /// encoded blocks as `(count: u16, block_id: u8, light_level: u8)` runs.
     #[must_use]
     pub fn serialize(&self) -> Vec<u8> {
-        let mut data = Vec::new();
+        let mut data = Vec::with_capacity(self.serialized_len());
 
+        data.push(Self::FORMAT_VERSION);
         data.extend_from_slice(&self.origin.x.to_be_bytes());
         data.extend_from_slice(&self.origin.y.to_be_bytes());
+        data.extend_from_slice(&(self.subchunks.len() as u32).to_be_bytes());
 
-        // for y in 0..CHUNK_HEIGHT {
-        //     for z in 0..CHUNK_SIZE {
-        //         for x in 0..CHUNK_SIZE {
-        //             data.push(self.blocks[y][z][x]);
-        //             data.push(self.light_levels[y][z][x]);
-        //         }
-        //     }
-        // }
+        for (count, block, light_level) in self.runs() {
+            data.extend_from_slice(&count.to_be_bytes());
+            data.push(block);
+            data.push(light_level);
+        }
 
         data
     }
 
     pub fn to_local(&self, position: Vec3) -> U16Vec3 {
-        let position = position.floor();
-
-        let x = (position.x.rem_euclid(CHUNK_SIZE_F32)) as u16;
-        let y = position.y.floor() as u16;
-        let z = (position.z.rem_euclid(CHUNK_SIZE_F32)) as u16;
-
-        U16Vec3::new(x, y, z)
+        coords::world_to_local(position)
     }
 
     pub const fn get_subchunk_index(&self, y: usize) -> [usize; 2] {
-        [y >> 4, y.rem_euclid(CHUNK_SIZE)]
+        coords::subchunk_index(y)
+    }
+
+    pub const fn to_world(&self, position: U16Vec3) -> IVec3 {
+        coords::local_to_world(self.origin, position)
     }
 
-    pub fn to_world(&self, position: U16Vec3) -> IVec3 {
-        let IVec2 { x, y } = self.origin;
+    pub const fn subchunk_count(&self) -> usize {
+        self.subchunks.len()
+    }
 
-        IVec3::new(
-            (x * CHUNK_SIZE_I32) + i32::from(position.x),
-            i32::from(position.y),
-            (y * CHUNK_SIZE_I32) + i32::from(position.z),
-        )
+    /// Returns chunk height in blocks.
+    pub const fn height(&self) -> u16 {
+        self.subchunks.len() as u16 * CHUNK_SIZE_U16
     }
 
     pub const fn contains_local_position(&self, position: U16Vec3) -> bool {
-        position.x < CHUNK_SIZE_U16 && position.y < CHUNK_HEIGHT_U16 && position.z < CHUNK_SIZE_U16
+        position.x < CHUNK_SIZE_U16 && position.y < self.height() && position.z < CHUNK_SIZE_U16
     }
 
     pub fn contains_position(&self, position: Vec3) -> bool {
-        self.origin.x == (position.x.floor() as i32 >> 4)
-            && self.origin.y == (position.z.floor() as i32 >> 4)
-            && (0..SUBCHUNK_COUNT).contains(&((position.y.floor() as i32 >> 4) as usize))
+        self.origin == coords::world_to_chunk(position) && self.get_subchunk(position.y).is_some()
     }
 
     pub fn set_block(&mut self, position: U16Vec3, block: u8) {
@@ -169,15 +420,23 @@ impl Chunk {
 
         let block_id = self.subchunks[subchunk].blocks[y][z][x];
 
-        if block_id == 0 { None } else { Some(block_id) }
+        if block_id == AIR_BLOCK {
+            None
+        } else {
+            Some(block_id)
+        }
     }
 
     pub fn get_subchunk(&self, y: f32) -> Option<&SubChunk> {
-        self.subchunks.get((y.floor() as i32 >> 4) as usize)
+        usize::try_from(coords::world_to_subchunk(y))
+            .ok()
+            .and_then(|index| self.subchunks.get(index))
     }
 
     pub fn get_subchunk_mut(&mut self, y: f32) -> Option<&mut SubChunk> {
-        self.subchunks.get_mut((y.floor() as i32 >> 4) as usize)
+        usize::try_from(coords::world_to_subchunk(y))
+            .ok()
+            .and_then(|index| self.subchunks.get_mut(index))
     }
 
     pub fn get_light_level(&self, position: U16Vec3) -> u8 {
@@ -241,17 +500,124 @@ impl Chunk {
         *level = (*level & 0xF0) | value;
     }
 
-    pub const fn new(origin: IVec2) -> Self {
+    /// Returns height of the highest non-air block in the column at the given
+    /// local coordinates.
+    pub fn surface_height(&self, x: u16, z: u16) -> Option<u16> {
+        (0..self.height())
+            .rev()
+            .find(|&y| self.get_block(U16Vec3::new(x, y, z)).is_some())
+    }
+
+    pub fn new(origin: IVec2) -> Self {
+        Self::with_subchunk_count(origin, SUBCHUNK_COUNT)
+    }
+
+    pub fn with_subchunk_count(origin: IVec2, subchunk_count: usize) -> Self {
         Self {
             origin,
-            ..Self::EMPTY
+            subchunks: vec![SubChunk::EMPTY; subchunk_count],
+        }
+    }
+
+    pub fn summary(&self) -> ChunkSummary {
+        let mut summary = ChunkSummary::default();
+
+        for subchunk in &self.subchunks {
+            for (blocks, light_levels) in subchunk.blocks.iter().zip(&subchunk.light_levels) {
+                for (blocks, light_levels) in blocks.iter().zip(light_levels) {
+                    for (&block, &light_level) in blocks.iter().zip(light_levels) {
+                        *summary.block_counts.entry(block).or_default() += 1;
+
+                        summary.sky_light[usize::from(light_level >> 4)] += 1;
+                        summary.block_light[usize::from(light_level & 0xF)] += 1;
+                    }
+                }
+            }
+        }
+
+        summary
+    }
+
+    /// Fills the chunk with dirt up to the given height, covering the top
+    /// layer with grass.
+    pub fn generate_flat(&mut self, height: u16) {
+        let height = height.min(self.height());
+
+        for y in 0..height {
+            let block = if y == height - 1 { 2 } else { 1 };
+
+            for z in 0..CHUNK_SIZE_U16 {
+                for x in 0..CHUNK_SIZE_U16 {
+                    self.set_block_unchecked(U16Vec3::new(x, y, z), block);
+                }
+            }
+        }
+    }
+
+    /// Carves caves out of blocks below the surface wherever ridged noise is
+    /// denser than the threshold, so that raising the threshold makes caves
+    /// rarer and narrower. Caves never break through the topmost block of a
+    /// column, which is left as a crust over them. Water is never carved, so
+    /// that seas don't drain into caves.
+    pub fn carve_caves(&mut self, seed: u32, threshold: f64) {
+        let generator = RidgedMulti::<Perlin>::new(seed);
+        let position = self.origin.as_dvec2() * CHUNK_SIZE_F64;
+
+        for z in 0..CHUNK_SIZE_U16 {
+            for x in 0..CHUNK_SIZE_U16 {
+                let Some(surface) = self.surface_height(x, z) else {
+                    continue;
+                };
+
+                for y in 0..surface {
+                    let position_in_chunk = U16Vec3::new(x, y, z);
+
+                    if self.get_block_unchecked(position_in_chunk) == Some(WATER_BLOCK) {
+                        continue;
+                    }
+
+                    let density = generator.get([
+                        (position.x + f64::from(x)) / CAVE_SCALE,
+                        f64::from(y) / CAVE_SCALE,
+                        (position.y + f64::from(z)) / CAVE_SCALE,
+                    ]);
+
+                    if density > threshold {
+                        self.set_block_unchecked(position_in_chunk, AIR_BLOCK);
+                    }
+                }
+            }
         }
     }
 
+    /// Generates terrain with the given seed, leaving the rest of
+    /// [`TerrainSettings`] default.
     pub fn generate_surface(&mut self, seed: u32) {
-        let generator = Fbm::<Perlin>::new(seed);
+        self.generate_surface_with(&TerrainSettings {
+            seed,
+            ..TerrainSettings::default()
+        });
+    }
 
-        let position = self.origin.as_vec2() * CHUNK_SIZE_F32;
+    /// Generates hills out of noise, flooding air below the sea level with
+    /// water. Grass doesn't grow under water, so the top blocks of flooded
+    /// columns are dirt.
+    pub fn generate_surface_with(&mut self, settings: &TerrainSettings) {
+        let generator = Fbm::<Perlin>::new(settings.seed)
+            .set_octaves(settings.octaves)
+            .set_lacunarity(settings.lacunarity)
+            .set_persistence(settings.persistence);
+
+        let position = self.origin.as_dvec2() * CHUNK_SIZE_F64;
+        let height = self.height();
+        let sea_level = settings.sea_level.min(height);
+        let is_solid = |x: u16, y: u16, z: u16| {
+            generator.get([
+                (position.x + f64::from(x)) * settings.frequency,
+                (f64::from(y) - settings.base_height) / settings.height_scale,
+                (position.y + f64::from(z)) * settings.frequency,
+            ]) > 0.0
+        };
         // let spline = Spline::from_iter([
         //     Key::new(-1.0, 100.0, Interpolation::Cosine),
         //     Key::new(0.3, 100.0, Interpolation::Cosine),
@@ -259,44 +625,24 @@ impl Chunk {
         //     Key::new(1.0, 150.0, Interpolation::Cosine),
         // ]);
 
-        for z in 0..CHUNK_SIZE {
-            for x in 0..CHUNK_SIZE {
-                let mut max = 0;
-
-                for y in 0..CHUNK_HEIGHT {
-                    let value = generator.get([
-                        (f64::from(position.x) + x as f64) / CHUNK_SIZE_F64,
-                        y as f64 / CHUNK_HEIGHT_F64,
-                        (f64::from(position.y) + z as f64) / CHUNK_SIZE_F64,
-                    ]);
+        for z in 0..CHUNK_SIZE_U16 {
+            for x in 0..CHUNK_SIZE_U16 {
+                for y in 0..height {
+                    let position = U16Vec3::new(x, y, z);
 
-                    if value > 0.0 {
-                        max = max.max(y);
+                    if is_solid(x, y, z) {
+                        let is_covered = y + 1 < height && is_solid(x, y + 1, z);
 
-                        if y == CHUNK_HEIGHT - 1 {
-                            self.set_block_unchecked(
-                                self.to_local(vec3(x as f32, y as f32, z as f32)),
-                                2,
-                            );
-                        } else {
-                            let value = generator.get([
-                                (f64::from(position.x) + x as f64) / CHUNK_SIZE_F64,
-                                (y + 1).min(CHUNK_HEIGHT - 1) as f64 / CHUNK_HEIGHT_F64,
-                                (f64::from(position.y) + z as f64) / CHUNK_SIZE_F64,
-                            ]);
-
-                            if value <= 0.0 {
-                                self.set_block_unchecked(
-                                    self.to_local(vec3(x as f32, y as f32, z as f32)),
-                                    2,
-                                );
+                        self.set_block_unchecked(
+                            position,
+                            if is_covered || y + 1 < sea_level {
+                                1
                             } else {
-                                self.set_block_unchecked(
-                                    self.to_local(vec3(x as f32, y as f32, z as f32)),
-                                    1,
-                                );
-                            }
-                        }
+                                2
+                            },
+                        );
+                    } else if y < sea_level {
+                        self.set_block_unchecked(position, WATER_BLOCK);
                     }
                 }
             }
@@ -314,13 +660,429 @@ mod tests {
 
         chunk.generate_surface(0);
 
-        let serialized = chunk.serialize();
-
-        println!("{}", serialized.len());
+        chunk.set_sky_light(U16Vec3::new(3, 100, 4), 12);
+        chunk.set_block_light(U16Vec3::new(3, 100, 4), 7);
 
+        let serialized = chunk.serialize();
         let deserialized = Chunk::deserialize(&serialized).unwrap();
 
         assert_eq!(chunk.origin, deserialized.origin);
         assert_eq!(chunk.subchunks, deserialized.subchunks);
+
+        let mut chunk = Chunk::with_subchunk_count(IVec2::new(-3, 7), 4);
+
+        chunk.set_block(U16Vec3::new(15, 63, 15), 2);
+
+        assert_eq!(Chunk::deserialize(chunk.serialize()).unwrap(), chunk);
+    }
+
+    #[test]
+    fn test_chunk_run_length_encoding() {
+        use super::*;
+
+        let mut chunk = Chunk::new(IVec2::new(2, -1));
+
+        chunk.generate_surface(0);
+
+        let serialized = chunk.serialize();
+
+        assert_eq!(serialized.len(), chunk.serialized_len());
+        assert!(serialized.len() < CHUNK_HEIGHT * CHUNK_SIZE * CHUNK_SIZE);
+        assert_eq!(Chunk::deserialize(&serialized).unwrap(), chunk);
+
+        // Runs longer than u16::MAX are split.
+        let empty = Chunk::new(IVec2::ZERO);
+
+        assert_eq!(empty.serialized_len(), 13 + 4 * 2);
+        assert_eq!(Chunk::deserialize(empty.serialize()).unwrap(), empty);
+    }
+
+    #[test]
+    fn test_raw_chunk_deserialization() {
+        use super::*;
+
+        let mut chunk = Chunk::with_subchunk_count(IVec2::new(4, 5), 2);
+
+        chunk.set_block(U16Vec3::new(1, 2, 3), 2);
+        chunk.set_sky_light(U16Vec3::new(1, 3, 3), 15);
+
+        let mut data = vec![Chunk::RAW_FORMAT_VERSION];
+
+        data.extend_from_slice(&4i32.to_be_bytes());
+        data.extend_from_slice(&5i32.to_be_bytes());
+        data.extend_from_slice(&2u32.to_be_bytes());
+
+        for subchunk in &chunk.subchunks {
+            for (block, light_level) in subchunk
+                .blocks
+                .as_flattened()
+                .as_flattened()
+                .iter()
+                .zip(subchunk.light_levels.as_flattened().as_flattened())
+            {
+                data.extend([*block, *light_level]);
+            }
+        }
+
+        assert_eq!(Chunk::deserialize(&data).unwrap(), chunk);
+    }
+
+    #[test]
+    fn test_chunk_serialization_errors() {
+        use super::*;
+
+        let mut chunk = Chunk::new(IVec2::new(1, 2));
+
+        // more runs than the subchunk count requires at least
+        chunk.set_block(U16Vec3::new(3, 100, 4), 1);
+
+        let mut serialized = chunk.serialize();
+
+        assert_eq!(
+            Chunk::deserialize(&serialized[..serialized.len() - 1])
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        // subchunk counts too large for a chunk or for the data
+        for subchunk_count in [u32::MAX, 4096, 100] {
+            let mut data = serialized.clone();
+
+            data[9..13].copy_from_slice(&subchunk_count.to_be_bytes());
+
+            assert_eq!(
+                Chunk::deserialize(&data).unwrap_err().kind(),
+                io::ErrorKind::InvalidData
+            );
+        }
+
+        let mut raw = vec![Chunk::RAW_FORMAT_VERSION];
+
+        raw.extend_from_slice(&[0; 8]);
+        raw.extend_from_slice(&2u32.to_be_bytes());
+        raw.extend_from_slice(&[0; 2 * CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE]);
+
+        assert_eq!(
+            Chunk::deserialize(&raw).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+
+        serialized[0] = Chunk::FORMAT_VERSION + 1;
+
+        assert_eq!(
+            Chunk::deserialize(&serialized).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_chunk_summary() {
+        use super::*;
+
+        let mut chunk = Chunk::new(IVec2::new(0, 0));
+
+        chunk.set_block(U16Vec3::new(0, 0, 0), 1);
+        chunk.set_block(U16Vec3::new(1, 0, 0), 1);
+        chunk.set_block(U16Vec3::new(5, 200, 7), 2);
+        chunk.set_sky_light(U16Vec3::new(0, 255, 0), 15);
+        chunk.set_block_light(U16Vec3::new(0, 255, 0), 4);
+
+        let summary = chunk.summary();
+        let total = CHUNK_SIZE * CHUNK_SIZE * CHUNK_HEIGHT;
+
+        assert_eq!(summary.block_counts.len(), 3);
+        assert_eq!(summary.block_counts[&0], total - 3);
+        assert_eq!(summary.block_counts[&1], 2);
+        assert_eq!(summary.block_counts[&2], 1);
+        assert_eq!(summary.sky_light[15], 1);
+        assert_eq!(summary.sky_light[0], total - 1);
+        assert_eq!(summary.block_light[4], 1);
+    }
+
+    #[test]
+    fn test_air_is_not_a_block() {
+        use super::*;
+
+        let mut chunk = Chunk::new(IVec2::new(0, 0));
+
+        chunk.set_block(U16Vec3::new(3, 64, 3), 1);
+        chunk.set_block(U16Vec3::new(3, 64, 3), AIR_BLOCK);
+
+        assert_eq!(chunk.get_block(U16Vec3::new(3, 64, 3)), None);
+        assert!(!chunk.check_for_block(Vec3::new(3.0, 64.0, 3.0)));
+    }
+
+    #[test]
+    fn test_custom_subchunk_count() {
+        use super::*;
+
+        for subchunk_count in [8, 24] {
+            let mut chunk = Chunk::with_subchunk_count(IVec2::new(0, 0), subchunk_count);
+            let top = U16Vec3::new(15, chunk.height() - 1, 15);
+
+            assert_eq!(chunk.height(), subchunk_count as u16 * CHUNK_SIZE_U16);
+
+            chunk.set_block(U16Vec3::ZERO, 1);
+            chunk.set_block(top, 2);
+            chunk.set_block(top.with_y(chunk.height()), 3);
+
+            assert_eq!(chunk.get_block(U16Vec3::ZERO), Some(1));
+            assert_eq!(chunk.get_block(top), Some(2));
+            assert_eq!(chunk.get_block(top.with_y(chunk.height())), None);
+            assert_eq!(chunk.surface_height(15, 15), Some(chunk.height() - 1));
+            assert!(chunk.check_for_block(top.as_vec3()));
+        }
+    }
+
+    #[test]
+    fn test_flat_generation() {
+        use super::*;
+
+        let mut chunk = Chunk::new(IVec2::new(0, 0));
+
+        chunk.generate_flat(64);
+
+        assert_eq!(chunk.surface_height(0, 0), Some(63));
+        assert_eq!(chunk.surface_height(15, 7), Some(63));
+        assert_eq!(chunk.get_block(U16Vec3::new(4, 63, 4)), Some(2));
+        assert_eq!(chunk.get_block(U16Vec3::new(4, 0, 4)), Some(1));
+        assert_eq!(chunk.get_block(U16Vec3::new(4, 64, 4)), None);
+
+        let mut chunk = Chunk::with_subchunk_count(IVec2::new(0, 0), 2);
+
+        chunk.generate_flat(u16::MAX);
+
+        assert_eq!(chunk.surface_height(0, 0), Some(chunk.height() - 1));
+    }
+
+    #[test]
+    fn test_cave_carving() {
+        use super::*;
+
+        let mut chunk = Chunk::new(IVec2::new(3, -2));
+
+        chunk.generate_flat(200);
+
+        let carved = chunk.clone();
+
+        chunk.carve_caves(12723, CAVE_THRESHOLD);
+
+        let positions = (0..200).flat_map(|y| {
+            (0..CHUNK_SIZE_U16)
+                .flat_map(move |z| (0..CHUNK_SIZE_U16).map(move |x| U16Vec3::new(x, y, z)))
+        });
+        let holes = positions
+            .filter(|&position| chunk.get_block(position).is_none())
+            .collect::<Vec<_>>();
+
+        assert!(!holes.is_empty());
+        assert!(
+            holes
+                .iter()
+                .all(|&position| carved.get_block(position).is_some())
+        );
+
+        // the surface crust is kept intact
+        for z in 0..CHUNK_SIZE_U16 {
+            for x in 0..CHUNK_SIZE_U16 {
+                assert_eq!(chunk.get_block(U16Vec3::new(x, 199, z)), Some(2));
+            }
+        }
+
+        // nothing is carved with a threshold above the noise range
+        let mut uncarved = carved.clone();
+
+        uncarved.carve_caves(12723, 2.0);
+
+        assert_eq!(uncarved.subchunks, carved.subchunks);
+    }
+
+    #[test]
+    fn test_sea_level() {
+        use super::*;
+
+        let mut dry = Chunk::new(IVec2::new(1, 2));
+        let mut flooded = dry.clone();
+
+        dry.generate_surface_with(&TerrainSettings {
+            seed: 4,
+            sea_level: 0,
+            ..TerrainSettings::default()
+        });
+        flooded.generate_surface_with(&TerrainSettings {
+            seed: 4,
+            sea_level: 128,
+            ..TerrainSettings::default()
+        });
+
+        let mut water = 0;
+
+        for y in 0..dry.height() {
+            for z in 0..CHUNK_SIZE_U16 {
+                for x in 0..CHUNK_SIZE_U16 {
+                    let position = U16Vec3::new(x, y, z);
+                    let block = flooded.get_block(position);
+
+                    match dry.get_block(position) {
+                        None if y < 128 => {
+                            assert_eq!(block, Some(WATER_BLOCK), "{position}");
+
+                            water += 1;
+                        }
+                        // grass under water turns into dirt
+                        Some(2) if y < 127 => assert_eq!(block, Some(1), "{position}"),
+                        expected => assert_eq!(block, expected, "{position}"),
+                    }
+                }
+            }
+        }
+
+        assert!(water > 0);
+    }
+
+    #[test]
+    fn test_terrain_height_scale() {
+        use super::*;
+
+        let surface_heights = |height_scale| {
+            let mut chunk = Chunk::new(IVec2::new(2, -1));
+
+            chunk.generate_surface_with(&TerrainSettings {
+                seed: 7,
+                height_scale,
+                sea_level: 0,
+                ..TerrainSettings::default()
+            });
+
+            (0..CHUNK_SIZE_U16)
+                .flat_map(|z| (0..CHUNK_SIZE_U16).map(move |x| (x, z)))
+                .filter_map(|(x, z)| chunk.surface_height(x, z))
+                .collect::<Vec<_>>()
+        };
+
+        let [low, high] = [32.0, CHUNK_HEIGHT_F64].map(surface_heights);
+
+        // Solidity is sampled from 3D noise without any falloff towards the
+        // top of the world, so some column reaches it whatever the scale is.
+        // The scale changes the shape of the terrain instead, which shows in
+        // heights of separate columns.
+        for heights in [&low, &high] {
+            assert_eq!(heights.iter().max(), Some(&(CHUNK_HEIGHT_U16 - 1)));
+        }
+
+        assert_ne!(low, high);
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_constants_consistency() {
+        use super::*;
+
+        assert_eq!(usize::from(CHUNK_SIZE_U16), CHUNK_SIZE);
+        assert_eq!(CHUNK_SIZE_I32 as usize, CHUNK_SIZE);
+        assert_eq!(CHUNK_SIZE_F32, CHUNK_SIZE as f32);
+        assert_eq!(CHUNK_SIZE_F64, CHUNK_SIZE as f64);
+
+        assert_eq!(usize::from(SUBCHUNK_COUNT_U16), SUBCHUNK_COUNT);
+        assert_eq!(SUBCHUNK_COUNT_I32 as usize, SUBCHUNK_COUNT);
+        assert_eq!(SUBCHUNK_COUNT_F32, SUBCHUNK_COUNT as f32);
+        assert_eq!(SUBCHUNK_COUNT_F64, SUBCHUNK_COUNT as f64);
+
+        assert_eq!(usize::from(CHUNK_HEIGHT_U16), CHUNK_HEIGHT);
+        assert_eq!(CHUNK_HEIGHT_I32 as usize, CHUNK_HEIGHT);
+        assert_eq!(CHUNK_HEIGHT_F32, CHUNK_HEIGHT as f32);
+        assert_eq!(CHUNK_HEIGHT_F64, CHUNK_HEIGHT as f64);
+        assert_eq!(Chunk::new(IVec2::ZERO).height(), CHUNK_HEIGHT_U16);
+    }
+
+    mod round_trip {
+        use glam::{IVec2, U16Vec3};
+        use proptest::{array::uniform5, collection::vec, prelude::*};
+
+        use crate::{CHUNK_SIZE_U16, Chunk, SUBCHUNK_COUNT, TerrainSettings};
+
+        impl Arbitrary for Chunk {
+            type Parameters = ();
+            type Strategy = BoxedStrategy<Self>;
+
+            /// Generates chunks by changing random blocks and light levels of
+            /// an empty chunk, so that failing chunks shrink to fewer changes.
+            fn arbitrary_with((): Self::Parameters) -> Self::Strategy {
+                (any::<[i32; 2]>(), 1..=SUBCHUNK_COUNT)
+                    .prop_flat_map(|(origin, subchunk_count)| {
+                        let height = CHUNK_SIZE_U16 * subchunk_count as u16;
+                        let change = (
+                            0..CHUNK_SIZE_U16,
+                            0..height,
+                            0..CHUNK_SIZE_U16,
+                            any::<u8>(),
+                            0..16u8,
+                            0..16u8,
+                        );
+
+                        vec(change, 0..512).prop_map(move |changes| {
+                            let mut chunk = Self::with_subchunk_count(
+                                IVec2::from_array(origin),
+                                subchunk_count,
+                            );
+
+                            for (x, y, z, block, sky_light, block_light) in changes {
+                                let position = U16Vec3::new(x, y, z);
+
+                                chunk.set_block(position, block);
+                                chunk.set_sky_light(position, sky_light);
+                                chunk.set_block_light(position, block_light);
+                            }
+
+                            chunk
+                        })
+                    })
+                    .boxed()
+            }
+        }
+
+        prop_compose! {
+            fn terrain_settings()(
+                seed in any::<u32>(),
+                octaves in 0..8usize,
+                // JSON has no representation of infinities and NaN
+                [frequency, lacunarity, persistence, height_scale, base_height] in uniform5(
+                    prop::num::f64::NORMAL | prop::num::f64::SUBNORMAL | prop::num::f64::ZERO,
+                ),
+                sea_level in any::<u16>(),
+            ) -> TerrainSettings {
+                TerrainSettings {
+                    seed,
+                    octaves,
+                    frequency,
+                    lacunarity,
+                    persistence,
+                    height_scale,
+                    base_height,
+                    sea_level,
+                }
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn test_chunk_round_trip(chunk: Chunk) {
+                let serialized = chunk.serialize();
+
+                prop_assert_eq!(serialized.len(), chunk.serialized_len());
+                prop_assert_eq!(Chunk::deserialize(serialized).unwrap(), chunk);
+            }
+
+            #[test]
+            fn test_terrain_settings_round_trip(settings in terrain_settings()) {
+                let serialized = serde_json::to_string(&settings).unwrap();
+
+                prop_assert_eq!(
+                    serde_json::from_str::<TerrainSettings>(&serialized).unwrap(),
+                    settings
+                );
+            }
+        }
     }
 }
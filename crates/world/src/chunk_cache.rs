@@ -0,0 +1,284 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::Path,
+};
+
+use glam::IVec2;
+
+use crate::ChunkManager;
+
+/// Keeps amount of loaded chunks within a budget by dropping ones that were
+/// seen least recently.
+#[derive(Debug, Clone)]
+pub struct ChunkCache {
+    /// Largest amount of chunks kept loaded.
+    budget: usize,
+    frame: u64,
+    /// Frames chunks were last seen on, with chunks never seen counting as
+    /// seen on the first frame.
+    last_seen: HashMap<IVec2, u64>,
+    /// Origins of dropped chunks, which can be loaded back from their files.
+    evicted: HashSet<IVec2>,
+}
+
+impl ChunkCache {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            frame: 0,
+            last_seen: HashMap::new(),
+            evicted: HashSet::new(),
+        }
+    }
+
+    pub const fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Starts the next frame, after which touched chunks count as seen more
+    /// recently than ones touched before.
+    pub const fn advance(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Marks the chunk as seen on the current frame.
+    pub fn touch(&mut self, origin: IVec2) {
+        self.last_seen.insert(origin, self.frame);
+    }
+
+    /// Returns frame the chunk was last seen on.
+    pub fn last_seen(&self, origin: &IVec2) -> u64 {
+        self.last_seen.get(origin).copied().unwrap_or_default()
+    }
+
+    /// Drops least recently seen chunks until the manager fits into the
+    /// budget, returning their origins. Chunks which were changed or never
+    /// saved are saved into the directory before they are dropped, so that
+    /// [`ChunkCache::reload`] can load them back. Chunks seen on the same
+    /// frame are dropped in order of their origins.
+    ///
+    /// Chunks seen on the current frame are never dropped, even if that keeps
+    /// the manager over the budget, since they would be loaded right back.
+    pub fn evict(
+        &mut self,
+        chunk_manager: &mut ChunkManager,
+        directory: &Path,
+    ) -> io::Result<Vec<IVec2>> {
+        let excess = chunk_manager.len().saturating_sub(self.budget);
+
+        if excess == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut evicted = chunk_manager
+            .chunks()
+            .map(|chunk| chunk.origin)
+            .filter(|origin| self.last_seen.get(origin) != Some(&self.frame))
+            .collect::<Vec<_>>();
+
+        evicted.sort_by_key(|origin| (self.last_seen(origin), origin.x, origin.y));
+        evicted.truncate(excess);
+
+        for origin in &evicted {
+            if (chunk_manager.is_dirty(origin) || !ChunkManager::is_saved(directory, *origin))
+                && let Some(chunk) = chunk_manager.get_chunk(origin)
+            {
+                fs::create_dir_all(directory)?;
+
+                ChunkManager::save_chunk(directory, chunk)?;
+            }
+
+            chunk_manager.remove_chunk(origin);
+
+            self.last_seen.remove(origin);
+            self.evicted.insert(*origin);
+        }
+
+        Ok(evicted)
+    }
+
+    /// Loads chunks dropped by [`ChunkCache::evict`] back from the directory
+    /// once they are within the given distance from the center chunk along
+    /// both axes, returning their origins in order.
+    pub fn reload(
+        &mut self,
+        chunk_manager: &mut ChunkManager,
+        directory: &Path,
+        center: IVec2,
+        distance: i32,
+    ) -> io::Result<Vec<IVec2>> {
+        let mut origins = self
+            .evicted
+            .iter()
+            .copied()
+            .filter(|origin| (*origin - center).abs().max_element() <= distance)
+            .collect::<Vec<_>>();
+        let mut reloaded = Vec::new();
+
+        origins.sort_by_key(|origin| (origin.x, origin.y));
+
+        for origin in origins {
+            let chunk = ChunkManager::load_chunk(directory, origin)?;
+
+            self.evicted.remove(&origin);
+
+            if let Some(chunk) = chunk {
+                chunk_manager.insert_chunk(chunk);
+
+                self.touch(origin);
+
+                reloaded.push(origin);
+            }
+        }
+
+        Ok(reloaded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use glam::{IVec2, U16Vec3, Vec3};
+
+    use super::ChunkCache;
+    use crate::ChunkManager;
+
+    #[test]
+    fn test_evicts_least_recently_seen() {
+        let directory = env::temp_dir().join(format!("meralus-cache-{}", std::process::id()));
+        let mut chunk_manager = ChunkManager::from_range(0..3, &(0..1));
+        let mut cache = ChunkCache::new(3);
+
+        for x in [2, 0, 1] {
+            cache.touch(IVec2::new(x, 0));
+            cache.advance();
+        }
+
+        assert!(
+            cache
+                .evict(&mut chunk_manager, &directory)
+                .unwrap()
+                .is_empty()
+        );
+
+        // loading another chunk exceeds the budget
+        chunk_manager.ensure_loaded(IVec2::new(3, 0), 0, 0);
+        cache.touch(IVec2::new(3, 0));
+        chunk_manager.set_block(Vec3::new(40.0, 64.0, 3.0), 2);
+
+        // the changed chunk is saved before it's dropped
+        assert_eq!(cache.evict(&mut chunk_manager, &directory).unwrap(), [
+            IVec2::new(2, 0)
+        ]);
+        assert_eq!(chunk_manager.len(), 3);
+        assert!(!chunk_manager.contains_chunk(&IVec2::new(2, 0)));
+        assert_eq!(
+            ChunkManager::load_chunk(&directory, IVec2::new(2, 0))
+                .unwrap()
+                .and_then(|chunk| chunk.get_block(U16Vec3::new(8, 64, 3))),
+            Some(2)
+        );
+
+        cache.advance();
+        chunk_manager.ensure_loaded(IVec2::new(4, 0), 0, 0);
+        cache.touch(IVec2::new(4, 0));
+
+        assert_eq!(cache.evict(&mut chunk_manager, &directory).unwrap(), [
+            IVec2::ZERO
+        ]);
+        // it wasn't changed, but it's saved so that it can be loaded back
+        assert!(ChunkManager::is_saved(&directory, IVec2::ZERO));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_reloads_evicted_chunks() {
+        let directory = env::temp_dir().join(format!("meralus-reload-{}", std::process::id()));
+        let mut chunk_manager = ChunkManager::from_range(0..4, &(0..1));
+        let mut cache = ChunkCache::new(2);
+
+        chunk_manager.set_block(Vec3::new(3.0, 64.0, 3.0), 2);
+
+        for x in [0, 1, 2, 3] {
+            cache.touch(IVec2::new(x, 0));
+            cache.advance();
+        }
+
+        assert_eq!(cache.evict(&mut chunk_manager, &directory).unwrap(), [
+            IVec2::ZERO,
+            IVec2::new(1, 0)
+        ]);
+
+        // the chunks are still too far away
+        assert!(
+            cache
+                .reload(&mut chunk_manager, &directory, IVec2::new(3, 0), 1)
+                .unwrap()
+                .is_empty()
+        );
+        assert_eq!(
+            cache
+                .reload(&mut chunk_manager, &directory, IVec2::new(3, 0), 3)
+                .unwrap(),
+            [IVec2::ZERO, IVec2::new(1, 0)]
+        );
+        assert_eq!(chunk_manager.len(), 4);
+        assert_eq!(chunk_manager.get_block(Vec3::new(3.0, 64.0, 3.0)), Some(2));
+        assert!(!chunk_manager.is_dirty(&IVec2::ZERO));
+
+        // chunks are reloaded only once
+        assert!(
+            cache
+                .reload(&mut chunk_manager, &directory, IVec2::new(3, 0), 3)
+                .unwrap()
+                .is_empty()
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_budget_below_view() {
+        let directory = env::temp_dir().join(format!("meralus-view-{}", std::process::id()));
+        let mut chunk_manager = ChunkManager::from_range(-1..2, &(-1..2));
+        let mut cache = ChunkCache::new(4);
+
+        // all 9 chunks within the reload distance stay in view
+        for _ in 0..3 {
+            cache.advance();
+
+            for chunk in chunk_manager.chunks() {
+                cache.touch(chunk.origin);
+            }
+
+            assert!(
+                cache
+                    .evict(&mut chunk_manager, &directory)
+                    .unwrap()
+                    .is_empty()
+            );
+            assert!(
+                cache
+                    .reload(&mut chunk_manager, &directory, IVec2::ZERO, 1)
+                    .unwrap()
+                    .is_empty()
+            );
+            assert_eq!(chunk_manager.len(), 9);
+        }
+
+        // once out of view, chunks are dropped down to the budget again
+        cache.advance();
+        cache.touch(IVec2::ZERO);
+
+        assert_eq!(
+            cache.evict(&mut chunk_manager, &directory).unwrap().len(),
+            5
+        );
+        assert!(chunk_manager.contains_chunk(&IVec2::ZERO));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
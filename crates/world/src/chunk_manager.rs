@@ -1,34 +1,125 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    fs, io, mem,
+    path::{Path, PathBuf},
+};
 
 use glam::{IVec2, IVec3, U16Vec3, Vec3};
+use image::{GrayImage, ImageResult, Luma};
 use owo_colors::OwoColorize;
 
-use crate::{CHUNK_SIZE_I32, Chunk, SUBCHUNK_COUNT_I32};
+use crate::{
+    AIR_BLOCK, CAVE_THRESHOLD, CHUNK_SIZE_I32, Chunk, SUBCHUNK_COUNT, TerrainSettings, coords,
+};
 
 pub struct ChunkManager {
     chunks: HashMap<IVec2, Chunk>,
+    /// Origins of chunks whose blocks or light were changed since they were
+    /// last taken by [`ChunkManager::take_dirty`].
+    dirty: HashSet<IVec2>,
 }
 
 impl ChunkManager {
     pub fn from_range<T: Iterator<Item = i32> + Clone>(x: T, z: &T) -> Self {
+        Self::from_range_with_subchunk_count(x, z, SUBCHUNK_COUNT)
+    }
+
+    pub fn from_range_with_subchunk_count<T: Iterator<Item = i32> + Clone>(
+        x: T,
+        z: &T,
+        subchunk_count: usize,
+    ) -> Self {
         Self {
             chunks: x
                 .flat_map(|x| {
                     z.clone().map(move |z| {
                         let origin = IVec2::new(x, z);
 
-                        (origin, Chunk::new(origin))
+                        (origin, Chunk::with_subchunk_count(origin, subchunk_count))
                     })
                 })
                 .collect(),
+            dirty: HashSet::new(),
         }
     }
 
+    /// Generates surface terrain with the given seed in chunks missing from the
+    /// square of the given radius around the center, returning their origins.
+    pub fn ensure_loaded(&mut self, center: IVec2, radius: i32, seed: u32) -> Vec<IVec2> {
+        let mut loaded = Vec::new();
+
+        for z in center.y - radius..=center.y + radius {
+            for x in center.x - radius..=center.x + radius {
+                let origin = IVec2::new(x, z);
+
+                if let Entry::Vacant(entry) = self.chunks.entry(origin) {
+                    let chunk = entry.insert(Chunk::new(origin));
+
+                    chunk.generate_surface(seed);
+                    chunk.carve_caves(seed, CAVE_THRESHOLD);
+
+                    loaded.push(origin);
+                }
+            }
+        }
+
+        loaded
+    }
+
+    /// Drops chunks outside of the square of the given radius around the
+    /// center, returning their origins. Changes of dropped chunks which
+    /// weren't saved are lost.
+    pub fn unload_beyond(&mut self, center: IVec2, radius: i32) -> Vec<IVec2> {
+        let unloaded = self
+            .chunks
+            .keys()
+            .copied()
+            .filter(|origin| (*origin - center).abs().max_element() > radius)
+            .collect::<Vec<_>>();
+
+        for origin in &unloaded {
+            self.remove_chunk(origin);
+        }
+
+        unloaded
+    }
+
+    /// Adds the chunk, returning the one it replaced.
+    pub fn insert_chunk(&mut self, chunk: Chunk) -> Option<Chunk> {
+        self.chunks.insert(chunk.origin, chunk)
+    }
+
+    /// Drops the chunk with the given origin, returning it. Changes of the
+    /// chunk which weren't saved are lost.
+    pub fn remove_chunk(&mut self, origin: &IVec2) -> Option<Chunk> {
+        self.dirty.remove(origin);
+        self.chunks.remove(origin)
+    }
+
+    /// Generates flat terrain of the given height in every chunk.
+    pub fn generate_flat(&mut self, height: u16) {
+        for chunk in self.chunks_mut() {
+            chunk.generate_flat(height);
+        }
+    }
+
+    /// Generates hills with the given seed in every chunk, flooding them up to
+    /// the default sea level and carving caves out of them.
     pub fn generate_surface(&mut self, seed: u32) {
+        self.generate_surface_with(&TerrainSettings {
+            seed,
+            ..TerrainSettings::default()
+        });
+    }
+
+    /// Generates terrain with the given settings in every chunk, carving caves
+    /// out of it.
+    pub fn generate_surface_with(&mut self, settings: &TerrainSettings) {
         let chunks = self.len();
 
         for (i, chunk) in self.chunks_mut().enumerate() {
-            chunk.generate_surface(seed);
+            chunk.generate_surface_with(settings);
+            chunk.carve_caves(settings.seed, CAVE_THRESHOLD);
 
             println!(
                 "[{:18}] Generated chunk at {}: {} opaque blocks ({} / {chunks})",
@@ -52,48 +143,197 @@ impl ChunkManager {
         }
     }
 
-    pub fn surface_size(&self) -> IVec3 {
-        let mut min = IVec2::ZERO;
-        let mut max = IVec2::ZERO;
-
-        for chunk in self.chunks.keys() {
-            min = min.min(*chunk);
-            max = max.max(*chunk);
-        }
+    /// Returns the smallest and the largest origins of loaded chunks along
+    /// each axis, or zeros if there are no chunks.
+    fn origin_bounds(&self) -> (IVec2, IVec2) {
+        self.chunks
+            .keys()
+            .fold(None, |bounds: Option<(IVec2, IVec2)>, &origin| {
+                Some(bounds.map_or((origin, origin), |(min, max)| {
+                    (min.min(origin), max.max(origin))
+                }))
+            })
+            .unwrap_or_default()
+    }
 
+    pub fn surface_size(&self) -> IVec3 {
+        let (min, max) = self.origin_bounds();
         let size = (max - min) * 16;
+        let height = self.chunks.values().map(Chunk::height).max().unwrap_or(0);
 
         IVec3::new(
             size.x + CHUNK_SIZE_I32,
-            CHUNK_SIZE_I32 * SUBCHUNK_COUNT_I32,
+            i32::from(height),
             size.y + CHUNK_SIZE_I32,
         )
     }
 
     pub fn bounds(&self) -> (IVec2, IVec2) {
-        let mut min = IVec2::ZERO;
-        let mut max = IVec2::ZERO;
+        let (min, max) = self.origin_bounds();
+
+        (min * CHUNK_SIZE_I32, max * CHUNK_SIZE_I32)
+    }
+
+    /// Builds a top-down grayscale image of all loaded chunks, where every
+    /// pixel is the surface height of the corresponding column.
+    pub fn heightmap(&self) -> GrayImage {
+        let (min, max) = self.bounds();
+        let size = (max - min + CHUNK_SIZE_I32).as_uvec2();
+
+        GrayImage::from_fn(size.x, size.y, |x, z| {
+            let position = (min + IVec2::new(x as i32, z as i32)).as_vec2();
+            let position = Vec3::new(position.x, 0.0, position.y);
+            let height = self
+                .get_chunk(&coords::world_to_chunk(position))
+                .and_then(|chunk| {
+                    let local = coords::world_to_local(position);
+
+                    chunk.surface_height(local.x, local.z)
+                })
+                .unwrap_or_default();
+
+            Luma([u8::try_from(height).unwrap_or(u8::MAX)])
+        })
+    }
+
+    pub fn export_heightmap<P: AsRef<Path>>(&self, path: P) -> ImageResult<()> {
+        self.heightmap().save(path)
+    }
+
+    /// Returns path of the file the chunk with the given origin is saved to.
+    fn chunk_path(directory: &Path, origin: IVec2) -> PathBuf {
+        directory.join(format!("{}_{}.bin", origin.x, origin.y))
+    }
 
-        for chunk in self.chunks.keys() {
-            min = min.min(*chunk);
-            max = max.max(*chunk);
+    /// Writes the chunk into its own file inside of the directory.
+    pub fn save_chunk(directory: &Path, chunk: &Chunk) -> io::Result<()> {
+        fs::write(Self::chunk_path(directory, chunk.origin), chunk.serialize())
+    }
+
+    /// Writes every chunk into its own file inside of the directory, creating
+    /// the directory if it doesn't exist.
+    pub fn save_to_dir(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)?;
+
+        for chunk in self.chunks() {
+            Self::save_chunk(path, chunk)?;
         }
 
-        (min * CHUNK_SIZE_I32, max * CHUNK_SIZE_I32)
+        Ok(())
+    }
+
+    /// Returns whether the chunk with the given origin has a file inside of
+    /// the directory.
+    pub fn is_saved(directory: &Path, origin: IVec2) -> bool {
+        Self::chunk_path(directory, origin).is_file()
+    }
+
+    /// Reads the chunk with the given origin from its file inside of the
+    /// directory, returning [`None`] if it was never saved.
+    pub fn load_chunk(directory: &Path, origin: IVec2) -> io::Result<Option<Chunk>> {
+        match fs::read(Self::chunk_path(directory, origin)) {
+            Ok(data) => Chunk::deserialize(data).map(Some),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Reads every chunk saved into the directory by
+    /// [`ChunkManager::save_to_dir`]. Chunks without files are left
+    /// ungenerated, so a missing directory results in an empty manager.
+    pub fn load_from_dir(path: &Path) -> io::Result<Self> {
+        let mut chunk_manager = Self {
+            chunks: HashMap::new(),
+            dirty: HashSet::new(),
+        };
+
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(chunk_manager),
+            Err(error) => return Err(error),
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+
+            if path.extension().is_some_and(|extension| extension == "bin") {
+                let chunk = Chunk::deserialize(fs::read(path)?)?;
+
+                chunk_manager.chunks.insert(chunk.origin, chunk);
+            }
+        }
+
+        Ok(chunk_manager)
+    }
+
+    /// Returns origins of the chunk containing the position and of
+    /// neighbouring chunks when the position lies on their seam, since their
+    /// meshes depend on blocks next to them for culling, light and ambient
+    /// occlusion. Chunks span the whole height of the world, so positions on
+    /// edges of subchunks only affect their own chunk.
+    pub fn chunks_affected_by(&self, position: Vec3) -> Vec<IVec2> {
+        let origin = Self::to_local(position);
+        let local = position
+            .floor()
+            .as_ivec3()
+            .rem_euclid(IVec3::splat(CHUNK_SIZE_I32));
+        let offsets = |value: i32| match value {
+            0 => &[0, -1][..],
+            value if value == CHUNK_SIZE_I32 - 1 => &[0, 1],
+            _ => &[0],
+        };
+
+        offsets(local.x)
+            .iter()
+            .flat_map(|&x| {
+                offsets(local.z)
+                    .iter()
+                    .map(move |&z| origin + IVec2::new(x, z))
+            })
+            .filter(|origin| self.contains_chunk(origin))
+            .collect()
+    }
+
+    /// Marks chunks affected by the change at the position as changed.
+    fn mark_dirty(&mut self, position: Vec3) {
+        let chunks = self.chunks_affected_by(position);
+
+        self.dirty.extend(chunks);
+    }
+
+    /// Checks whether the chunk was changed since it was last taken by
+    /// [`ChunkManager::take_dirty`].
+    pub fn is_dirty(&self, origin: &IVec2) -> bool {
+        self.dirty.contains(origin)
+    }
+
+    /// Returns origins of chunks changed since the last call, clearing them.
+    pub fn take_dirty(&mut self) -> HashSet<IVec2> {
+        mem::take(&mut self.dirty)
     }
 
     pub fn to_local(position: Vec3) -> IVec2 {
-        IVec2::new(
-            position.x.floor() as i32 >> 4,
-            position.z.floor() as i32 >> 4,
-        )
+        coords::world_to_chunk(position)
     }
 
     pub fn to_chunk_local(&self, position: Vec3) -> Option<U16Vec3> {
-        self.get_chunk(&Self::to_local(position))
+        self.get_chunk_at(position)
             .map(|chunk| chunk.to_local(position))
     }
 
+    /// Returns chunk containing the given world position. Unlike looking the
+    /// chunk up by [`ChunkManager::to_local`], positions below or above the
+    /// chunk are rejected, as [`Chunk::to_local`] would clamp them into it.
+    fn get_chunk_at(&self, position: Vec3) -> Option<&Chunk> {
+        self.get_chunk(&Self::to_local(position))
+            .filter(|chunk| chunk.contains_position(position))
+    }
+
+    fn get_chunk_at_mut(&mut self, position: Vec3) -> Option<&mut Chunk> {
+        self.get_chunk_mut(&Self::to_local(position))
+            .filter(|chunk| chunk.contains_position(position))
+    }
+
     pub fn get_chunk(&self, position: &IVec2) -> Option<&Chunk> {
         self.chunks.get(position)
     }
@@ -103,26 +343,44 @@ impl ChunkManager {
     }
 
     pub fn get_block(&self, position: Vec3) -> Option<u8> {
-        let chunk = self.get_chunk(&Self::to_local(position))?;
+        let chunk = self.get_chunk_at(position)?;
 
         chunk.get_block(chunk.to_local(position))
     }
 
-    pub fn set_block(&mut self, position: Vec3, block: u8) {
-        if let Some(chunk) = self.get_chunk_mut(&Self::to_local(position)) {
-            chunk.set_block(chunk.to_local(position), block);
-        }
+    /// Sets the block at the position, returning whether a loaded chunk
+    /// contains it.
+    pub fn set_block(&mut self, position: Vec3, block: u8) -> bool {
+        self.replace_block(position, block).is_some()
+    }
+
+    /// Sets the block at the position, returning the block it replaced, or
+    /// [`None`] if no loaded chunk contains the position.
+    pub fn replace_block(&mut self, position: Vec3, block: u8) -> Option<u8> {
+        let chunk = self.get_chunk_at_mut(position)?;
+        let local = chunk.to_local(position);
+        let previous = chunk.get_block(local).unwrap_or(AIR_BLOCK);
+
+        chunk.set_block(local, block);
+
+        self.mark_dirty(position);
+
+        Some(previous)
     }
 
     pub fn set_block_light(&mut self, position: Vec3, light_level: u8) {
-        if let Some(chunk) = self.get_chunk_mut(&Self::to_local(position)) {
+        if let Some(chunk) = self.get_chunk_at_mut(position) {
             chunk.set_block_light(chunk.to_local(position), light_level);
+
+            self.mark_dirty(position);
         }
     }
 
     pub fn set_sky_light(&mut self, position: Vec3, light_level: u8) {
-        if let Some(chunk) = self.get_chunk_mut(&Self::to_local(position)) {
+        if let Some(chunk) = self.get_chunk_at_mut(position) {
             chunk.set_sky_light(chunk.to_local(position), light_level);
+
+            self.mark_dirty(position);
         }
     }
 
@@ -136,42 +394,26 @@ impl ChunkManager {
     }
 
     pub fn get_block_light(&self, position: Vec3) -> u8 {
-        self.get_chunk(&Self::to_local(position))
-            .map_or(15, |chunk| {
-                let local_position = chunk.to_local(position);
-
-                if chunk.contains_local_position(local_position) {
-                    chunk.get_block_light(local_position)
-                } else {
-                    15
-                }
-            })
+        self.get_chunk_at(position)
+            .map_or(15, |chunk| chunk.get_block_light(chunk.to_local(position)))
     }
 
     pub fn get_sky_light(&self, position: Vec3) -> u8 {
-        self.get_chunk(&Self::to_local(position))
-            .map_or(15, |chunk| {
-                let local_position = chunk.to_local(position);
-
-                if chunk.contains_local_position(local_position) {
-                    chunk.get_sky_light(local_position)
-                } else {
-                    15
-                }
-            })
+        self.get_chunk_at(position)
+            .map_or(15, |chunk| chunk.get_sky_light(chunk.to_local(position)))
     }
 
     pub fn get_light(&self, position: Vec3) -> u8 {
-        self.get_chunk(&Self::to_local(position))
-            .map_or(240, |chunk| {
-                let local_position = chunk.to_local(position);
+        self.get_chunk_at(position)
+            .map_or(240, |chunk| chunk.get_light_level(chunk.to_local(position)))
+    }
 
-                if chunk.contains_local_position(local_position) {
-                    chunk.get_light_level(local_position)
-                } else {
-                    240
-                }
-            })
+    /// Returns sky and block light levels at the given position, defaulting to
+    /// full sky light outside of loaded chunks.
+    pub fn get_light_split(&self, position: Vec3) -> (u8, u8) {
+        let light = self.get_light(position);
+
+        (light >> 4, light & 0xF)
     }
 
     pub fn len(&self) -> usize {
@@ -190,3 +432,344 @@ impl ChunkManager {
         self.chunks.values_mut()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, env, fs};
+
+    use glam::{IVec2, IVec3, U16Vec3, Vec3};
+
+    use super::ChunkManager;
+    use crate::CHUNK_SIZE_U16;
+
+    #[test]
+    fn test_heightmap() {
+        let mut chunk_manager = ChunkManager::from_range(-1..2, &(0..2));
+
+        if let Some(chunk) = chunk_manager.get_chunk_mut(&IVec2::new(-1, 1)) {
+            for z in 0..CHUNK_SIZE_U16 {
+                for x in 0..CHUNK_SIZE_U16 {
+                    chunk.set_block(U16Vec3::new(x, 64, z), 1);
+                }
+            }
+        }
+
+        let heightmap = chunk_manager.heightmap();
+        let size = u32::from(CHUNK_SIZE_U16);
+
+        assert_eq!(heightmap.dimensions(), (size * 3, size * 2));
+
+        for z in 0..size {
+            for x in 0..size {
+                assert_eq!(heightmap.get_pixel(x, size + z).0, [64]);
+                assert_eq!(heightmap.get_pixel(size + x, z).0, [0]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_negative_coordinates() {
+        let mut chunk_manager = ChunkManager::from_range(-2..1, &(-2..1));
+
+        for (position, origin, local) in [
+            (
+                Vec3::new(-1.0, 5.0, -1.0),
+                IVec2::new(-1, -1),
+                U16Vec3::new(15, 5, 15),
+            ),
+            (
+                Vec3::new(-0.5, 5.0, -0.5),
+                IVec2::new(-1, -1),
+                U16Vec3::new(15, 5, 15),
+            ),
+            (
+                Vec3::new(-16.0, 5.0, -16.0),
+                IVec2::new(-1, -1),
+                U16Vec3::new(0, 5, 0),
+            ),
+            (
+                Vec3::new(-17.0, 5.0, 0.0),
+                IVec2::new(-2, 0),
+                U16Vec3::new(15, 5, 0),
+            ),
+            (
+                Vec3::new(0.0, 5.0, -17.0),
+                IVec2::new(0, -2),
+                U16Vec3::new(0, 5, 15),
+            ),
+        ] {
+            assert_eq!(ChunkManager::to_local(position), origin);
+            assert_eq!(chunk_manager.to_chunk_local(position), Some(local));
+
+            let chunk = chunk_manager.get_chunk(&origin).unwrap();
+
+            assert!(chunk.contains_position(position));
+            assert_eq!(chunk.to_world(local), position.floor().as_ivec3());
+
+            chunk_manager.set_block(position, 1);
+
+            assert_eq!(chunk_manager.get_block(position), Some(1));
+            assert_eq!(
+                chunk_manager.get_chunk(&origin).unwrap().get_block(local),
+                Some(1)
+            );
+            assert_eq!(
+                chunk_manager
+                    .chunks()
+                    .filter(|chunk| chunk.get_block(local).is_some())
+                    .count(),
+                1
+            );
+
+            chunk_manager.set_block(position, 0);
+        }
+    }
+
+    #[test]
+    fn test_out_of_height_positions() {
+        let mut chunk_manager = ChunkManager::from_range(-1..1, &(-1..1));
+        let height = f32::from(chunk_manager.get_chunk(&IVec2::ZERO).unwrap().height());
+
+        chunk_manager.set_block(Vec3::new(-1.0, 0.0, -1.0), 1);
+        chunk_manager.set_block(Vec3::new(-1.0, -1.0, -1.0), 2);
+        chunk_manager.set_block(Vec3::new(-1.0, height, -1.0), 2);
+        chunk_manager.set_block_light(Vec3::new(-1.0, -0.5, -1.0), 7);
+
+        assert_eq!(chunk_manager.get_block(Vec3::new(-1.0, 0.0, -1.0)), Some(1));
+        assert_eq!(chunk_manager.get_block(Vec3::new(-1.0, -0.5, -1.0)), None);
+        assert_eq!(chunk_manager.get_block(Vec3::new(-1.0, height, -1.0)), None);
+        assert_eq!(chunk_manager.get_block_light(Vec3::new(-1.0, 0.0, -1.0)), 0);
+        assert_eq!(
+            chunk_manager.get_block_light(Vec3::new(-1.0, -0.5, -1.0)),
+            15
+        );
+        assert_eq!(
+            chunk_manager.to_chunk_local(Vec3::new(-1.0, -1.0, -1.0)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_light_split() {
+        let mut chunk_manager = ChunkManager::from_range(0..1, &(0..1));
+
+        for (position, sky_light, block_light) in [
+            (Vec3::new(1.0, 10.0, 1.0), 15, 0),
+            (Vec3::new(2.0, 10.0, 1.0), 3, 14),
+            (Vec3::new(3.0, 10.0, 1.0), 0, 7),
+        ] {
+            chunk_manager.set_sky_light(position, sky_light);
+            chunk_manager.set_block_light(position, block_light);
+
+            let light = chunk_manager.get_light(position);
+
+            assert_eq!(
+                chunk_manager.get_light_split(position),
+                (light >> 4, light & 0xF)
+            );
+            assert_eq!(
+                chunk_manager.get_light_split(position),
+                (sky_light, block_light)
+            );
+        }
+
+        assert_eq!(
+            chunk_manager.get_light_split(Vec3::new(-1.0, 10.0, 1.0)),
+            (15, 0)
+        );
+    }
+
+    #[test]
+    fn test_chunks_affected_by() {
+        let chunk_manager = ChunkManager::from_range(-1..2, &(-1..2));
+
+        assert_eq!(
+            chunk_manager.chunks_affected_by(Vec3::new(5.0, 64.0, 5.0)),
+            [IVec2::ZERO]
+        );
+        // edges of subchunks don't matter
+        assert_eq!(
+            chunk_manager.chunks_affected_by(Vec3::new(5.0, 16.0, 5.0)),
+            [IVec2::ZERO]
+        );
+        assert_eq!(
+            chunk_manager.chunks_affected_by(Vec3::new(15.0, 64.0, 5.0)),
+            [IVec2::ZERO, IVec2::X]
+        );
+
+        // both neighbours are affected in corners, along with the diagonal
+        // one sharing ambient occlusion
+        assert_eq!(
+            chunk_manager.chunks_affected_by(Vec3::new(0.0, 64.0, 15.0)),
+            [IVec2::ZERO, IVec2::Y, IVec2::NEG_X, IVec2::new(-1, 1)]
+        );
+        // chunks outside of the world are skipped
+        assert_eq!(
+            chunk_manager.chunks_affected_by(Vec3::new(-1.0, 64.0, -16.0)),
+            [IVec2::NEG_ONE, IVec2::new(0, -1)]
+        );
+    }
+
+    #[test]
+    fn test_dirty_chunks() {
+        let mut chunk_manager = ChunkManager::from_range(-1..2, &(-1..2));
+
+        chunk_manager.set_block(Vec3::new(5.0, 64.0, 5.0), 1);
+
+        assert_eq!(chunk_manager.take_dirty(), HashSet::from([IVec2::ZERO]));
+        assert!(chunk_manager.take_dirty().is_empty());
+
+        // light changes are tracked as well
+        chunk_manager.set_sky_light(Vec3::new(20.0, 64.0, 5.0), 4);
+
+        assert_eq!(chunk_manager.take_dirty(), HashSet::from([IVec2::X]));
+
+        // blocks on a seam affect meshes of chunks next to them
+        chunk_manager.set_block(Vec3::new(15.0, 64.0, 7.0), 1);
+
+        assert_eq!(
+            chunk_manager.take_dirty(),
+            HashSet::from([IVec2::ZERO, IVec2::X])
+        );
+
+        chunk_manager.set_block(Vec3::new(0.0, 64.0, 0.0), 1);
+
+        assert_eq!(
+            chunk_manager.take_dirty(),
+            HashSet::from([IVec2::NEG_X, IVec2::new(-1, -1), IVec2::NEG_Y, IVec2::ZERO])
+        );
+
+        // chunks outside of the world aren't marked
+        chunk_manager.set_block(Vec3::new(31.0, 64.0, 31.0), 1);
+
+        assert_eq!(chunk_manager.take_dirty(), HashSet::from([IVec2::ONE]));
+    }
+
+    #[test]
+    fn test_streaming() {
+        let mut chunk_manager = ChunkManager::from_range(0..0, &(0..0));
+        let square = |center: IVec2| {
+            (-1..=1)
+                .flat_map(|z| (-1..=1).map(move |x| center + IVec2::new(x, z)))
+                .collect::<HashSet<_>>()
+        };
+        let origins = |chunk_manager: &ChunkManager| {
+            chunk_manager
+                .chunks()
+                .map(|chunk| chunk.origin)
+                .collect::<HashSet<_>>()
+        };
+
+        let loaded = chunk_manager.ensure_loaded(IVec2::ZERO, 1, 0);
+
+        assert_eq!(
+            loaded.into_iter().collect::<HashSet<_>>(),
+            square(IVec2::ZERO)
+        );
+
+        chunk_manager.set_block(Vec3::new(-10.0, 64.0, 5.0), 1);
+
+        // only chunks that weren't there yet are generated
+        let center = IVec2::new(2, 0);
+        let loaded = chunk_manager.ensure_loaded(center, 1, 0);
+
+        assert_eq!(loaded.len(), 6);
+        assert!(loaded.iter().all(|origin| origin.x > 1));
+
+        let unloaded = chunk_manager.unload_beyond(center, 1);
+
+        assert_eq!(
+            unloaded.into_iter().collect::<HashSet<_>>(),
+            square(IVec2::ZERO)
+                .into_iter()
+                .filter(|origin| origin.x < 1)
+                .collect()
+        );
+        assert_eq!(origins(&chunk_manager), square(center));
+        // changes of unloaded chunks aren't tracked anymore
+        assert!(chunk_manager.take_dirty().is_empty());
+    }
+
+    #[test]
+    fn test_save_and_load_dir() {
+        let directory = env::temp_dir().join(format!("meralus-chunks-{}", std::process::id()));
+        let mut chunk_manager = ChunkManager::from_range(-1..1, &(0..1));
+
+        chunk_manager.generate_flat(4);
+        chunk_manager.set_block(Vec3::new(-3.0, 10.0, 5.0), 2);
+        chunk_manager.set_block(Vec3::new(7.0, 2.0, 1.0), 0);
+        chunk_manager.save_to_dir(&directory).unwrap();
+
+        let loaded = ChunkManager::load_from_dir(&directory).unwrap();
+
+        assert_eq!(loaded.len(), chunk_manager.len());
+
+        for chunk in chunk_manager.chunks() {
+            assert_eq!(loaded.get_chunk(&chunk.origin), Some(chunk));
+        }
+
+        assert_eq!(loaded.get_block(Vec3::new(-3.0, 10.0, 5.0)), Some(2));
+
+        // chunks without files stay ungenerated
+        fs::remove_file(directory.join("0_0.bin")).unwrap();
+
+        let loaded = ChunkManager::load_from_dir(&directory).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert!(!loaded.contains_chunk(&IVec2::ZERO));
+        assert!(
+            ChunkManager::load_chunk(&directory, IVec2::ZERO)
+                .unwrap()
+                .is_none()
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert!(ChunkManager::load_from_dir(&directory).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_set_block_result() {
+        let mut chunk_manager = ChunkManager::from_range(0..2, &(0..1));
+        let position = Vec3::new(20.0, 64.0, 3.0);
+
+        assert!(chunk_manager.set_block(position, 1));
+        assert_eq!(chunk_manager.replace_block(position, 2), Some(1));
+        assert_eq!(chunk_manager.replace_block(position, 0), Some(2));
+        assert_eq!(chunk_manager.replace_block(position, 0), Some(0));
+        assert!(chunk_manager.take_dirty().contains(&IVec2::new(1, 0)));
+
+        // outside of loaded chunks and the world height
+        for position in [
+            Vec3::new(-1.0, 64.0, 3.0),
+            Vec3::new(40.0, 64.0, 3.0),
+            Vec3::new(3.0, -1.0, 3.0),
+            Vec3::new(3.0, 256.0, 3.0),
+        ] {
+            assert!(!chunk_manager.set_block(position, 1));
+            assert_eq!(chunk_manager.replace_block(position, 1), None);
+            assert_eq!(chunk_manager.get_block(position), None);
+        }
+
+        assert!(chunk_manager.take_dirty().is_empty());
+    }
+
+    #[test]
+    fn test_negative_bounds() {
+        let mut chunk_manager = ChunkManager::from_range(-3..-1, &(-4..-3));
+
+        assert_eq!(
+            chunk_manager.bounds(),
+            (IVec2::new(-48, -64), IVec2::new(-32, -64))
+        );
+        assert_eq!(chunk_manager.surface_size(), IVec3::new(32, 256, 16));
+
+        chunk_manager.set_block(Vec3::new(-40.0, 70.0, -60.0), 1);
+
+        let heightmap = chunk_manager.heightmap();
+
+        assert_eq!(heightmap.dimensions(), (32, 16));
+        assert_eq!(heightmap.get_pixel(8, 4).0, [70]);
+        assert_eq!(heightmap.get_pixel(9, 4).0, [0]);
+    }
+}
@@ -0,0 +1,149 @@
+//! Conversions between world, chunk and chunk-local coordinates.
+//!
+//! Block at world position `P` belongs to chunk `floor(P.xz / CHUNK_SIZE)`,
+//! including negative coordinates, so every conversion floors first and then
+//! uses Euclidean division.
+
+use glam::{IVec2, IVec3, U16Vec3, Vec3};
+
+use crate::{CHUNK_SIZE, CHUNK_SIZE_I32};
+
+/// Returns origin of the chunk containing the given world position.
+pub fn world_to_chunk(position: Vec3) -> IVec2 {
+    let position = position.floor().as_ivec3();
+
+    IVec2::new(
+        position.x.div_euclid(CHUNK_SIZE_I32),
+        position.z.div_euclid(CHUNK_SIZE_I32),
+    )
+}
+
+/// Returns position of the block local to the chunk containing it.
+///
+/// Height is not checked against the chunk, so positions below the world
+/// are clamped to `0`.
+pub fn world_to_local(position: Vec3) -> U16Vec3 {
+    let position = position.floor();
+
+    U16Vec3::new(
+        (position.x as i32).rem_euclid(CHUNK_SIZE_I32) as u16,
+        position.y as u16,
+        (position.z as i32).rem_euclid(CHUNK_SIZE_I32) as u16,
+    )
+}
+
+/// Returns world position of the block with the given chunk-local position.
+pub const fn local_to_world(origin: IVec2, position: U16Vec3) -> IVec3 {
+    let origin = chunk_to_world(origin);
+
+    IVec3::new(
+        origin.x + position.x as i32,
+        position.y as i32,
+        origin.z + position.z as i32,
+    )
+}
+
+/// Returns world position of the bottom corner of the chunk.
+pub const fn chunk_to_world(origin: IVec2) -> IVec3 {
+    IVec3::new(origin.x * CHUNK_SIZE_I32, 0, origin.y * CHUNK_SIZE_I32)
+}
+
+/// Returns index of the subchunk containing the given world height, which is
+/// negative below the world.
+pub const fn world_to_subchunk(y: f32) -> i32 {
+    (y.floor() as i32).div_euclid(CHUNK_SIZE_I32)
+}
+
+/// Splits chunk-local height into subchunk index and height inside of it.
+pub const fn subchunk_index(y: usize) -> [usize; 2] {
+    [y / CHUNK_SIZE, y % CHUNK_SIZE]
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{IVec2, IVec3, U16Vec3, Vec3};
+
+    use super::{
+        chunk_to_world, local_to_world, subchunk_index, world_to_chunk, world_to_local,
+        world_to_subchunk,
+    };
+
+    #[test]
+    fn test_world_to_chunk() {
+        for (position, origin, local) in [
+            (
+                Vec3::new(0.0, 0.0, 0.0),
+                IVec2::new(0, 0),
+                U16Vec3::new(0, 0, 0),
+            ),
+            (
+                Vec3::new(15.9, 1.5, 15.9),
+                IVec2::new(0, 0),
+                U16Vec3::new(15, 1, 15),
+            ),
+            (
+                Vec3::new(16.0, 1.0, 16.0),
+                IVec2::new(1, 1),
+                U16Vec3::new(0, 1, 0),
+            ),
+            (
+                Vec3::new(-0.1, 1.0, -0.1),
+                IVec2::new(-1, -1),
+                U16Vec3::new(15, 1, 15),
+            ),
+            (
+                Vec3::new(-1.0, 1.0, -1.0),
+                IVec2::new(-1, -1),
+                U16Vec3::new(15, 1, 15),
+            ),
+            (
+                Vec3::new(-16.0, 1.0, -16.0),
+                IVec2::new(-1, -1),
+                U16Vec3::new(0, 1, 0),
+            ),
+            (
+                Vec3::new(-16.5, 1.0, -16.5),
+                IVec2::new(-2, -2),
+                U16Vec3::new(15, 1, 15),
+            ),
+            (
+                Vec3::new(-17.0, 1.0, 0.0),
+                IVec2::new(-2, 0),
+                U16Vec3::new(15, 1, 0),
+            ),
+        ] {
+            assert_eq!(world_to_chunk(position), origin, "{position}");
+            assert_eq!(world_to_local(position), local, "{position}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for z in -40..40 {
+            for x in -40..40 {
+                for offset in [0.0, 0.25, 0.999] {
+                    let position = Vec3::new(x as f32 + offset, 7.5, z as f32 + offset);
+                    let origin = world_to_chunk(position);
+                    let local = world_to_local(position);
+
+                    assert!(local.x < 16 && local.z < 16);
+                    assert_eq!(origin, IVec2::new(x, z) >> 4);
+                    assert_eq!(local_to_world(origin, local), IVec3::new(x, 7, z));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_subchunks() {
+        assert_eq!(chunk_to_world(IVec2::new(-2, 3)), IVec3::new(-32, 0, 48));
+        assert_eq!(subchunk_index(0), [0, 0]);
+        assert_eq!(subchunk_index(15), [0, 15]);
+        assert_eq!(subchunk_index(16), [1, 0]);
+        assert_eq!(subchunk_index(255), [15, 15]);
+        assert_eq!(world_to_subchunk(0.0), 0);
+        assert_eq!(world_to_subchunk(31.9), 1);
+        assert_eq!(world_to_subchunk(-0.5), -1);
+        assert_eq!(world_to_subchunk(-16.5), -2);
+    }
+}
@@ -0,0 +1,131 @@
+/// Maximum number of items in a single slot.
+pub const STACK_SIZE: u8 = 64;
+
+/// Items occupying a single inventory slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ItemStack {
+    /// Id of the block the item places.
+    pub item: u8,
+    pub count: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+}
+
+impl Inventory {
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: vec![None; size],
+        }
+    }
+
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    /// Returns the total number of the items across all slots.
+    pub fn count(&self, item: u8) -> usize {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == item)
+            .map(|stack| usize::from(stack.count))
+            .sum()
+    }
+
+    /// Removes items, emptying slots that run out of them. Nothing is
+    /// removed if there are not enough items, which is indicated by returning
+    /// `false`.
+    pub fn remove(&mut self, item: u8, count: usize) -> bool {
+        if self.count(item) < count {
+            return false;
+        }
+
+        let mut count = count;
+
+        for slot in self.slots.iter_mut().rev() {
+            if count == 0 {
+                break;
+            }
+
+            if let Some(stack) = slot
+                && stack.item == item
+            {
+                let removed = stack.count.min(u8::try_from(count).unwrap_or(u8::MAX));
+
+                stack.count -= removed;
+                count -= usize::from(removed);
+
+                if stack.count == 0 {
+                    *slot = None;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Adds items, topping up stacks of the same item before taking empty
+    /// slots. Returns the number of items that didn't fit.
+    pub fn add(&mut self, item: u8, mut count: u8) -> u8 {
+        for stack in self.slots.iter_mut().flatten() {
+            if stack.item == item {
+                let added = count.min(STACK_SIZE - stack.count);
+
+                stack.count += added;
+                count -= added;
+            }
+        }
+
+        for slot in &mut self.slots {
+            if count == 0 {
+                break;
+            }
+
+            if slot.is_none() {
+                let added = count.min(STACK_SIZE);
+
+                *slot = Some(ItemStack { item, count: added });
+                count -= added;
+            }
+        }
+
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Inventory, ItemStack, STACK_SIZE};
+
+    #[test]
+    fn test_stacking() {
+        let mut inventory = Inventory::new(2);
+
+        assert_eq!(inventory.add(1, STACK_SIZE - 1), 0);
+        assert_eq!(inventory.add(1, 2), 0);
+        assert_eq!(inventory.slots(), [
+            Some(ItemStack {
+                item: 1,
+                count: STACK_SIZE
+            }),
+            Some(ItemStack { item: 1, count: 1 })
+        ]);
+
+        // no free slots are left for another item
+        assert_eq!(inventory.add(2, 3), 3);
+        assert_eq!(inventory.count(1), usize::from(STACK_SIZE) + 1);
+
+        assert!(!inventory.remove(1, usize::from(STACK_SIZE) + 2));
+        assert!(inventory.remove(1, 2));
+        assert_eq!(inventory.slots(), [
+            Some(ItemStack {
+                item: 1,
+                count: STACK_SIZE - 1
+            }),
+            None
+        ]);
+    }
+}
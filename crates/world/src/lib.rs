@@ -9,20 +9,32 @@
 
 mod block;
 mod chunk;
+mod chunk_cache;
 mod chunk_manager;
+pub mod coords;
+mod inventory;
+mod recipe;
 
 pub use serde_json::Error as JsonError;
 
 pub use self::{
     block::{
-        Axis, BlockCondition, BlockElement, BlockFace, BlockModel, BlockState, BlockStates,
-        ConditionValue, Corner, ElementRotation, Face, Faces, Property, PropertyValue, TextureId,
-        TexturePath, TextureRef,
+        Axis, BlockCondition, BlockElement, BlockFace, BlockManifest, BlockModel, BlockState,
+        BlockStates, ConditionValue, Corner, ElementRotation, Face, Faces, ModelProblem, Property,
+        PropertyValue, TextureId, TexturePath, TextureRef,
     },
     chunk::{
-        CHUNK_HEIGHT, CHUNK_HEIGHT_F32, CHUNK_HEIGHT_F64, CHUNK_HEIGHT_I32, CHUNK_HEIGHT_U16,
-        CHUNK_SIZE, CHUNK_SIZE_F32, CHUNK_SIZE_F64, CHUNK_SIZE_I32, CHUNK_SIZE_U16, Chunk,
-        SUBCHUNK_COUNT, SUBCHUNK_COUNT_F32, SUBCHUNK_COUNT_I32, SUBCHUNK_COUNT_U16, SubChunk,
+        AIR_BLOCK, CAVE_THRESHOLD, CHUNK_HEIGHT, CHUNK_HEIGHT_F32, CHUNK_HEIGHT_F64,
+        CHUNK_HEIGHT_I32, CHUNK_HEIGHT_U16, CHUNK_SIZE, CHUNK_SIZE_F32, CHUNK_SIZE_F64,
+        CHUNK_SIZE_I32, CHUNK_SIZE_U16, Chunk, ChunkSummary, SEA_LEVEL, SUBCHUNK_COUNT,
+        SUBCHUNK_COUNT_F32, SUBCHUNK_COUNT_I32, SUBCHUNK_COUNT_U16, SubChunk, TerrainSettings,
+        WATER_BLOCK,
     },
+    chunk_cache::ChunkCache,
     chunk_manager::ChunkManager,
+    inventory::{Inventory, ItemStack, STACK_SIZE},
+    recipe::{
+        CraftingGrid, GRID_SIZE, Recipe, RecipeDefinition, RecipeError, RecipeRegistry,
+        RecipeResult,
+    },
 };
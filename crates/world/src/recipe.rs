@@ -0,0 +1,399 @@
+use std::{collections::HashMap, ffi::OsStr, fmt, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BlockManifest, Inventory, ItemStack};
+
+/// Side of the square crafting grid.
+pub const GRID_SIZE: usize = 3;
+
+/// Items placed in the crafting grid row by row, where [`None`] is an empty
+/// cell.
+pub type CraftingGrid = [Option<u8>; GRID_SIZE * GRID_SIZE];
+
+/// Recipe as it's written in JSON, referring to items by block names.
+///
+/// Shaped recipes are written as rows of the pattern, where every character
+/// is an item from `key` and a space is an empty cell:
+///
+/// ```json
+/// {
+///     "type": "shaped",
+///     "pattern": ["##", "##"],
+///     "key": { "#": "dirt" },
+///     "result": { "item": "grass_block" }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RecipeDefinition {
+    Shaped {
+        pattern: Vec<String>,
+        key: HashMap<char, String>,
+        result: RecipeResult,
+    },
+    Shapeless {
+        ingredients: Vec<String>,
+        result: RecipeResult,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RecipeResult {
+    pub item: String,
+    #[serde(default = "RecipeResult::default_count")]
+    pub count: u8,
+}
+
+impl RecipeResult {
+    const fn default_count() -> u8 {
+        1
+    }
+}
+
+#[derive(Debug)]
+pub enum RecipeError {
+    Io(io::Error),
+    ParsingFailed(serde_json::Error),
+    /// Item isn't declared in the block manifest.
+    UnknownItem(String),
+    /// Character of a shaped recipe pattern is missing from its key.
+    UnknownKey(char),
+    /// Recipe doesn't fit into the crafting grid or has no ingredients.
+    InvalidSize,
+}
+
+impl fmt::Display for RecipeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "failed to read recipe: {error}"),
+            Self::ParsingFailed(error) => write!(f, "failed to parse recipe: {error}"),
+            Self::UnknownItem(item) => write!(f, "`{item}` is not declared in the manifest"),
+            Self::UnknownKey(key) => write!(f, "`{key}` is missing from the pattern key"),
+            Self::InvalidSize => write!(
+                f,
+                "recipe must have from 1 to {GRID_SIZE}x{GRID_SIZE} ingredients"
+            ),
+        }
+    }
+}
+
+/// Cells of a pattern trimmed down to its non-empty part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Shape {
+    width: usize,
+    cells: Vec<Option<u8>>,
+}
+
+impl Shape {
+    /// Trims empty rows and columns around the items.
+    fn trimmed(width: usize, cells: &[Option<u8>]) -> Option<Self> {
+        let filled = || {
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| cell.is_some())
+                .map(|(index, _)| (index % width, index / width))
+        };
+
+        let min_x = filled().map(|(x, _)| x).min()?;
+        let max_x = filled().map(|(x, _)| x).max()?;
+        let min_y = filled().map(|(_, y)| y).min()?;
+        let max_y = filled().map(|(_, y)| y).max()?;
+
+        Some(Self {
+            width: max_x - min_x + 1,
+            cells: (min_y..=max_y)
+                .flat_map(|y| (min_x..=max_x).map(move |x| cells[y * width + x]))
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+    ingredients: Ingredients,
+    result: ItemStack,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Ingredients {
+    /// Items must be placed in the grid in the same layout, but anywhere in
+    /// it.
+    Shaped(Shape),
+    /// Items may be placed in the grid in any order, kept sorted.
+    Shapeless(Vec<u8>),
+}
+
+impl Recipe {
+    /// Resolves items of the recipe definition to ids using the manifest.
+    pub fn new(
+        definition: RecipeDefinition,
+        manifest: &BlockManifest,
+    ) -> Result<Self, RecipeError> {
+        let id = |name: &String| {
+            manifest
+                .id(name)
+                .ok_or_else(|| RecipeError::UnknownItem(name.clone()))
+        };
+
+        let (ingredients, result) = match definition {
+            RecipeDefinition::Shaped {
+                pattern,
+                key,
+                result,
+            } => {
+                let width = pattern.iter().map(|row| row.chars().count()).max();
+
+                if pattern.len() > GRID_SIZE || width.is_none_or(|width| width > GRID_SIZE) {
+                    return Err(RecipeError::InvalidSize);
+                }
+
+                let width = width.unwrap_or_default();
+                let mut cells = Vec::with_capacity(width * pattern.len());
+
+                for row in &pattern {
+                    let mut chars = row.chars();
+
+                    for _ in 0..width {
+                        cells.push(match chars.next() {
+                            None | Some(' ') => None,
+                            Some(char) => {
+                                Some(id(key.get(&char).ok_or(RecipeError::UnknownKey(char))?)?)
+                            }
+                        });
+                    }
+                }
+
+                let shape = Shape::trimmed(width, &cells).ok_or(RecipeError::InvalidSize)?;
+
+                (Ingredients::Shaped(shape), result)
+            }
+            RecipeDefinition::Shapeless {
+                ingredients,
+                result,
+            } => {
+                if ingredients.is_empty() || ingredients.len() > GRID_SIZE * GRID_SIZE {
+                    return Err(RecipeError::InvalidSize);
+                }
+
+                let mut ingredients = ingredients.iter().map(id).collect::<Result<Vec<_>, _>>()?;
+
+                ingredients.sort_unstable();
+
+                (Ingredients::Shapeless(ingredients), result)
+            }
+        };
+
+        Ok(Self {
+            ingredients,
+            result: ItemStack {
+                item: id(&result.item)?,
+                count: result.count,
+            },
+        })
+    }
+
+    pub fn from_slice(data: &[u8], manifest: &BlockManifest) -> Result<Self, RecipeError> {
+        Self::new(
+            serde_json::from_slice(data).map_err(RecipeError::ParsingFailed)?,
+            manifest,
+        )
+    }
+
+    pub const fn result(&self) -> ItemStack {
+        self.result
+    }
+
+    pub fn matches(&self, grid: &CraftingGrid) -> bool {
+        match &self.ingredients {
+            Ingredients::Shaped(shape) => Shape::trimmed(GRID_SIZE, grid).as_ref() == Some(shape),
+            Ingredients::Shapeless(ingredients) => {
+                let mut items = grid.iter().flatten().copied().collect::<Vec<_>>();
+
+                items.sort_unstable();
+
+                items == *ingredients
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RecipeRegistry {
+    recipes: Vec<Recipe>,
+}
+
+impl RecipeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads every JSON recipe directly inside of the directory.
+    pub fn load<P: AsRef<Path>>(
+        directory: P,
+        manifest: &BlockManifest,
+    ) -> Result<Self, RecipeError> {
+        let mut registry = Self::new();
+
+        for entry in fs::read_dir(directory).map_err(RecipeError::Io)? {
+            let path = entry.map_err(RecipeError::Io)?.path();
+
+            if path.extension().and_then(OsStr::to_str) == Some("json") {
+                let data = fs::read(path).map_err(RecipeError::Io)?;
+
+                registry.insert(Recipe::from_slice(&data, manifest)?);
+            }
+        }
+
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, recipe: Recipe) {
+        self.recipes.push(recipe);
+    }
+
+    /// Returns the first recipe matching items in the grid.
+    pub fn find(&self, grid: &CraftingGrid) -> Option<&Recipe> {
+        self.recipes.iter().find(|recipe| recipe.matches(grid))
+    }
+
+    /// Crafts the recipe matching items in the grid, consuming one of each
+    /// of them from the inventory and adding the result into it.
+    ///
+    /// Nothing is crafted if there are not enough items in the inventory or
+    /// there's no space for the result.
+    pub fn craft(&self, grid: &CraftingGrid, inventory: &mut Inventory) -> Option<ItemStack> {
+        let result = self.find(grid)?.result();
+        let mut crafted = inventory.clone();
+
+        for item in grid.iter().flatten() {
+            if !crafted.remove(*item, 1) {
+                return None;
+            }
+        }
+
+        if crafted.add(result.item, result.count) > 0 {
+            return None;
+        }
+
+        *inventory = crafted;
+
+        Some(result)
+    }
+
+    pub const fn len(&self) -> usize {
+        self.recipes.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.recipes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CraftingGrid, Recipe, RecipeError, RecipeRegistry};
+    use crate::{BlockManifest, Inventory, ItemStack};
+
+    const DIRT: u8 = 1;
+    const GRASS: u8 = 2;
+    const STONE: u8 = 3;
+
+    fn registry(manifest: &BlockManifest) -> RecipeRegistry {
+        let mut registry = RecipeRegistry::new();
+
+        for recipe in [
+            br#"{
+                "type": "shapeless",
+                "ingredients": ["dirt", "grass_block"],
+                "result": { "item": "grass_block", "count": 2 }
+            }"#
+            .as_slice(),
+            br#"{
+                "type": "shaped",
+                "pattern": ["x", "xx"],
+                "key": { "x": "dirt" },
+                "result": { "item": "stone" }
+            }"#,
+        ] {
+            registry.insert(Recipe::from_slice(recipe, manifest).unwrap());
+        }
+
+        registry
+    }
+
+    fn manifest() -> BlockManifest {
+        BlockManifest::from_slice(br#"{ "1": "dirt", "2": "grass_block", "3": "stone" }"#).unwrap()
+    }
+
+    #[test]
+    fn test_shapeless_recipe() {
+        let registry = registry(&manifest());
+        let mut inventory = Inventory::new(4);
+
+        inventory.add(DIRT, 1);
+        inventory.add(GRASS, 1);
+
+        let mut grid: CraftingGrid = [None; 9];
+
+        grid[8] = Some(DIRT);
+        grid[0] = Some(GRASS);
+
+        assert_eq!(
+            registry.craft(&grid, &mut inventory),
+            Some(ItemStack {
+                item: GRASS,
+                count: 2
+            })
+        );
+        assert_eq!(inventory.count(DIRT), 0);
+        assert_eq!(inventory.count(GRASS), 2);
+
+        // the dirt is used up
+        assert_eq!(registry.craft(&grid, &mut inventory), None);
+        assert_eq!(inventory.count(GRASS), 2);
+    }
+
+    #[test]
+    fn test_shaped_recipe() {
+        let registry = registry(&manifest());
+        let mut inventory = Inventory::new(4);
+
+        inventory.add(DIRT, 6);
+
+        let d = Some(DIRT);
+        let shifted = [None, None, None, None, d, None, None, d, d];
+        let mirrored = [None, d, None, d, d, None, None, None, None];
+
+        assert!(registry.find(&mirrored).is_none());
+        assert_eq!(
+            registry.craft(&shifted, &mut inventory),
+            Some(ItemStack {
+                item: STONE,
+                count: 1
+            })
+        );
+        assert_eq!(inventory.count(DIRT), 3);
+    }
+
+    #[test]
+    fn test_invalid_recipe() {
+        let manifest = manifest();
+
+        assert!(matches!(
+            Recipe::from_slice(
+                br#"{ "type": "shapeless", "ingredients": ["sand"], "result": { "item": "dirt" } }"#,
+                &manifest
+            ),
+            Err(RecipeError::UnknownItem(item)) if item == "sand"
+        ));
+        assert!(matches!(
+            Recipe::from_slice(
+                br#"{ "type": "shaped", "pattern": ["xy"], "key": { "x": "dirt" }, "result": { "item": "dirt" } }"#,
+                &manifest
+            ),
+            Err(RecipeError::UnknownKey('y'))
+        ));
+    }
+}